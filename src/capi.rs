@@ -0,0 +1,348 @@
+// c abi over the editor core, the host measures text and draws the returned values
+use std::{
+    ffi::{c_void, CStr},
+    os::raw::c_char
+};
+
+use crate::{ProgramState, RenderValue, LayoutConfig, TextMeasure, Key, Modifiers};
+
+
+// named keys live above the last valid char so they dont clash with characters
+pub const KEY_BACKSPACE: u32 = 0x110000;
+pub const KEY_DELETE: u32 = 0x110001;
+pub const KEY_RETURN: u32 = 0x110002;
+pub const KEY_TAB: u32 = 0x110003;
+pub const KEY_LEFT: u32 = 0x110004;
+pub const KEY_RIGHT: u32 = 0x110005;
+pub const KEY_UP: u32 = 0x110006;
+pub const KEY_DOWN: u32 = 0x110007;
+pub const KEY_F5: u32 = 0x110008;
+pub const KEY_ESCAPE: u32 = 0x110009;
+
+pub const MODIFIER_CTRL: u32 = 1;
+pub const MODIFIER_SHIFT: u32 = 2;
+
+// measures utf8 text, writing its size into width and height
+pub type MeasureCallback = extern "C" fn(
+    user_data: *mut c_void,
+    text: *const u8,
+    length: usize,
+    width: *mut u32,
+    height: *mut u32
+);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatheditorKind
+{
+    Text,
+    Line,
+    Dots,
+    Unit,
+    Cursor,
+    Highlight,
+    Placeholder,
+    Panel,
+    // root sign over the first height / 2 of the width, the rest is the bar along the top
+    Radical,
+    // text is the bracket character to stretch over the whole height
+    Bracket
+}
+
+// text points into the editor and stays valid until the next render call
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MatheditorValue
+{
+    pub kind: MatheditorKind,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub thickness: f32,
+    // 0xrrggbb, set for the cursor and colored values, 0 for everything else means the default color
+    pub color: u32,
+    // should be drawn faintly
+    pub ghost: bool,
+    // text size relative to the font size, below 1 in exponents
+    pub scale: f32,
+    pub text: *const u8,
+    pub text_length: usize
+}
+
+pub struct Matheditor
+{
+    state: ProgramState,
+    measure: MeasureCallback,
+    user_data: *mut c_void,
+    texts: Vec<String>,
+    values: Vec<MatheditorValue>
+}
+
+struct CallbackMeasure
+{
+    measure: MeasureCallback,
+    user_data: *mut c_void
+}
+
+impl TextMeasure for CallbackMeasure
+{
+    fn text_size(&self, text: &str) -> (u32, u32)
+    {
+        let (mut width, mut height) = (0, 0);
+        (self.measure)(self.user_data, text.as_ptr(), text.len(), &mut width, &mut height);
+
+        (width, height)
+    }
+
+    // the host is expected to fall back on its own
+    fn has_glyph(&self, _c: char) -> bool
+    {
+        true
+    }
+
+    fn height(&self) -> u32
+    {
+        self.text_size("|").1
+    }
+}
+
+fn key_from_code(code: u32) -> Option<Key>
+{
+    let key = match code
+    {
+        KEY_BACKSPACE => Key::Backspace,
+        KEY_DELETE => Key::Delete,
+        KEY_RETURN => Key::Return,
+        KEY_TAB => Key::Tab,
+        KEY_LEFT => Key::Left,
+        KEY_RIGHT => Key::Right,
+        KEY_UP => Key::Up,
+        KEY_DOWN => Key::Down,
+        KEY_F5 => Key::F5,
+        KEY_ESCAPE => Key::Escape,
+        x => Key::Character(char::from_u32(x)?)
+    };
+
+    Some(key)
+}
+
+fn empty_value(kind: MatheditorKind, x: f32, y: f32, width: f32, height: f32) -> MatheditorValue
+{
+    MatheditorValue{
+        kind,
+        x,
+        y,
+        width,
+        height,
+        thickness: 0.0,
+        color: 0,
+        ghost: false,
+        scale: 1.0,
+        text: std::ptr::null(),
+        text_length: 0
+    }
+}
+
+fn packed_color([r, g, b]: [u8; 3]) -> u32
+{
+    ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+}
+
+fn convert_value(render: &RenderValue, layout: &LayoutConfig, texts: &mut Vec<String>) -> MatheditorValue
+{
+    // moving a string doesnt move its buffer so the pointer stays valid
+    let mut with_text = |value: MatheditorValue, text: String|
+    {
+        let value = MatheditorValue{text: text.as_ptr(), text_length: text.len(), ..value};
+        texts.push(text);
+
+        value
+    };
+
+    match render
+    {
+        RenderValue::Text{x, y, text} =>
+        {
+            with_text(empty_value(MatheditorKind::Text, *x, *y, 0.0, 0.0), text.to_string())
+        },
+        // goes from x, y to x + width, y + height
+        RenderValue::Line{x1, y1, x2, y2, thickness} =>
+        {
+            MatheditorValue{thickness: *thickness, ..empty_value(MatheditorKind::Line, *x1, *y1, x2 - x1, y2 - y1)}
+        },
+        RenderValue::Dots{x, y, kind} =>
+        {
+            with_text(empty_value(MatheditorKind::Dots, *x, *y, 0.0, 0.0), kind.as_char().to_string())
+        },
+        RenderValue::Unit{x, y, kind} =>
+        {
+            with_text(empty_value(MatheditorKind::Unit, *x, *y, 0.0, 0.0), kind.as_char().to_string())
+        },
+        RenderValue::Placeholder{x, y, width, height} =>
+        {
+            empty_value(MatheditorKind::Placeholder, *x, *y, *width, *height)
+        },
+        RenderValue::Panel{x, y, width, height} =>
+        {
+            empty_value(MatheditorKind::Panel, *x, *y, *width, *height)
+        },
+        RenderValue::Cursor{x, y, color} =>
+        {
+            let color = packed_color(*color);
+
+            MatheditorValue{
+                color,
+                ..empty_value(MatheditorKind::Cursor, *x, *y, layout.cursor_width as f32, layout.font_size as f32)
+            }
+        },
+        RenderValue::Radical{x, y, width, height, thickness} =>
+        {
+            MatheditorValue{thickness: *thickness, ..empty_value(MatheditorKind::Radical, *x, *y, *width, *height)}
+        },
+        RenderValue::Bracket{x, y, height, side, kind} =>
+        {
+            let value = empty_value(MatheditorKind::Bracket, *x, *y, layout.bracket_width(), *height);
+
+            with_text(value, kind.as_char(*side).to_string())
+        },
+        RenderValue::Ghost(value) =>
+        {
+            MatheditorValue{ghost: true, ..convert_value(value, layout, texts)}
+        },
+        RenderValue::Colored{color, value} =>
+        {
+            MatheditorValue{color: packed_color(*color), ..convert_value(value, layout, texts)}
+        },
+        RenderValue::Scaled{scale, value} =>
+        {
+            MatheditorValue{scale: *scale, ..convert_value(value, layout, texts)}
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn matheditor_new(measure: MeasureCallback, user_data: *mut c_void) -> *mut Matheditor
+{
+    Box::into_raw(Box::new(Matheditor{
+        state: ProgramState::new(),
+        measure,
+        user_data,
+        texts: Vec::new(),
+        values: Vec::new()
+    }))
+}
+
+/// # Safety
+/// editor must come from matheditor_new and not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn matheditor_free(editor: *mut Matheditor)
+{
+    if !editor.is_null()
+    {
+        drop(Box::from_raw(editor));
+    }
+}
+
+/// # Safety
+/// editor must come from matheditor_new
+#[no_mangle]
+pub unsafe extern "C" fn matheditor_key(editor: *mut Matheditor, key: u32, modifiers: u32) -> bool
+{
+    let editor = &mut *editor;
+
+    let Some(key) = key_from_code(key) else { return false };
+
+    let modifiers = Modifiers{
+        ctrl: (modifiers & MODIFIER_CTRL) != 0,
+        shift: (modifiers & MODIFIER_SHIFT) != 0
+    };
+
+    editor.state.key_pressed(key, modifiers)
+}
+
+/// # Safety
+/// editor must come from matheditor_new and text must be a nul terminated utf8 string
+#[no_mangle]
+pub unsafe extern "C" fn matheditor_text(editor: *mut Matheditor, text: *const c_char) -> bool
+{
+    let editor = &mut *editor;
+
+    let Ok(text) = CStr::from_ptr(text).to_str() else { return false };
+
+    editor.state.text_input(text.to_owned())
+}
+
+/// # Safety
+/// editor must come from matheditor_new
+#[no_mangle]
+pub unsafe extern "C" fn matheditor_render(editor: *mut Matheditor, width: u32, height: u32) -> usize
+{
+    let editor = &mut *editor;
+
+    let measurer = CallbackMeasure{measure: editor.measure, user_data: editor.user_data};
+    let layout = editor.state.layout().clone();
+
+    let mut texts: Vec<String> = Vec::new();
+    let mut values = Vec::new();
+    let mut highlights = Vec::new();
+
+    editor.state.render(&measurer, width, height, |rect|
+    {
+        highlights.push(empty_value(MatheditorKind::Highlight, rect.x, rect.y, rect.width, rect.height));
+    }, |render|
+    {
+        values.push(convert_value(render, &layout, &mut texts));
+    });
+
+    // highlights go first so theyre drawn under everything else
+    values.splice(0..0, highlights);
+
+    editor.texts = texts;
+    editor.values = values;
+
+    editor.values.len()
+}
+
+/// # Safety
+/// editor must come from matheditor_new, the index must be less than what matheditor_render returned
+#[no_mangle]
+pub unsafe extern "C" fn matheditor_value(editor: *const Matheditor, index: usize) -> MatheditorValue
+{
+    let editor = &*editor;
+
+    editor.values[index]
+}
+
+/// # Safety
+/// editor must come from matheditor_new
+#[no_mangle]
+pub unsafe extern "C" fn matheditor_set_origin(editor: *mut Matheditor, pinned: bool, x: f32, y: f32)
+{
+    let editor = &mut *editor;
+
+    // unpinned goes back to centering in the render size
+    editor.state.set_origin(pinned.then_some((x, y)));
+}
+
+/// # Safety
+/// editor must come from matheditor_new
+#[no_mangle]
+pub unsafe extern "C" fn matheditor_animating(editor: *const Matheditor) -> bool
+{
+    let editor = &*editor;
+
+    // the host should keep rendering frames while this is true
+    editor.state.animating()
+}
+
+/// # Safety
+/// editor must come from matheditor_new
+#[no_mangle]
+pub unsafe extern "C" fn matheditor_tick(editor: *mut Matheditor, seconds: f32)
+{
+    let editor = &mut *editor;
+
+    // theres no clock on some hosts so they say how much time passed themselves
+    editor.state.tick(seconds);
+}
@@ -0,0 +1,554 @@
+// small binary encoding of the document for sharing as text, base64 of varints and tags
+use std::{fmt, collections::HashMap};
+
+use crate::{Line, InputValue, InputValues, DotsKind, UnitKind, SpaceKind, BracketKind, ColumnAlign, custom::CustomConstructor};
+
+
+// bump when the meaning of existing tags changes, new tags dont need it
+// 2 added a label after every line
+const VERSION: u8 = 2;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompactError
+{
+    InvalidCharacter(char),
+    UnexpectedEnd,
+    UnsupportedVersion(u8),
+    UnknownTag(u8),
+    UnknownCustom(String),
+    InvalidText,
+    TooDeep,
+    EmptyMatrix,
+    InvalidAnswer
+}
+
+impl fmt::Display for CompactError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::InvalidCharacter(c) => write!(f, "invalid character {c:?}"),
+            Self::UnexpectedEnd => write!(f, "data ends too early"),
+            Self::UnsupportedVersion(x) => write!(f, "made by a newer version ({x})"),
+            Self::UnknownTag(x) => write!(f, "unknown value tag {x}"),
+            Self::UnknownCustom(x) => write!(f, "no custom value registered as {x}"),
+            Self::InvalidText => write!(f, "text isnt valid utf8"),
+            Self::TooDeep => write!(f, "structures are nested too deep"),
+            Self::EmptyMatrix => write!(f, "matrix without any cells"),
+            Self::InvalidAnswer => write!(f, "answer referring to its own line")
+        }
+    }
+}
+
+fn encode_base64(bytes: &[u8]) -> String
+{
+    bytes.chunks(3).flat_map(|chunk|
+    {
+        let value = chunk.iter().enumerate().fold(0_u32, |acc, (index, byte)|
+        {
+            acc | ((*byte as u32) << (16 - index * 8))
+        });
+
+        // no padding, the length tells how many characters are real
+        (0..chunk.len() + 1).map(move |index|
+        {
+            ALPHABET[((value >> (18 - index * 6)) & 0x3f) as usize] as char
+        })
+    }).collect()
+}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, CompactError>
+{
+    let values = text.chars().map(|c|
+    {
+        ALPHABET.iter().position(|x| *x as char == c).ok_or(CompactError::InvalidCharacter(c))
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(values.chunks(4).flat_map(|chunk|
+    {
+        let value = chunk.iter().enumerate().fold(0_u32, |acc, (index, x)|
+        {
+            acc | ((*x as u32) << (18 - index * 6))
+        });
+
+        (0..chunk.len().saturating_sub(1)).map(move |index| (value >> (16 - index * 8)) as u8)
+    }).collect())
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer
+{
+    fn varint(&mut self, mut value: usize)
+    {
+        while value >= 0x80
+        {
+            self.0.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+
+        self.0.push(value as u8);
+    }
+
+    fn text(&mut self, text: &str)
+    {
+        self.varint(text.len());
+        self.0.extend(text.as_bytes());
+    }
+
+    fn values(&mut self, values: &InputValues)
+    {
+        self.slice(&values.0);
+    }
+
+    fn slice(&mut self, values: &[InputValue])
+    {
+        self.varint(values.len());
+
+        values.iter().for_each(|value|
+        {
+            match value
+            {
+                InputValue::Value(x) =>
+                {
+                    self.0.push(0);
+                    self.text(x);
+                },
+                InputValue::Dots(kind) =>
+                {
+                    self.0.push(1);
+                    self.0.push(*kind as u8);
+                },
+                InputValue::Unit(kind) =>
+                {
+                    self.0.push(2);
+                    self.0.push(*kind as u8);
+                },
+                InputValue::TextRun{content} =>
+                {
+                    self.0.push(3);
+                    self.text(content);
+                },
+                InputValue::Fraction{top, bottom} =>
+                {
+                    self.0.push(4);
+                    self.values(top);
+                    self.values(bottom);
+                },
+                // only the kind is kept, it comes back as a fresh value from its constructor
+                InputValue::Extension(value) =>
+                {
+                    self.0.push(5);
+                    self.text(value.tag());
+                },
+                InputValue::Cancel{content} =>
+                {
+                    self.0.push(6);
+                    self.values(content);
+                },
+                InputValue::Space(kind) =>
+                {
+                    self.0.push(7);
+                    self.0.push(*kind as u8);
+                },
+                InputValue::Answer{line_ref} =>
+                {
+                    self.0.push(8);
+                    self.varint(*line_ref);
+                },
+                InputValue::Superscript{base, exponent} =>
+                {
+                    self.0.push(9);
+                    self.values(base);
+                    self.values(exponent);
+                },
+                InputValue::Subscript{base, subscript} =>
+                {
+                    self.0.push(10);
+                    self.values(base);
+                    self.values(subscript);
+                },
+                InputValue::SubSuperscript{base, subscript, superscript} =>
+                {
+                    self.0.push(11);
+                    self.values(base);
+                    self.values(subscript);
+                    self.values(superscript);
+                },
+                InputValue::Sqrt{inner} =>
+                {
+                    self.0.push(12);
+                    self.values(inner);
+                },
+                InputValue::NthRoot{degree, inner} =>
+                {
+                    self.0.push(13);
+                    self.values(degree);
+                    self.values(inner);
+                },
+                InputValue::Paren{inner, kind} =>
+                {
+                    self.0.push(14);
+                    self.0.push(*kind as u8);
+                    self.values(inner);
+                },
+                InputValue::Divided{left, right, kind} =>
+                {
+                    self.0.push(19);
+                    self.0.push(*kind as u8);
+                    self.values(left);
+                    self.values(right);
+                },
+                InputValue::Abs{inner} =>
+                {
+                    self.0.push(15);
+                    self.values(inner);
+                },
+                InputValue::Matrix{rows, bracket} =>
+                {
+                    self.0.push(18);
+                    self.0.push(*bracket as u8);
+                    self.varint(rows.len());
+                    self.varint(rows[0].len());
+
+                    rows.iter().flatten().for_each(|cell| self.values(cell));
+                },
+                InputValue::Table{rows, columns, rules} =>
+                {
+                    self.0.push(20);
+                    self.varint(rows.len());
+                    self.varint(columns.len());
+                    self.0.extend(columns.iter().map(|align| *align as u8));
+                    self.0.push(*rules as u8);
+
+                    rows.iter().flatten().for_each(|cell| self.values(cell));
+                },
+                InputValue::Sum{lower, upper, body} | InputValue::Integral{lower, upper, body} =>
+                {
+                    self.0.push(if matches!(value, InputValue::Sum{..}) { 16 } else { 17 });
+                    self.values(lower);
+                    self.values(upper);
+                    self.values(body);
+                }
+            }
+        });
+    }
+}
+
+struct Reader<'a>
+{
+    bytes: &'a [u8],
+    custom: &'a HashMap<String, CustomConstructor>,
+    // how many more structures can be nested inside the current one
+    depth_left: usize
+}
+
+impl Reader<'_>
+{
+    fn byte(&mut self) -> Result<u8, CompactError>
+    {
+        let (first, rest) = self.bytes.split_first().ok_or(CompactError::UnexpectedEnd)?;
+        self.bytes = rest;
+
+        Ok(*first)
+    }
+
+    fn varint(&mut self) -> Result<usize, CompactError>
+    {
+        let mut value = 0;
+        let mut shift = 0;
+
+        loop
+        {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as usize).checked_shl(shift).ok_or(CompactError::UnexpectedEnd)?;
+
+            if byte & 0x80 == 0
+            {
+                return Ok(value);
+            }
+
+            shift += 7;
+        }
+    }
+
+    fn text(&mut self) -> Result<String, CompactError>
+    {
+        let length = self.varint()?;
+
+        if length > self.bytes.len()
+        {
+            return Err(CompactError::UnexpectedEnd);
+        }
+
+        let (text, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+
+        String::from_utf8(text.to_vec()).map_err(|_| CompactError::InvalidText)
+    }
+
+    fn kind<T>(&mut self, kinds: &[T]) -> Result<T, CompactError>
+    where
+        T: Copy
+    {
+        let tag = self.byte()?;
+
+        kinds.get(tag as usize).copied().ok_or(CompactError::UnknownTag(tag))
+    }
+
+    // a part of a structure, one level deeper than the values around it
+    fn part(&mut self) -> Result<InputValues, CompactError>
+    {
+        self.depth_left = self.depth_left.checked_sub(1).ok_or(CompactError::TooDeep)?;
+
+        let values = self.values();
+        self.depth_left += 1;
+
+        values
+    }
+
+    fn values(&mut self) -> Result<InputValues, CompactError>
+    {
+        let length = self.varint()?;
+
+        // every value takes at least a byte so this cant allocate more than the input
+        let mut values = Vec::with_capacity(length.min(self.bytes.len()));
+        for _ in 0..length
+        {
+            let value = match self.byte()?
+            {
+                0 => InputValue::Value(self.text()?),
+                1 =>
+                {
+                    let kinds = [DotsKind::Low, DotsKind::Center, DotsKind::Vertical, DotsKind::Diagonal];
+                    InputValue::Dots(self.kind(&kinds)?)
+                },
+                2 =>
+                {
+                    let kinds = [UnitKind::Degree, UnitKind::Prime, UnitKind::DoublePrime];
+                    InputValue::Unit(self.kind(&kinds)?)
+                },
+                3 => InputValue::TextRun{content: self.text()?},
+                4 =>
+                {
+                    let top = self.part()?;
+                    let bottom = self.part()?;
+
+                    InputValue::Fraction{top, bottom}
+                },
+                5 =>
+                {
+                    let tag = self.text()?;
+                    let constructor = self.custom.get(&tag).ok_or(CompactError::UnknownCustom(tag))?;
+
+                    InputValue::Extension(constructor())
+                },
+                6 => InputValue::Cancel{content: self.part()?},
+                7 => InputValue::Space(self.kind(&[SpaceKind::NegativeThin])?),
+                8 =>
+                {
+                    // line refs count back from the line theyre on, 0 would be the line itself
+                    let line_ref = self.varint()?;
+                    if line_ref == 0
+                    {
+                        return Err(CompactError::InvalidAnswer);
+                    }
+
+                    InputValue::Answer{line_ref}
+                },
+                9 =>
+                {
+                    let base = self.part()?;
+                    let exponent = self.part()?;
+
+                    InputValue::Superscript{base, exponent}
+                },
+                10 =>
+                {
+                    let base = self.part()?;
+                    let subscript = self.part()?;
+
+                    InputValue::Subscript{base, subscript}
+                },
+                11 =>
+                {
+                    let base = self.part()?;
+                    let subscript = self.part()?;
+                    let superscript = self.part()?;
+
+                    InputValue::SubSuperscript{base, subscript, superscript}
+                },
+                12 => InputValue::Sqrt{inner: self.part()?},
+                13 =>
+                {
+                    let degree = self.part()?;
+                    let inner = self.part()?;
+
+                    InputValue::NthRoot{degree, inner}
+                },
+                14 =>
+                {
+                    let kind = self.kind(&[BracketKind::Round, BracketKind::Square, BracketKind::Curly])?;
+
+                    InputValue::Paren{inner: self.part()?, kind}
+                },
+                15 => InputValue::Abs{inner: self.part()?},
+                19 =>
+                {
+                    let kind = self.kind(&[BracketKind::Round, BracketKind::Square, BracketKind::Curly])?;
+
+                    let left = self.part()?;
+                    let right = self.part()?;
+
+                    InputValue::Divided{left, right, kind}
+                },
+                18 =>
+                {
+                    let bracket = self.kind(&[BracketKind::Round, BracketKind::Square, BracketKind::Curly])?;
+
+                    let height = self.varint()?;
+                    let width = self.varint()?;
+
+                    // an empty grid has no cell to put the cursor in
+                    if height == 0 || width == 0
+                    {
+                        return Err(CompactError::EmptyMatrix);
+                    }
+
+                    let rows = (0..height).map(|_|
+                    {
+                        (0..width).map(|_| self.part()).collect::<Result<Vec<_>, _>>()
+                    }).collect::<Result<Vec<_>, _>>()?;
+
+                    InputValue::Matrix{rows, bracket}
+                },
+                20 =>
+                {
+                    let height = self.varint()?;
+                    let width = self.varint()?;
+
+                    if height == 0 || width == 0
+                    {
+                        return Err(CompactError::EmptyMatrix);
+                    }
+
+                    let columns = (0..width).map(|_|
+                    {
+                        self.kind(&[ColumnAlign::Left, ColumnAlign::Center, ColumnAlign::Right])
+                    }).collect::<Result<Vec<_>, _>>()?;
+
+                    let rules = self.kind(&[false, true])?;
+
+                    let rows = (0..height).map(|_|
+                    {
+                        (0..width).map(|_| self.part()).collect::<Result<Vec<_>, _>>()
+                    }).collect::<Result<Vec<_>, _>>()?;
+
+                    InputValue::Table{rows, columns, rules}
+                },
+                tag@(16 | 17) =>
+                {
+                    let lower = self.part()?;
+                    let upper = self.part()?;
+                    let body = self.part()?;
+
+                    if tag == 16 { InputValue::Sum{lower, upper, body} } else { InputValue::Integral{lower, upper, body} }
+                },
+                x => return Err(CompactError::UnknownTag(x))
+            };
+
+            values.push(value);
+        }
+
+        Ok(InputValues(values))
+    }
+}
+
+pub(crate) fn encode(lines: &[Line]) -> String
+{
+    let mut writer = Writer(vec![VERSION]);
+
+    writer.varint(lines.len());
+    lines.iter().for_each(|line|
+    {
+        writer.values(&line.values);
+
+        // empty means no label
+        writer.text(line.label.as_deref().unwrap_or(""));
+    });
+
+    encode_base64(&writer.0)
+}
+
+pub(crate) fn decode(
+    text: &str,
+    custom: &HashMap<String, CustomConstructor>,
+    max_depth: usize
+) -> Result<Vec<Line>, CompactError>
+{
+    let bytes = decode_base64(text.trim())?;
+    let mut reader = Reader{bytes: &bytes, custom, depth_left: max_depth};
+
+    let version = reader.byte()?;
+    if version > VERSION
+    {
+        return Err(CompactError::UnsupportedVersion(version));
+    }
+
+    let length = reader.varint()?;
+
+    (0..length).map(|_|
+    {
+        let values = reader.values()?;
+
+        // older documents dont have labels
+        let label = if version >= 2 { Some(reader.text()?).filter(|label| !label.is_empty()) } else { None };
+
+        Ok(Line{values, label, ..Line::default()})
+    }).collect()
+}
+
+// values copied out of a line, without any of the lines around them
+pub(crate) fn encode_values(values: &[InputValue]) -> String
+{
+    let mut writer = Writer(vec![VERSION]);
+    writer.slice(values);
+
+    encode_base64(&writer.0)
+}
+
+pub(crate) fn decode_values(
+    text: &str,
+    custom: &HashMap<String, CustomConstructor>,
+    max_depth: usize
+) -> Result<InputValues, CompactError>
+{
+    let bytes = decode_base64(text.trim())?;
+    let mut reader = Reader{bytes: &bytes, custom, depth_left: max_depth};
+
+    let version = reader.byte()?;
+    if version > VERSION
+    {
+        return Err(CompactError::UnsupportedVersion(version));
+    }
+
+    reader.values()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn answer_to_own_line()
+    {
+        // version, one line, one value, an answer to line 0 and no label
+        let code = encode_base64(&[2, 1, 1, 8, 0, 0]);
+
+        assert_eq!(decode(&code, &HashMap::new(), 8).unwrap_err(), CompactError::InvalidAnswer);
+    }
+}
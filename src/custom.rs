@@ -0,0 +1,113 @@
+use std::{fmt, hash::{Hash, Hasher}};
+
+use crate::{RenderValue, export::ExportFormat};
+
+
+// structures added by embedders, the editor treats them as a single value without regions inside
+pub trait CustomValue: fmt::Debug
+{
+    // identifies the kind of value, also used as the command that inserts it
+    fn tag(&self) -> &str;
+
+    // pieces to draw with x and y as the top left, the editor measures and lays them out
+    fn render(&self, x: f32, y: f32) -> Vec<RenderValue<'_>>;
+
+    fn export(&self, format: ExportFormat) -> String;
+
+    fn evaluate(&self) -> Option<f64>
+    {
+        None
+    }
+}
+
+impl Hash for dyn CustomValue
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.tag().hash(state);
+        self.export(ExportFormat::Plain).hash(state);
+    }
+}
+
+// makes a new value for its command
+pub type CustomConstructor = Box<dyn Fn() -> Box<dyn CustomValue>>;
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{ProgramState, tests::Fixed};
+
+    // an empty box standing for three of something
+    #[derive(Debug)]
+    struct Crate;
+
+    impl CustomValue for Crate
+    {
+        fn tag(&self) -> &str
+        {
+            "crate"
+        }
+
+        fn render(&self, x: f32, y: f32) -> Vec<RenderValue<'_>>
+        {
+            vec![RenderValue::Text{x, y, text: "[]".into()}]
+        }
+
+        fn export(&self, _format: ExportFormat) -> String
+        {
+            "[]".to_owned()
+        }
+
+        fn evaluate(&self) -> Option<f64>
+        {
+            Some(3.0)
+        }
+    }
+
+    fn with_crate() -> ProgramState
+    {
+        let mut state = ProgramState::new();
+        state.register_custom("crate", Box::new(|| Box::new(Crate)));
+
+        state
+    }
+
+    #[test]
+    fn typed_in()
+    {
+        let mut state = with_crate();
+        "2\\crate ".chars().for_each(|c| { state.text_input(c.to_string()); });
+
+        assert_eq!(state.lines[0].values.to_latex(), "2[]");
+        assert_eq!(state.lines[0].values.evaluate(), Ok(6.0));
+
+        let mut texts = Vec::new();
+        state.render(&Fixed, 400, 200, |_| (), |value|
+        {
+            if let RenderValue::Text{text, ..} = value
+            {
+                texts.push(text.to_string());
+            }
+        });
+
+        assert!(texts.iter().any(|text| text == "[]"));
+    }
+
+    #[test]
+    fn shared()
+    {
+        let mut state = with_crate();
+        "\\crate ".chars().for_each(|c| { state.text_input(c.to_string()); });
+
+        let code = state.to_compact();
+
+        // without the constructor registered theres nothing to make it from
+        assert!(ProgramState::from_compact(&code).is_err());
+
+        let mut other = with_crate();
+        other.load_compact(&code).unwrap();
+
+        assert_eq!(other.lines[0].values.to_latex(), "[]");
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt::Write;
+
+use crate::{InputValue, InputValues};
+
+
+impl InputValues
+{
+    // one value per line, children indented under their parent
+    pub fn dump(&self, depth: usize, output: &mut String)
+    {
+        let indent = "  ".repeat(depth);
+
+        if self.0.is_empty()
+        {
+            writeln!(output, "{indent}(empty)").unwrap();
+        }
+
+        self.0.iter().for_each(|value|
+        {
+            match value
+            {
+                InputValue::Value(x) => writeln!(output, "{indent}Value {x:?}").unwrap(),
+                InputValue::Dots(kind) => writeln!(output, "{indent}Dots {kind:?}").unwrap(),
+                InputValue::Unit(kind) => writeln!(output, "{indent}Unit {kind:?}").unwrap(),
+                InputValue::Space(kind) => writeln!(output, "{indent}Space {kind:?}").unwrap(),
+                InputValue::Answer{line_ref} => writeln!(output, "{indent}Answer {line_ref}").unwrap(),
+                InputValue::TextRun{content} => writeln!(output, "{indent}TextRun {content:?}").unwrap(),
+                InputValue::Extension(value) => writeln!(output, "{indent}Extension {value:?}").unwrap(),
+                InputValue::Fraction{top, bottom} =>
+                {
+                    writeln!(output, "{indent}Fraction").unwrap();
+
+                    writeln!(output, "{indent}  top").unwrap();
+                    top.dump(depth + 2, output);
+
+                    writeln!(output, "{indent}  bottom").unwrap();
+                    bottom.dump(depth + 2, output);
+                },
+                InputValue::Cancel{content} =>
+                {
+                    writeln!(output, "{indent}Cancel").unwrap();
+                    content.dump(depth + 1, output);
+                },
+                InputValue::Superscript{base, exponent} =>
+                {
+                    writeln!(output, "{indent}Superscript").unwrap();
+
+                    writeln!(output, "{indent}  base").unwrap();
+                    base.dump(depth + 2, output);
+
+                    writeln!(output, "{indent}  exponent").unwrap();
+                    exponent.dump(depth + 2, output);
+                },
+                InputValue::Subscript{base, subscript} =>
+                {
+                    writeln!(output, "{indent}Subscript").unwrap();
+
+                    writeln!(output, "{indent}  base").unwrap();
+                    base.dump(depth + 2, output);
+
+                    writeln!(output, "{indent}  subscript").unwrap();
+                    subscript.dump(depth + 2, output);
+                },
+                InputValue::SubSuperscript{base, subscript, superscript} =>
+                {
+                    writeln!(output, "{indent}SubSuperscript").unwrap();
+
+                    writeln!(output, "{indent}  base").unwrap();
+                    base.dump(depth + 2, output);
+
+                    writeln!(output, "{indent}  subscript").unwrap();
+                    subscript.dump(depth + 2, output);
+
+                    writeln!(output, "{indent}  superscript").unwrap();
+                    superscript.dump(depth + 2, output);
+                },
+                InputValue::Sqrt{inner} =>
+                {
+                    writeln!(output, "{indent}Sqrt").unwrap();
+                    inner.dump(depth + 1, output);
+                },
+                InputValue::NthRoot{degree, inner} =>
+                {
+                    writeln!(output, "{indent}NthRoot").unwrap();
+
+                    writeln!(output, "{indent}  degree").unwrap();
+                    degree.dump(depth + 2, output);
+
+                    writeln!(output, "{indent}  inner").unwrap();
+                    inner.dump(depth + 2, output);
+                },
+                InputValue::Paren{inner, kind} =>
+                {
+                    writeln!(output, "{indent}Paren {kind:?}").unwrap();
+                    inner.dump(depth + 1, output);
+                },
+                InputValue::Divided{left, right, kind} =>
+                {
+                    writeln!(output, "{indent}Divided {kind:?}").unwrap();
+
+                    writeln!(output, "{indent}  left").unwrap();
+                    left.dump(depth + 2, output);
+
+                    writeln!(output, "{indent}  right").unwrap();
+                    right.dump(depth + 2, output);
+                },
+                InputValue::Abs{inner} =>
+                {
+                    writeln!(output, "{indent}Abs").unwrap();
+                    inner.dump(depth + 1, output);
+                },
+                InputValue::Matrix{rows, bracket} =>
+                {
+                    writeln!(output, "{indent}Matrix {bracket:?}").unwrap();
+
+                    rows.iter().enumerate().for_each(|(index, cells)|
+                    {
+                        writeln!(output, "{indent}  row {index}").unwrap();
+                        cells.iter().for_each(|cell| cell.dump(depth + 2, output));
+                    });
+                },
+                InputValue::Table{rows, columns, rules} =>
+                {
+                    writeln!(output, "{indent}Table {columns:?} rules {rules}").unwrap();
+
+                    rows.iter().enumerate().for_each(|(index, cells)|
+                    {
+                        writeln!(output, "{indent}  row {index}").unwrap();
+                        cells.iter().for_each(|cell| cell.dump(depth + 2, output));
+                    });
+                },
+                InputValue::Sum{lower, upper, body} | InputValue::Integral{lower, upper, body} =>
+                {
+                    let name = if matches!(value, InputValue::Sum{..}) { "Sum" } else { "Integral" };
+                    writeln!(output, "{indent}{name}").unwrap();
+
+                    writeln!(output, "{indent}  lower").unwrap();
+                    lower.dump(depth + 2, output);
+
+                    writeln!(output, "{indent}  upper").unwrap();
+                    upper.dump(depth + 2, output);
+
+                    writeln!(output, "{indent}  body").unwrap();
+                    body.dump(depth + 2, output);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,493 @@
+use std::{fmt, f64::consts};
+
+use crate::{Line, InputValue, InputValues, UnitKind};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError
+{
+    Empty,
+    UnexpectedEnd,
+    Unexpected(String),
+    UnknownName(String),
+    Unsupported,
+    NotFinite,
+    NoAnswer
+}
+
+impl fmt::Display for EvalError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::Empty => write!(f, "nothing to evaluate"),
+            Self::UnexpectedEnd => write!(f, "unexpected end of expression"),
+            Self::Unexpected(x) => write!(f, "unexpected {x}"),
+            Self::UnknownName(x) => write!(f, "unknown name {x}"),
+            Self::Unsupported => write!(f, "cant evaluate this structure"),
+            Self::NotFinite => write!(f, "result isnt a finite number"),
+            Self::NoAnswer => write!(f, "referenced line has no result")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token
+{
+    Number(f64),
+    Operator(char),
+    Open,
+    Close
+}
+
+impl fmt::Display for Token
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::Number(x) => write!(f, "{x}"),
+            Self::Operator(x) => write!(f, "{x}"),
+            Self::Open => write!(f, "("),
+            Self::Close => write!(f, ")")
+        }
+    }
+}
+
+struct Lexer<'a>
+{
+    tokens: Vec<Token>,
+    // results of the lines before the one being evaluated
+    answers: &'a [Result<f64, EvalError>],
+    number: String,
+    name: String,
+    decimal_separator: char,
+    // a separator right after a digit, only part of the number if another digit follows
+    pending_separator: bool
+}
+
+impl<'a> Lexer<'a>
+{
+    fn new(decimal_separator: char, answers: &'a [Result<f64, EvalError>]) -> Self
+    {
+        Self{
+            tokens: Vec::new(),
+            answers,
+            number: String::new(),
+            name: String::new(),
+            decimal_separator,
+            pending_separator: false
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), EvalError>
+    {
+        if self.pending_separator
+        {
+            return Err(EvalError::Unexpected(self.decimal_separator.to_string()));
+        }
+
+        if !self.number.is_empty()
+        {
+            let number = self.number.parse()
+                .map_err(|_| EvalError::Unexpected(self.number.clone()))?;
+
+            self.tokens.push(Token::Number(number));
+            self.number.clear();
+        }
+
+        if !self.name.is_empty()
+        {
+            let value = match self.name.as_ref()
+            {
+                "pi" | "π" => consts::PI,
+                "e" => consts::E,
+                _ => return Err(EvalError::UnknownName(self.name.clone()))
+            };
+
+            self.tokens.push(Token::Number(value));
+            self.name.clear();
+        }
+
+        Ok(())
+    }
+
+    fn push_char(&mut self, c: char) -> Result<(), EvalError>
+    {
+        if self.pending_separator
+        {
+            self.pending_separator = false;
+
+            if !c.is_ascii_digit()
+            {
+                return Err(EvalError::Unexpected(self.decimal_separator.to_string()));
+            }
+
+            self.number.push('.');
+        }
+
+        // other separators like , could mean something else so they only count between digits
+        if c == self.decimal_separator && c != '.'
+        {
+            if self.number.is_empty()
+            {
+                return Err(EvalError::Unexpected(c.to_string()));
+            }
+
+            self.pending_separator = true;
+
+            return Ok(());
+        }
+
+        if c.is_ascii_digit() || (c == '.' && self.decimal_separator == '.')
+        {
+            if !self.name.is_empty()
+            {
+                self.flush()?;
+            }
+
+            self.number.push(c);
+
+            return Ok(());
+        }
+
+        if c.is_alphabetic()
+        {
+            if !self.number.is_empty()
+            {
+                self.flush()?;
+            }
+
+            self.name.push(c);
+
+            return Ok(());
+        }
+
+        self.flush()?;
+
+        let token = match c
+        {
+            '+' => Token::Operator('+'),
+            '-' | '−' => Token::Operator('-'),
+            '*' | '×' | '·' => Token::Operator('*'),
+            '/' | '÷' => Token::Operator('/'),
+            '^' => Token::Operator('^'),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if c.is_whitespace() => return Ok(()),
+            c => return Err(EvalError::Unexpected(c.to_string()))
+        };
+
+        self.tokens.push(token);
+
+        Ok(())
+    }
+
+    fn push_value(&mut self, value: &InputValue) -> Result<(), EvalError>
+    {
+        match value
+        {
+            InputValue::Value(text) =>
+            {
+                text.chars().try_for_each(|c| self.push_char(c))
+            },
+            InputValue::Fraction{top, bottom} =>
+            {
+                self.flush()?;
+
+                let (separator, answers) = (self.decimal_separator, self.answers);
+                let value = top.evaluate_after(separator, answers)? / bottom.evaluate_after(separator, answers)?;
+                self.tokens.push(Token::Number(value));
+
+                Ok(())
+            },
+            InputValue::Superscript{base, exponent} =>
+            {
+                self.flush()?;
+
+                let (separator, answers) = (self.decimal_separator, self.answers);
+                let value = base.evaluate_after(separator, answers)?.powf(exponent.evaluate_after(separator, answers)?);
+                self.tokens.push(Token::Number(value));
+
+                Ok(())
+            },
+            InputValue::Sqrt{inner} =>
+            {
+                self.flush()?;
+
+                let value = inner.evaluate_after(self.decimal_separator, self.answers)?.sqrt();
+                self.tokens.push(Token::Number(value));
+
+                Ok(())
+            },
+            InputValue::Abs{inner} =>
+            {
+                self.flush()?;
+
+                let value = inner.evaluate_after(self.decimal_separator, self.answers)?.abs();
+                self.tokens.push(Token::Number(value));
+
+                Ok(())
+            },
+            InputValue::NthRoot{degree, inner} =>
+            {
+                self.flush()?;
+
+                let (separator, answers) = (self.decimal_separator, self.answers);
+                let value = inner.evaluate_after(separator, answers)?.powf(degree.evaluate_after(separator, answers)?.recip());
+                self.tokens.push(Token::Number(value));
+
+                Ok(())
+            },
+            // any kind of bracket groups the same way
+            InputValue::Paren{inner, ..} =>
+            {
+                self.flush()?;
+                self.tokens.push(Token::Open);
+
+                inner.0.iter().try_for_each(|value| self.push_value(value))?;
+
+                self.flush()?;
+                self.tokens.push(Token::Close);
+
+                Ok(())
+            },
+            InputValue::Unit(UnitKind::Degree) =>
+            {
+                self.flush()?;
+
+                self.tokens.push(Token::Operator('*'));
+                self.tokens.push(Token::Number(consts::PI / 180.0));
+
+                Ok(())
+            },
+            // striking through is only an annotation, the content still counts
+            InputValue::Cancel{content} =>
+            {
+                content.0.iter().try_for_each(|value| self.push_value(value))
+            },
+            InputValue::Answer{line_ref} =>
+            {
+                self.flush()?;
+
+                let index = self.answers.len().checked_sub(*line_ref).ok_or(EvalError::NoAnswer)?;
+                let value = self.answers.get(index).ok_or(EvalError::NoAnswer)?.clone().map_err(|_| EvalError::NoAnswer)?;
+
+                self.tokens.push(Token::Number(value));
+
+                Ok(())
+            },
+            // prose and spacing dont take part in the math
+            InputValue::TextRun{..} | InputValue::Space(_) => self.flush(),
+            InputValue::Extension(value) =>
+            {
+                self.flush()?;
+
+                self.tokens.push(Token::Number(value.evaluate().ok_or(EvalError::Unsupported)?));
+
+                Ok(())
+            },
+            // an indexed name like a_1 is a variable which cant be evaluated
+            InputValue::Unit(_)
+            | InputValue::Dots(_)
+            | InputValue::Subscript{..}
+            | InputValue::SubSuperscript{..}
+            | InputValue::Sum{..}
+            | InputValue::Integral{..}
+            | InputValue::Divided{..}
+            | InputValue::Matrix{..}
+            | InputValue::Table{..} => Err(EvalError::Unsupported)
+        }
+    }
+}
+
+struct Parser
+{
+    tokens: Vec<Token>,
+    index: usize
+}
+
+impl Parser
+{
+    fn peek(&self) -> Option<&Token>
+    {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Result<Token, EvalError>
+    {
+        let token = self.tokens.get(self.index).cloned().ok_or(EvalError::UnexpectedEnd)?;
+        self.index += 1;
+
+        Ok(token)
+    }
+
+    fn expression(&mut self) -> Result<f64, EvalError>
+    {
+        let mut value = self.term()?;
+
+        while let Some(Token::Operator(op@('+' | '-'))) = self.peek().cloned()
+        {
+            self.index += 1;
+
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64, EvalError>
+    {
+        let mut value = self.unary()?;
+
+        loop
+        {
+            match self.peek()
+            {
+                Some(Token::Operator('*')) =>
+                {
+                    self.index += 1;
+                    value *= self.unary()?;
+                },
+                Some(Token::Operator('/')) =>
+                {
+                    self.index += 1;
+                    value /= self.unary()?;
+                },
+                // implicit multiplication like 2(3) or 2pi
+                Some(Token::Number(_) | Token::Open) =>
+                {
+                    value *= self.power()?;
+                },
+                _ => return Ok(value)
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64, EvalError>
+    {
+        match self.peek()
+        {
+            Some(Token::Operator('-')) =>
+            {
+                self.index += 1;
+                Ok(-self.unary()?)
+            },
+            Some(Token::Operator('+')) =>
+            {
+                self.index += 1;
+                self.unary()
+            },
+            _ => self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<f64, EvalError>
+    {
+        let base = self.atom()?;
+
+        if let Some(Token::Operator('^')) = self.peek()
+        {
+            self.index += 1;
+
+            return Ok(base.powf(self.unary()?));
+        }
+
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<f64, EvalError>
+    {
+        match self.next()?
+        {
+            Token::Number(x) => Ok(x),
+            Token::Open =>
+            {
+                let value = self.expression()?;
+
+                match self.next()?
+                {
+                    Token::Close => Ok(value),
+                    x => Err(EvalError::Unexpected(x.to_string()))
+                }
+            },
+            x => Err(EvalError::Unexpected(x.to_string()))
+        }
+    }
+}
+
+impl InputValues
+{
+    pub fn evaluate(&self) -> Result<f64, EvalError>
+    {
+        self.evaluate_with('.')
+    }
+
+    pub fn evaluate_with(&self, decimal_separator: char) -> Result<f64, EvalError>
+    {
+        self.evaluate_after(decimal_separator, &[])
+    }
+
+    // answers are the results of the lines above this one in order
+    pub fn evaluate_after(&self, decimal_separator: char, answers: &[Result<f64, EvalError>]) -> Result<f64, EvalError>
+    {
+        let mut lexer = Lexer::new(decimal_separator, answers);
+
+        self.0.iter().try_for_each(|value| lexer.push_value(value))?;
+        lexer.flush()?;
+
+        if lexer.tokens.is_empty()
+        {
+            return Err(EvalError::Empty);
+        }
+
+        let mut parser = Parser{tokens: lexer.tokens, index: 0};
+
+        let value = parser.expression()?;
+
+        if let Some(token) = parser.peek()
+        {
+            return Err(EvalError::Unexpected(token.to_string()));
+        }
+
+        if !value.is_finite()
+        {
+            return Err(EvalError::NotFinite);
+        }
+
+        Ok(value)
+    }
+}
+
+// every line evaluated top to bottom so answers can refer to the ones above
+pub fn evaluate_lines(lines: &[Line], decimal_separator: char) -> Vec<Result<f64, EvalError>>
+{
+    lines.iter().fold(Vec::with_capacity(lines.len()), |mut results, line|
+    {
+        let result = line.values.evaluate_after(decimal_separator, &results);
+        results.push(result);
+
+        results
+    })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn answer_without_result()
+    {
+        let answer = |line_ref| Line{
+            values: InputValues(vec![InputValue::Answer{line_ref}]),
+            ..Default::default()
+        };
+
+        let results = evaluate_lines(&[answer(0), answer(3)], '.');
+
+        assert_eq!(results, vec![Err(EvalError::NoAnswer), Err(EvalError::NoAnswer)]);
+    }
+}
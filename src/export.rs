@@ -0,0 +1,1267 @@
+use crate::{Line, InputValue, InputValues, DotsKind, UnitKind, SpaceKind, BracketKind, BracketSide, ColumnAlign, evaluate};
+
+
+// how far a drop shadow is offset down and to the right
+const SHADOW_OFFSET: f32 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat
+{
+    Plain,
+    Latex,
+    Typst
+}
+
+// space and a drop shadow around exported images, the window never gets one
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ImageBorder
+{
+    pub padding: f32,
+    pub shadow: bool
+}
+
+impl ImageBorder
+{
+    pub const SHADOW_COLOR: [u8; 4] = [0, 0, 0, 64];
+
+    pub fn shadow_offset(&self) -> f32
+    {
+        if self.shadow { SHADOW_OFFSET } else { 0.0 }
+    }
+
+    // the card the content sits on in the top left of the image, the shadow is under the rest
+    pub fn card_size(&self, width: f32, height: f32) -> (f32, f32)
+    {
+        (width + self.padding * 2.0, height + self.padding * 2.0)
+    }
+
+    pub fn image_size(&self, width: f32, height: f32) -> (f32, f32)
+    {
+        let (width, height) = self.card_size(width, height);
+        let offset = self.shadow_offset();
+
+        (width + offset, height + offset)
+    }
+}
+
+// how loosely plain text binds at its top level, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence
+{
+    Sum,
+    Product,
+    Atom
+}
+
+fn precedence(text: &str) -> Precedence
+{
+    let mut depth = 0;
+    let mut product = false;
+    let mut previous: Option<char> = None;
+
+    for c in text.chars()
+    {
+        match c
+        {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            // a sign at the start or after another operator is unary and doesnt split anything
+            '+' | '-' | '−' if depth == 0 =>
+            {
+                if previous.is_some_and(|x| !"+-−*/×÷·^(".contains(x))
+                {
+                    return Precedence::Sum;
+                }
+
+                product = true;
+            },
+            '*' | '/' | '×' | '÷' | '·' | '^' if depth == 0 => product = true,
+            _ => ()
+        }
+
+        if !c.is_whitespace()
+        {
+            previous = Some(c);
+        }
+    }
+
+    if product
+    {
+        return Precedence::Product;
+    }
+
+    let number = text.chars().all(|c| c.is_ascii_digit() || c == '.');
+    let name = text.chars().all(char::is_alphabetic);
+
+    // a single group only counts if the first paren closes at the very end
+    let grouped = text.starts_with('(') && text.char_indices().scan(0, |depth, (index, c)|
+    {
+        match c
+        {
+            '(' => *depth += 1,
+            ')' => *depth -= 1,
+            _ => ()
+        }
+
+        Some((index, *depth))
+    }).find(|(_, depth)| *depth == 0).is_some_and(|(index, _)| index == text.len() - 1);
+
+    if !text.is_empty() && (number || name || grouped)
+    {
+        Precedence::Atom
+    } else
+    {
+        // juxtaposed things are an implicit multiplication
+        Precedence::Product
+    }
+}
+
+// the same name its rendered with
+pub(crate) fn answer_name(line_ref: usize) -> String
+{
+    if line_ref == 1 { "Ans".to_owned() } else { format!("Ans{line_ref}") }
+}
+
+fn parenthesized(text: String, needed: bool) -> String
+{
+    if needed { format!("({text})") } else { text }
+}
+
+// a single character or a plain number, which can be a base or an exponent without grouping
+fn is_single(text: &str) -> bool
+{
+    text.chars().count() == 1 || (!text.is_empty() && text.chars().all(|c| c.is_ascii_digit() || c == '.'))
+}
+
+impl InputValues
+{
+    pub fn export(&self, format: ExportFormat) -> String
+    {
+        match format
+        {
+            ExportFormat::Plain => self.to_plain(),
+            ExportFormat::Latex => self.to_latex(),
+            ExportFormat::Typst => self.to_typst()
+        }
+    }
+
+    pub fn to_plain(&self) -> String
+    {
+        let texts: Vec<String> = self.0.iter().map(|value|
+        {
+            match value
+            {
+                InputValue::Value(x) => x.clone(),
+                InputValue::Dots(kind) => kind.as_char().to_string(),
+                InputValue::Unit(kind) => kind.as_char().to_string(),
+                InputValue::Space(_) => String::new(),
+                InputValue::Answer{line_ref} => answer_name(*line_ref),
+                InputValue::TextRun{content} => content.clone(),
+                InputValue::Extension(value) => value.export(ExportFormat::Plain),
+                InputValue::Fraction{top, bottom} =>
+                {
+                    let (top, bottom) = (top.to_plain(), bottom.to_plain());
+
+                    // the numerator only splits on + and -, the denominator on anything
+                    let top_parens = top.is_empty() || precedence(&top) == Precedence::Sum;
+                    let bottom_parens = precedence(&bottom) != Precedence::Atom;
+
+                    format!("{}/{}", parenthesized(top, top_parens), parenthesized(bottom, bottom_parens))
+                },
+                InputValue::Cancel{content} => content.to_plain(),
+                InputValue::Superscript{base, exponent} =>
+                {
+                    let (base, exponent) = (base.to_plain(), exponent.to_plain());
+
+                    let base_parens = !is_single(&base);
+                    let exponent_parens = !is_single(&exponent);
+
+                    format!("{}^{}", parenthesized(base, base_parens), parenthesized(exponent, exponent_parens))
+                },
+                InputValue::Subscript{base, subscript} =>
+                {
+                    let (base, subscript) = (base.to_plain(), subscript.to_plain());
+
+                    let base_parens = !is_single(&base);
+                    let subscript_parens = !is_single(&subscript);
+
+                    format!("{}_{}", parenthesized(base, base_parens), parenthesized(subscript, subscript_parens))
+                },
+                InputValue::SubSuperscript{base, subscript, superscript} =>
+                {
+                    let [base, subscript, superscript] = [base, subscript, superscript].map(|values|
+                    {
+                        let text = values.to_plain();
+
+                        let parens = !is_single(&text);
+                        parenthesized(text, parens)
+                    });
+
+                    format!("{base}_{subscript}^{superscript}")
+                },
+                InputValue::Sqrt{inner} => format!("sqrt({})", inner.to_plain()),
+                // written as a power so it doesnt need a root function
+                InputValue::NthRoot{degree, inner} =>
+                {
+                    let (degree, inner) = (degree.to_plain(), inner.to_plain());
+
+                    let degree_parens = !is_single(&degree);
+                    let inner_parens = !is_single(&inner);
+
+                    format!("{}^(1/{})", parenthesized(inner, inner_parens), parenthesized(degree, degree_parens))
+                },
+                InputValue::Paren{inner, kind} =>
+                {
+                    format!("{}{}{}", kind.as_char(BracketSide::Left), inner.to_plain(), kind.as_char(BracketSide::Right))
+                },
+                InputValue::Divided{left, right, kind} =>
+                {
+                    let (open, close) = (kind.as_char(BracketSide::Left), kind.as_char(BracketSide::Right));
+
+                    format!("{open}{}|{}{close}", left.to_plain(), right.to_plain())
+                },
+                InputValue::Abs{inner} => format!("abs({})", inner.to_plain()),
+                InputValue::Sum{lower, upper, body} =>
+                {
+                    let [lower, upper] = [lower, upper].map(|values|
+                    {
+                        let text = values.to_plain();
+
+                        let parens = !is_single(&text);
+                        parenthesized(text, parens)
+                    });
+
+                    format!("sum_{lower}^{upper}({})", body.to_plain())
+                },
+                InputValue::Integral{lower, upper, body} =>
+                {
+                    let [lower, upper] = [lower, upper].map(|values|
+                    {
+                        let text = values.to_plain();
+
+                        let parens = !is_single(&text);
+                        parenthesized(text, parens)
+                    });
+
+                    format!("int_{lower}^{upper}({})", body.to_plain())
+                },
+                InputValue::Matrix{rows, ..} =>
+                {
+                    let rows: Vec<String> = rows.iter().map(|cells|
+                    {
+                        let cells: Vec<String> = cells.iter().map(InputValues::to_plain).collect();
+
+                        format!("[{}]", cells.join(", "))
+                    }).collect();
+
+                    format!("[{}]", rows.join(", "))
+                },
+                // rows on their own line would end the line so theyre separated like in the latex
+                InputValue::Table{rows, ..} =>
+                {
+                    let rows: Vec<String> = rows.iter().map(|cells|
+                    {
+                        cells.iter().map(InputValues::to_plain).collect::<Vec<_>>().join(" & ")
+                    }).collect();
+
+                    format!("table({})", rows.join(" \\\\ "))
+                }
+            }
+        }).collect();
+
+        let mut output = String::new();
+
+        texts.iter().enumerate().for_each(|(index, text)|
+        {
+            let is_fraction = matches!(self.0[index], InputValue::Fraction{..});
+
+            // a fraction next to something that binds tighter than division or would merge with it keeps its parens
+            let binds_before = output.chars().last().is_some_and(|c|
+            {
+                c.is_alphanumeric() || ")/^.".contains(c)
+            });
+
+            let binds_after = texts.get(index + 1).and_then(|next| next.chars().next()).is_some_and(|c|
+            {
+                c.is_alphanumeric() || "(^.".contains(c)
+            });
+
+            output.push_str(&parenthesized(text.clone(), is_fraction && (binds_before || binds_after)));
+        });
+
+        output
+    }
+
+    // a reading of the equation for screen readers like "a over b plus 2"
+    pub fn to_speech(&self) -> String
+    {
+        let mut words: Vec<String> = Vec::new();
+
+        // digits stay together as one number and letters as one name
+        let push_char = |words: &mut Vec<String>, c: char, previous: &mut Option<char>|
+        {
+            let joins = previous.is_some_and(|previous|
+            {
+                let is_number = |c: char| c.is_ascii_digit() || c == '.';
+
+                (is_number(previous) && is_number(c)) || (previous.is_alphabetic() && c.is_alphabetic())
+            });
+
+            *previous = Some(c);
+
+            if joins
+            {
+                words.last_mut().unwrap().push(c);
+                return;
+            }
+
+            let word = match c
+            {
+                '+' => "plus",
+                '-' | '−' => "minus",
+                '*' | '×' | '·' => "times",
+                '/' | '÷' => "divided by",
+                '^' => "to the power of",
+                '=' => "equals",
+                '≠' => "is not equal to",
+                '<' => "is less than",
+                '>' => "is greater than",
+                '≤' => "is less than or equal to",
+                '≥' => "is greater than or equal to",
+                '±' => "plus or minus",
+                '(' => "open paren",
+                ')' => "close paren",
+                ',' => "comma",
+                'π' => "pi",
+                '∞' => "infinity",
+                c if c.is_whitespace() =>
+                {
+                    *previous = None;
+                    return;
+                },
+                c =>
+                {
+                    words.push(c.to_string());
+                    return;
+                }
+            };
+
+            words.push(word.to_owned());
+        };
+
+        let mut previous = None;
+
+        self.0.iter().for_each(|value|
+        {
+            let word = match value
+            {
+                InputValue::Value(x) =>
+                {
+                    x.chars().for_each(|c| push_char(&mut words, c, &mut previous));
+                    return;
+                },
+                InputValue::Dots(_) => "dot dot dot".to_owned(),
+                InputValue::Unit(kind) =>
+                {
+                    let text = match kind
+                    {
+                        UnitKind::Degree => "degrees",
+                        UnitKind::Prime => "prime",
+                        UnitKind::DoublePrime => "double prime"
+                    };
+
+                    text.to_owned()
+                },
+                InputValue::Space(_) => return,
+                InputValue::Answer{line_ref: 1} => "the previous answer".to_owned(),
+                InputValue::Answer{line_ref} => format!("the answer {line_ref} lines up"),
+                InputValue::TextRun{content} => content.clone(),
+                InputValue::Extension(value) => value.export(ExportFormat::Plain),
+                InputValue::Fraction{top, bottom} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "blank".to_owned() } else { values.to_speech() }
+                    };
+
+                    let (top, bottom) = (part(top), part(bottom));
+
+                    // single words dont need the fraction marked out to be unambiguous
+                    if !top.contains(' ') && !bottom.contains(' ')
+                    {
+                        format!("{top} over {bottom}")
+                    } else
+                    {
+                        format!("the fraction {top} over {bottom}, end fraction")
+                    }
+                },
+                InputValue::Cancel{content} => format!("crossed out {}, end crossed out", content.to_speech()),
+                InputValue::Superscript{base, exponent} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "blank".to_owned() } else { values.to_speech() }
+                    };
+
+                    let (base, exponent) = (part(base), part(exponent));
+
+                    match exponent.as_ref()
+                    {
+                        "2" => format!("{base} squared"),
+                        "3" => format!("{base} cubed"),
+                        _ if !exponent.contains(' ') => format!("{base} to the power of {exponent}"),
+                        _ => format!("{base} to the power of {exponent}, end power")
+                    }
+                },
+                InputValue::Subscript{base, subscript} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "blank".to_owned() } else { values.to_speech() }
+                    };
+
+                    let (base, subscript) = (part(base), part(subscript));
+
+                    if !subscript.contains(' ')
+                    {
+                        format!("{base} sub {subscript}")
+                    } else
+                    {
+                        format!("{base} sub {subscript}, end sub")
+                    }
+                },
+                InputValue::SubSuperscript{base, subscript, superscript} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "blank".to_owned() } else { values.to_speech() }
+                    };
+
+                    let (base, subscript, superscript) = (part(base), part(subscript), part(superscript));
+
+                    // the subscript always gets closed off so the power isnt read as part of it
+                    let indexed = format!("{base} sub {subscript}, end sub");
+
+                    match superscript.as_ref()
+                    {
+                        "2" => format!("{indexed} squared"),
+                        "3" => format!("{indexed} cubed"),
+                        _ if !superscript.contains(' ') => format!("{indexed} to the power of {superscript}"),
+                        _ => format!("{indexed} to the power of {superscript}, end power")
+                    }
+                },
+                InputValue::Sqrt{inner} =>
+                {
+                    let inner = if inner.0.is_empty() { "blank".to_owned() } else { inner.to_speech() };
+
+                    if !inner.contains(' ')
+                    {
+                        format!("the square root of {inner}")
+                    } else
+                    {
+                        format!("the square root of {inner}, end root")
+                    }
+                },
+                InputValue::NthRoot{degree, inner} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "blank".to_owned() } else { values.to_speech() }
+                    };
+
+                    let (degree, inner) = (part(degree), part(inner));
+
+                    let root = match degree.as_ref()
+                    {
+                        "2" => "the square root".to_owned(),
+                        "3" => "the cube root".to_owned(),
+                        _ => format!("the root of degree {degree}")
+                    };
+
+                    if !inner.contains(' ')
+                    {
+                        format!("{root} of {inner}")
+                    } else
+                    {
+                        format!("{root} of {inner}, end root")
+                    }
+                },
+                InputValue::Paren{inner, kind} =>
+                {
+                    let name = match kind
+                    {
+                        BracketKind::Round => "paren",
+                        BracketKind::Square => "bracket",
+                        BracketKind::Curly => "brace"
+                    };
+
+                    let inner = if inner.0.is_empty() { "blank".to_owned() } else { inner.to_speech() };
+
+                    format!("open {name} {inner} close {name}")
+                },
+                InputValue::Divided{left, right, kind} =>
+                {
+                    let name = match kind
+                    {
+                        BracketKind::Round => "paren",
+                        BracketKind::Square => "bracket",
+                        BracketKind::Curly => "brace"
+                    };
+
+                    let [left, right] = [left, right].map(|values|
+                    {
+                        if values.0.is_empty() { "blank".to_owned() } else { values.to_speech() }
+                    });
+
+                    format!("open {name} {left} bar {right} close {name}")
+                },
+                InputValue::Abs{inner} =>
+                {
+                    let inner = if inner.0.is_empty() { "blank".to_owned() } else { inner.to_speech() };
+
+                    if !inner.contains(' ')
+                    {
+                        format!("the absolute value of {inner}")
+                    } else
+                    {
+                        format!("the absolute value of {inner}, end absolute value")
+                    }
+                },
+                InputValue::Sum{lower, upper, body} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "blank".to_owned() } else { values.to_speech() }
+                    };
+
+                    let (lower, upper, body) = (part(lower), part(upper), part(body));
+
+                    format!("the sum from {lower} to {upper} of {body}, end sum")
+                },
+                InputValue::Integral{lower, upper, body} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "blank".to_owned() } else { values.to_speech() }
+                    };
+
+                    let (lower, upper, body) = (part(lower), part(upper), part(body));
+
+                    format!("the integral from {lower} to {upper} of {body}, end integral")
+                },
+                InputValue::Matrix{rows, ..} =>
+                {
+                    let size = format!("{} by {}", rows.len(), rows[0].len());
+
+                    let rows: Vec<String> = rows.iter().enumerate().map(|(index, cells)|
+                    {
+                        let cells: Vec<String> = cells.iter().map(|cell|
+                        {
+                            if cell.0.is_empty() { "blank".to_owned() } else { cell.to_speech() }
+                        }).collect();
+
+                        format!("row {}: {}", index + 1, cells.join(", "))
+                    }).collect();
+
+                    format!("the {size} matrix, {}, end matrix", rows.join("; "))
+                },
+                InputValue::Table{rows, ..} =>
+                {
+                    let size = format!("{} by {}", rows.len(), rows[0].len());
+
+                    let rows: Vec<String> = rows.iter().enumerate().map(|(index, cells)|
+                    {
+                        let cells: Vec<String> = cells.iter().map(|cell|
+                        {
+                            if cell.0.is_empty() { "blank".to_owned() } else { cell.to_speech() }
+                        }).collect();
+
+                        format!("row {}: {}", index + 1, cells.join(", "))
+                    }).collect();
+
+                    format!("the {size} table, {}, end table", rows.join("; "))
+                }
+            };
+
+            previous = None;
+            words.push(word);
+        });
+
+        words.join(" ")
+    }
+
+    // end of what a typed caret or underscore starting at the index applies to when evaluating,
+    // a number, a bracketed group or a single value with the rest of a chain of powers
+    fn typed_script_end(&self, start: usize, power: bool) -> usize
+    {
+        let is = |index: usize, text: &str| matches!(self.0.get(index), Some(InputValue::Value(x)) if x == text);
+
+        let mut end = start;
+        if is(end, "-")
+        {
+            end += 1;
+        }
+
+        if is(end, "(")
+        {
+            let mut depth = 0;
+            while end < self.0.len()
+            {
+                if is(end, "(") { depth += 1; } else if is(end, ")") { depth -= 1; }
+                end += 1;
+
+                if depth == 0
+                {
+                    break;
+                }
+            }
+        } else if self.0.get(end).is_some_and(InputValue::is_digits)
+        {
+            while self.0.get(end).is_some_and(InputValue::is_digits) || is(end, ".")
+            {
+                end += 1;
+            }
+        } else if end < self.0.len()
+        {
+            end += 1;
+        }
+
+        // powers go right to left so the exponent holds the rest of the chain
+        if power && is(end, "^") && end + 1 < self.0.len()
+        {
+            end = self.typed_script_end(end + 1, true);
+        }
+
+        end
+    }
+
+    pub fn to_latex(&self) -> String
+    {
+        let mut output = String::new();
+
+        // latex only scripts the next character or group so longer operands of typed scripts get braced
+        let mut opens = vec![false; self.0.len()];
+        let mut closes = vec![0; self.0.len()];
+        self.0.iter().enumerate().for_each(|(index, value)|
+        {
+            let InputValue::Value(x) = value else { return };
+
+            if x != "^" && x != "_"
+            {
+                return;
+            }
+
+            let start = index + 1;
+            let end = self.typed_script_end(start, x == "^");
+
+            let single = end == start + 1 && matches!(&self.0[start], InputValue::Value(x) if x.chars().count() == 1);
+            if end > start && !single
+            {
+                opens[index] = true;
+                closes[end - 1] += 1;
+            }
+        });
+
+        self.0.iter().enumerate().for_each(|(index, value)|
+        {
+            // a command followed by a letter needs a space to stay a separate command
+            let push_command = |output: &mut String, command: &str|
+            {
+                output.push_str(command);
+                output.push(' ');
+            };
+
+            match value
+            {
+                InputValue::Value(x) =>
+                {
+                    match x.as_ref()
+                    {
+                        "{" => output.push_str("\\{"),
+                        "}" => output.push_str("\\}"),
+                        "\\" => output.push_str("\\backslash "),
+                        // the unicode dot doesnt work in every latex setup
+                        "·" => push_command(&mut output, "\\cdot"),
+                        x => output.push_str(x)
+                    }
+                },
+                InputValue::Dots(kind) =>
+                {
+                    let command = match kind
+                    {
+                        DotsKind::Low => "\\ldots",
+                        DotsKind::Center => "\\cdots",
+                        DotsKind::Vertical => "\\vdots",
+                        DotsKind::Diagonal => "\\ddots"
+                    };
+
+                    push_command(&mut output, command);
+                },
+                InputValue::Unit(kind) =>
+                {
+                    let text = match kind
+                    {
+                        UnitKind::Degree => "^\\circ",
+                        UnitKind::Prime => "'",
+                        UnitKind::DoublePrime => "''"
+                    };
+
+                    output.push_str(text);
+                },
+                InputValue::Space(kind) =>
+                {
+                    // control symbols end by themselves so no space is needed
+                    let command = match kind
+                    {
+                        SpaceKind::NegativeThin => "\\!"
+                    };
+
+                    output.push_str(command);
+                },
+                InputValue::Answer{line_ref} =>
+                {
+                    output.push_str(&format!("\\mathrm{{{}}}", answer_name(*line_ref)));
+                },
+                InputValue::Extension(value) =>
+                {
+                    output.push_str(&value.export(ExportFormat::Latex));
+                },
+                InputValue::TextRun{content} =>
+                {
+                    let escaped: String = content.chars().map(|c|
+                    {
+                        match c
+                        {
+                            '{' | '}' | '%' | '$' | '&' | '#' | '_' => format!("\\{c}"),
+                            '\\' => "\\textbackslash{}".to_owned(),
+                            c => c.to_string()
+                        }
+                    }).collect();
+
+                    output.push_str(&format!("\\text{{{escaped}}}"));
+                },
+                InputValue::Fraction{top, bottom} =>
+                {
+                    output.push_str(&format!("\\frac{{{}}}{{{}}}", top.to_latex(), bottom.to_latex()));
+                },
+                InputValue::Cancel{content} =>
+                {
+                    output.push_str(&format!("\\cancel{{{}}}", content.to_latex()));
+                },
+                InputValue::Superscript{base, exponent} =>
+                {
+                    output.push_str(&format!("{{{}}}^{{{}}}", base.to_latex(), exponent.to_latex()));
+                },
+                InputValue::Subscript{base, subscript} =>
+                {
+                    output.push_str(&format!("{{{}}}_{{{}}}", base.to_latex(), subscript.to_latex()));
+                },
+                InputValue::SubSuperscript{base, subscript, superscript} =>
+                {
+                    output.push_str(&format!(
+                        "{{{}}}_{{{}}}^{{{}}}",
+                        base.to_latex(),
+                        subscript.to_latex(),
+                        superscript.to_latex()
+                    ));
+                },
+                InputValue::Sqrt{inner} =>
+                {
+                    output.push_str(&format!("\\sqrt{{{}}}", inner.to_latex()));
+                },
+                InputValue::NthRoot{degree, inner} =>
+                {
+                    output.push_str(&format!("\\sqrt[{}]{{{}}}", degree.to_latex(), inner.to_latex()));
+                },
+                // sized to the content like they are in the editor
+                InputValue::Paren{inner, kind} =>
+                {
+                    let (left, right) = match kind
+                    {
+                        BracketKind::Round => ("(", ")"),
+                        BracketKind::Square => ("[", "]"),
+                        BracketKind::Curly => ("\\{", "\\}")
+                    };
+
+                    output.push_str(&format!("\\left{left}{}\\right{right}", inner.to_latex()));
+                },
+                InputValue::Divided{left: left_part, right: right_part, kind} =>
+                {
+                    let (left, right) = match kind
+                    {
+                        BracketKind::Round => ("(", ")"),
+                        BracketKind::Square => ("[", "]"),
+                        BracketKind::Curly => ("\\{", "\\}")
+                    };
+
+                    output.push_str(&format!(
+                        "\\left{left}{}\\middle|{}\\right{right}",
+                        left_part.to_latex(),
+                        right_part.to_latex()
+                    ));
+                },
+                InputValue::Abs{inner} =>
+                {
+                    output.push_str(&format!("\\left|{}\\right|", inner.to_latex()));
+                },
+                InputValue::Sum{lower, upper, body} =>
+                {
+                    output.push_str(&format!("\\sum_{{{}}}^{{{}}} {}", lower.to_latex(), upper.to_latex(), body.to_latex()));
+                },
+                InputValue::Integral{lower, upper, body} =>
+                {
+                    output.push_str(&format!("\\int_{{{}}}^{{{}}} {}", lower.to_latex(), upper.to_latex(), body.to_latex()));
+                },
+                InputValue::Matrix{rows, bracket} =>
+                {
+                    let environment = match bracket
+                    {
+                        BracketKind::Round => "pmatrix",
+                        BracketKind::Square => "bmatrix",
+                        BracketKind::Curly => "Bmatrix"
+                    };
+
+                    let rows: Vec<String> = rows.iter().map(|cells|
+                    {
+                        cells.iter().map(InputValues::to_latex).collect::<Vec<_>>().join(" & ")
+                    }).collect();
+
+                    output.push_str(&format!("\\begin{{{environment}}} {} \\end{{{environment}}}", rows.join(" \\\\ ")));
+                },
+                InputValue::Table{rows, columns, rules} =>
+                {
+                    let (column_rule, row_rule) = if *rules { ("|", " \\hline") } else { ("", "") };
+
+                    let spec: Vec<String> = columns.iter().map(|align| align.as_char().to_string()).collect();
+                    let rows: Vec<String> = rows.iter().map(|cells|
+                    {
+                        cells.iter().map(InputValues::to_latex).collect::<Vec<_>>().join(" & ")
+                    }).collect();
+
+                    output.push_str(&format!(
+                        "\\begin{{array}}{{{}}} {} \\end{{array}}",
+                        spec.join(column_rule),
+                        rows.join(&format!(" \\\\{row_rule} "))
+                    ));
+                }
+            }
+
+            if opens[index]
+            {
+                output.push('{');
+            }
+
+            (0..closes[index]).for_each(|_| output.push('}'));
+        });
+
+        output.trim_end().to_owned()
+    }
+
+    pub fn to_typst(&self) -> String
+    {
+        let mut output = String::new();
+
+        // anything after a letter that could continue it into one longer name needs a space
+        let push = |output: &mut String, text: &str|
+        {
+            let joins = output.chars().last().is_some_and(char::is_alphabetic)
+                && text.starts_with(|c: char| c.is_alphanumeric() || c == '.' || c == '"');
+
+            if joins
+            {
+                output.push(' ');
+            }
+
+            output.push_str(text);
+        };
+
+        // quoted text is shown upright as is
+        let quoted = |text: &str|
+        {
+            let escaped: String = text.chars().map(|c|
+            {
+                match c
+                {
+                    '"' | '\\' => format!("\\{c}"),
+                    c => c.to_string()
+                }
+            }).collect();
+
+            format!("\"{escaped}\"")
+        };
+
+        self.0.iter().for_each(|value|
+        {
+            match value
+            {
+                InputValue::Value(x) =>
+                {
+                    let text: String = x.chars().map(|c|
+                    {
+                        match c
+                        {
+                            // a / would turn into a fraction and commas would split function arguments
+                            '/' | ',' | ';' | '#' | '$' | '\\' | '"' => format!("\\{c}"),
+                            c => c.to_string()
+                        }
+                    }).collect();
+
+                    push(&mut output, &text);
+                },
+                InputValue::Dots(kind) =>
+                {
+                    let name = match kind
+                    {
+                        DotsKind::Low => "dots.h",
+                        DotsKind::Center => "dots.c",
+                        DotsKind::Vertical => "dots.v",
+                        DotsKind::Diagonal => "dots.down"
+                    };
+
+                    push(&mut output, name);
+                },
+                InputValue::Unit(kind) =>
+                {
+                    let text = match kind
+                    {
+                        UnitKind::Degree => "degree",
+                        UnitKind::Prime => "'",
+                        UnitKind::DoublePrime => "''"
+                    };
+
+                    push(&mut output, text);
+                },
+                InputValue::Space(kind) =>
+                {
+                    let text = match kind
+                    {
+                        SpaceKind::NegativeThin => "#h(-1em / 6)"
+                    };
+
+                    output.push_str(text);
+                },
+                InputValue::Answer{line_ref} =>
+                {
+                    push(&mut output, &quoted(&answer_name(*line_ref)));
+                },
+                InputValue::Extension(value) =>
+                {
+                    push(&mut output, &value.export(ExportFormat::Typst));
+                },
+                InputValue::TextRun{content} =>
+                {
+                    push(&mut output, &quoted(content));
+                },
+                InputValue::Fraction{top, bottom} =>
+                {
+                    // an empty argument isnt allowed so empty parts get empty text
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "\"\"".to_owned() } else { values.to_typst() }
+                    };
+
+                    push(&mut output, &format!("frac({}, {})", part(top), part(bottom)));
+                },
+                InputValue::Cancel{content} =>
+                {
+                    push(&mut output, &format!("cancel({})", content.to_typst()));
+                },
+                InputValue::Superscript{base, exponent} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "\"\"".to_owned() } else { values.to_typst() }
+                    };
+
+                    let (base, exponent) = (part(base), part(exponent));
+
+                    // attach keeps longer parts grouped without showing parens around them
+                    if is_single(&base) && is_single(&exponent)
+                    {
+                        push(&mut output, &format!("{base}^{exponent}"));
+                    } else
+                    {
+                        push(&mut output, &format!("attach({base}, t: {exponent})"));
+                    }
+                },
+                InputValue::Subscript{base, subscript} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "\"\"".to_owned() } else { values.to_typst() }
+                    };
+
+                    let (base, subscript) = (part(base), part(subscript));
+
+                    if is_single(&base) && is_single(&subscript)
+                    {
+                        push(&mut output, &format!("{base}_{subscript}"));
+                    } else
+                    {
+                        push(&mut output, &format!("attach({base}, b: {subscript})"));
+                    }
+                },
+                InputValue::SubSuperscript{base, subscript, superscript} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "\"\"".to_owned() } else { values.to_typst() }
+                    };
+
+                    let (base, subscript, superscript) = (part(base), part(subscript), part(superscript));
+
+                    if [&base, &subscript, &superscript].into_iter().all(|text| is_single(text))
+                    {
+                        push(&mut output, &format!("{base}_{subscript}^{superscript}"));
+                    } else
+                    {
+                        push(&mut output, &format!("attach({base}, t: {superscript}, b: {subscript})"));
+                    }
+                },
+                InputValue::Sqrt{inner} =>
+                {
+                    let inner = if inner.0.is_empty() { "\"\"".to_owned() } else { inner.to_typst() };
+
+                    push(&mut output, &format!("sqrt({inner})"));
+                },
+                InputValue::NthRoot{degree, inner} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "\"\"".to_owned() } else { values.to_typst() }
+                    };
+
+                    push(&mut output, &format!("root({}, {})", part(degree), part(inner)));
+                },
+                // matched brackets already grow with their content
+                InputValue::Paren{inner, kind} =>
+                {
+                    let (left, right) = (kind.as_char(BracketSide::Left), kind.as_char(BracketSide::Right));
+
+                    push(&mut output, &format!("{left}{}{right}", inner.to_typst()));
+                },
+                InputValue::Divided{left: left_part, right: right_part, kind} =>
+                {
+                    let (left, right) = (kind.as_char(BracketSide::Left), kind.as_char(BracketSide::Right));
+
+                    push(&mut output, &format!("{left}{} mid(|) {}{right}", left_part.to_typst(), right_part.to_typst()));
+                },
+                InputValue::Abs{inner} =>
+                {
+                    let inner = if inner.0.is_empty() { "\"\"".to_owned() } else { inner.to_typst() };
+
+                    push(&mut output, &format!("abs({inner})"));
+                },
+                InputValue::Sum{lower, upper, body} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "\"\"".to_owned() } else { values.to_typst() }
+                    };
+
+                    push(&mut output, &format!("sum_({})^({}) {}", part(lower), part(upper), part(body)));
+                },
+                InputValue::Integral{lower, upper, body} =>
+                {
+                    let part = |values: &InputValues|
+                    {
+                        if values.0.is_empty() { "\"\"".to_owned() } else { values.to_typst() }
+                    };
+
+                    push(&mut output, &format!("integral_({})^({}) {}", part(lower), part(upper), part(body)));
+                },
+                InputValue::Matrix{rows, bracket} =>
+                {
+                    let rows: Vec<String> = rows.iter().map(|cells|
+                    {
+                        cells.iter().map(|cell|
+                        {
+                            if cell.0.is_empty() { "\"\"".to_owned() } else { cell.to_typst() }
+                        }).collect::<Vec<_>>().join(", ")
+                    }).collect();
+
+                    let delimiter = bracket.as_char(BracketSide::Left);
+
+                    push(&mut output, &format!("mat(delim: \"{delimiter}\", {})", rows.join("; ")));
+                },
+                // typst lines a whole matrix up one way so its the first columns alignment
+                InputValue::Table{rows: cells, columns, rules} =>
+                {
+                    let rows: Vec<String> = cells.iter().map(|cells|
+                    {
+                        cells.iter().map(|cell|
+                        {
+                            if cell.0.is_empty() { "\"\"".to_owned() } else { cell.to_typst() }
+                        }).collect::<Vec<_>>().join(", ")
+                    }).collect();
+
+                    let align = match columns[0]
+                    {
+                        ColumnAlign::Left => "left",
+                        ColumnAlign::Center => "center",
+                        ColumnAlign::Right => "right"
+                    };
+
+                    let augment = if *rules
+                    {
+                        let lines = |count: usize| (1..count).map(|index| format!("{index}, ")).collect::<String>();
+
+                        format!("augment: (hline: ({}), vline: ({})), ", lines(cells.len()), lines(columns.len()))
+                    } else
+                    {
+                        String::new()
+                    };
+
+                    push(&mut output, &format!("mat(delim: #none, align: #{align}, {augment}{})", rows.join("; ")));
+                }
+            }
+        });
+
+        output
+    }
+}
+
+// formats a number with at most precision decimal places and no trailing zeros
+pub fn format_number(value: f64, precision: usize, decimal_separator: char) -> String
+{
+    let text = format!("{value:.precision$}");
+
+    let text = if text.contains('.')
+    {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else
+    {
+        &text
+    };
+
+    if text == "-0" { "0".to_owned() } else { text.replace('.', &decimal_separator.to_string()) }
+}
+
+// every line followed by = and its evaluated value, lines that dont evaluate or are in skipped are left out
+pub(crate) fn export_with_results(
+    lines: &[Line],
+    format: ExportFormat,
+    precision: usize,
+    decimal_separator: char,
+    skipped: &[usize]
+) -> String
+{
+    let results = evaluate::evaluate_lines(lines, decimal_separator);
+
+    lines.iter().zip(results).enumerate().filter_map(|(index, (Line{values, label, ..}, result))|
+    {
+        if skipped.contains(&index)
+        {
+            return None;
+        }
+
+        let result = format_number(result.ok()?, precision, decimal_separator);
+
+        // plain text has nothing to reference it from
+        let label = match (format, label)
+        {
+            (ExportFormat::Latex, Some(label)) => format!(" \\label{{{label}}}"),
+            _ => String::new()
+        };
+
+        Some(format!("{} = {result}{label}", values.export(format)))
+    }).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::tests::{line, fraction};
+
+    fn values(parts: Vec<InputValue>) -> InputValues
+    {
+        InputValues(parts)
+    }
+
+    fn with(mut values: InputValues, more: &str) -> InputValues
+    {
+        values.0.extend(line(more).0);
+
+        values
+    }
+
+    fn cases() -> Vec<InputValues>
+    {
+        vec![
+            values(vec![fraction("1+2", "3")]),
+            values(vec![fraction("6", "2*3")]),
+            values(vec![fraction("-1", "2")]),
+            values(vec![fraction("(1+2)", "(3)")]),
+            with(values(vec![fraction("1", "2")]), "3"),
+            with(values(vec![fraction("1", "2")]), "^2"),
+            {
+                let mut values = line("1/");
+                values.0.push(fraction("1", "2"));
+
+                values
+            },
+            {
+                let mut values = line("2^");
+                values.0.push(fraction("1", "2"));
+
+                values
+            },
+            values(vec![InputValue::Fraction{
+                top: values(vec![fraction("1", "2")]),
+                bottom: values(vec![fraction("3", "4+1")])
+            }]),
+            line("2^12"),
+            line("2^-1.5"),
+            line("2^3^2"),
+            line("2^(1+1)3"),
+            line("2^-(1+1)")
+        ]
+    }
+
+    #[test]
+    fn plain_keeps_meaning()
+    {
+        cases().into_iter().for_each(|case|
+        {
+            let plain = case.to_plain();
+
+            assert_eq!(case.evaluate(), line(&plain).evaluate(), "{plain}");
+        });
+    }
+
+    #[test]
+    fn latex_keeps_meaning()
+    {
+        cases().into_iter().for_each(|case|
+        {
+            let latex = case.to_latex();
+
+            assert_eq!(case.evaluate(), InputValues::from_latex(&latex).unwrap().evaluate(), "{latex}");
+        });
+    }
+
+    #[test]
+    fn sum_over_something()
+    {
+        assert_eq!(values(vec![fraction("a+b", "c")]).to_plain(), "(a+b)/c");
+        assert_eq!(values(vec![fraction("a", "b*c")]).to_plain(), "a/(b*c)");
+    }
+
+    #[test]
+    fn typed_scripts()
+    {
+        assert_eq!(line("x^2").to_latex(), "x^2");
+        assert_eq!(line("x^12").to_latex(), "x^{12}");
+        assert_eq!(line("x_12").to_latex(), "x_{12}");
+        assert_eq!(line("x_1^2").to_latex(), "x_1^2");
+        assert_eq!(line("2^3^2").to_latex(), "2^{3^2}");
+        assert_eq!(line("2^(1+1)3").to_latex(), "2^{(1+1)}3");
+        assert_eq!(line("2^").to_latex(), "2^");
+
+        let mut power = line("2^");
+        power.0.push(fraction("1", "2"));
+
+        assert_eq!(power.to_latex(), "2^{\\frac{1}{2}}");
+    }
+}
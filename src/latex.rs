@@ -0,0 +1,339 @@
+// reads latex back into values, only the parts the editor can make are understood
+use std::fmt;
+
+use crate::{InputValue, InputValues};
+
+
+// braces nested deeper than this are almost certainly not math
+const MAX_NESTING: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind
+{
+    Unclosed(char),
+    UnexpectedClose(char),
+    MissingArgument(String),
+    UnknownCommand(String),
+    TooDeep
+}
+
+// position counts characters from the start of the text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError
+{
+    pub position: usize,
+    pub kind: ParseErrorKind
+}
+
+impl fmt::Display for ParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let position = self.position;
+
+        match &self.kind
+        {
+            ParseErrorKind::Unclosed(c) => write!(f, "{c:?} at {position} is never closed"),
+            ParseErrorKind::UnexpectedClose(c) => write!(f, "{c:?} at {position} doesnt close anything"),
+            ParseErrorKind::MissingArgument(x) => write!(f, "{x} at {position} is missing an argument"),
+            ParseErrorKind::UnknownCommand(x) => write!(f, "unknown command \\{x} at {position}"),
+            ParseErrorKind::TooDeep => write!(f, "braces at {position} are nested too deep")
+        }
+    }
+}
+
+struct Parser
+{
+    chars: Vec<char>,
+    position: usize,
+    nesting: usize
+}
+
+impl Parser
+{
+    fn error<T>(&self, position: usize, kind: ParseErrorKind) -> Result<T, ParseError>
+    {
+        Err(ParseError{position, kind})
+    }
+
+    fn peek(&self) -> Option<char>
+    {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self)
+    {
+        while self.peek().is_some_and(char::is_whitespace)
+        {
+            self.position += 1;
+        }
+    }
+
+    // values until the closing character of the group opened at the given position
+    fn values(&mut self, close: Option<(char, usize)>) -> Result<InputValues, ParseError>
+    {
+        let mut values = Vec::new();
+
+        loop
+        {
+            self.skip_whitespace();
+
+            let position = self.position;
+            let Some(c) = self.peek()
+            else
+            {
+                return match close
+                {
+                    Some((close, opened)) =>
+                    {
+                        let open = if close == '}' { '{' } else { '[' };
+
+                        self.error(opened, ParseErrorKind::Unclosed(open))
+                    },
+                    None => Ok(InputValues(values))
+                };
+            };
+
+            self.position += 1;
+
+            match c
+            {
+                c if close.is_some_and(|(close, _)| close == c) => return Ok(InputValues(values)),
+                '}' => return self.error(position, ParseErrorKind::UnexpectedClose(c)),
+                '{' =>
+                {
+                    let group = self.group('}', position)?;
+
+                    // a braced group right before a script is its base like to_latex writes it
+                    self.skip_whitespace();
+                    if matches!(self.peek(), Some('^' | '_'))
+                    {
+                        let script = self.script(group)?;
+
+                        values.push(script);
+                    } else
+                    {
+                        values.extend(group.0);
+                    }
+                },
+                '^' | '_' =>
+                {
+                    self.position = position;
+
+                    let base = values.pop().map(|value| InputValues(vec![value])).unwrap_or_else(|| InputValues(Vec::new()));
+                    let script = self.script(base)?;
+
+                    values.push(script);
+                },
+                '\\' => values.push(self.command(position)?),
+                c => values.push(InputValue::Value(c.to_string()))
+            }
+        }
+    }
+
+    fn group(&mut self, close: char, opened: usize) -> Result<InputValues, ParseError>
+    {
+        if self.nesting >= MAX_NESTING
+        {
+            return self.error(opened, ParseErrorKind::TooDeep);
+        }
+
+        self.nesting += 1;
+        let values = self.values(Some((close, opened)));
+        self.nesting -= 1;
+
+        values
+    }
+
+    // a braced group or a single character like in x^2
+    fn argument(&mut self, name: &str, position: usize) -> Result<InputValues, ParseError>
+    {
+        self.skip_whitespace();
+
+        let opened = self.position;
+        match self.peek()
+        {
+            Some('{') =>
+            {
+                self.position += 1;
+
+                self.group('}', opened)
+            },
+            Some(c) if !matches!(c, '}' | '^' | '_' | '\\') =>
+            {
+                self.position += 1;
+
+                Ok(InputValues(vec![InputValue::Value(c.to_string())]))
+            },
+            _ => self.error(position, ParseErrorKind::MissingArgument(name.to_owned()))
+        }
+    }
+
+    // a superscript, subscript or both on the base, starting at the first script character
+    fn script(&mut self, base: InputValues) -> Result<InputValue, ParseError>
+    {
+        let mut superscript = None;
+        let mut subscript = None;
+
+        loop
+        {
+            self.skip_whitespace();
+
+            let position = self.position;
+            let slot = match self.peek()
+            {
+                Some('^') if superscript.is_none() => &mut superscript,
+                Some('_') if subscript.is_none() => &mut subscript,
+                _ => break
+            };
+
+            let name = self.chars[position].to_string();
+
+            self.position += 1;
+            *slot = Some(self.argument(&name, position)?);
+        }
+
+        let value = match (subscript, superscript)
+        {
+            (Some(subscript), Some(superscript)) => InputValue::SubSuperscript{base, subscript, superscript},
+            (Some(subscript), None) => InputValue::Subscript{base, subscript},
+            (None, Some(exponent)) => InputValue::Superscript{base, exponent},
+            (None, None) => unreachable!()
+        };
+
+        Ok(value)
+    }
+
+    // a command starting at the backslash
+    fn command(&mut self, position: usize) -> Result<InputValue, ParseError>
+    {
+        let name: String = self.chars[self.position..].iter().take_while(|c| c.is_ascii_alphabetic()).collect();
+
+        // control symbols are a single character that isnt a letter
+        let name = if name.is_empty()
+        {
+            let Some(c) = self.peek()
+            else
+            {
+                return self.error(position, ParseErrorKind::UnknownCommand(String::new()));
+            };
+
+            c.to_string()
+        } else
+        {
+            name
+        };
+
+        self.position += name.chars().count();
+
+        let command = format!("\\{name}");
+        match name.as_ref()
+        {
+            "frac" =>
+            {
+                let top = self.argument(&command, position)?;
+                let bottom = self.argument(&command, position)?;
+
+                Ok(InputValue::Fraction{top, bottom})
+            },
+            "sqrt" =>
+            {
+                self.skip_whitespace();
+
+                let opened = self.position;
+                if self.peek() == Some('[')
+                {
+                    self.position += 1;
+
+                    let degree = self.group(']', opened)?;
+                    let inner = self.argument(&command, position)?;
+
+                    Ok(InputValue::NthRoot{degree, inner})
+                } else
+                {
+                    let inner = self.argument(&command, position)?;
+
+                    Ok(InputValue::Sqrt{inner})
+                }
+            },
+            "cdot" => Ok(InputValue::Value("·".to_owned())),
+            "backslash" => Ok(InputValue::Value("\\".to_owned())),
+            "{" | "}" => Ok(InputValue::Value(name)),
+            _ => self.error(position, ParseErrorKind::UnknownCommand(name))
+        }
+    }
+}
+
+impl InputValues
+{
+    pub fn from_latex(text: &str) -> Result<Self, ParseError>
+    {
+        let mut parser = Parser{chars: text.chars().collect(), position: 0, nesting: 0};
+
+        parser.values(None)
+    }
+}
+
+// pasted text that should be read as latex instead of typed in
+pub fn looks_like_latex(text: &str) -> bool
+{
+    ["\\frac", "\\sqrt", "^{", "_{", "}^", "}_"].iter().any(|x| text.contains(x))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::ProgramState;
+
+    #[test]
+    fn round_trip()
+    {
+        [
+            "\\frac{1}{2}",
+            "{x}^{2}+1",
+            "{a}_{i}^{n}",
+            "{x}_{1}",
+            "\\sqrt{\\frac{a}{b}}",
+            "\\sqrt[3]{x}",
+            "{\\frac{1}{x}}^{2}",
+            "a\\cdot b"
+        ].into_iter().for_each(|text|
+        {
+            assert_eq!(InputValues::from_latex(text).unwrap().to_latex(), text);
+        });
+
+        // scripts without braces come back out in the editors own form
+        assert_eq!(InputValues::from_latex("x^2_1").unwrap().to_latex(), "{x}_{1}^{2}");
+    }
+
+    #[test]
+    fn errors()
+    {
+        let error = InputValues::from_latex("\\frac{1}{2").unwrap_err();
+        assert_eq!(error, ParseError{position: 8, kind: ParseErrorKind::Unclosed('{')});
+
+        let error = InputValues::from_latex("a}").unwrap_err();
+        assert_eq!(error, ParseError{position: 1, kind: ParseErrorKind::UnexpectedClose('}')});
+
+        let error = InputValues::from_latex("\\frac{1}").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::MissingArgument("\\frac".to_owned()));
+
+        let error = InputValues::from_latex("\\foo").unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnknownCommand("foo".to_owned()));
+    }
+
+    #[test]
+    fn paste()
+    {
+        let mut state = ProgramState::new();
+        state.paste("1+\\frac{a}{b}");
+
+        assert_eq!(state.lines[0].values.to_plain(), "1+a/b");
+
+        // not valid latex so it gets typed in like any other text
+        state.paste("x^{");
+
+        assert_eq!(state.lines[0].values.to_plain(), "1+(a/b)x^({})");
+    }
+}
@@ -0,0 +1,7855 @@
+use std::{
+    fmt,
+    mem,
+    borrow::Cow,
+    ops::Range,
+    collections::HashMap,
+    hash::{Hash, Hasher, DefaultHasher}
+};
+
+#[cfg(feature = "sdl")]
+use sdl2::{rect::Rect, ttf::Font};
+
+use custom::{CustomValue, CustomConstructor};
+use compact::CompactError;
+use evaluate::EvalError;
+use export::ExportFormat;
+
+pub mod evaluate;
+pub mod export;
+pub mod dump;
+pub mod custom;
+pub mod compact;
+pub mod latex;
+pub mod png;
+pub mod svg;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+
+
+pub const FONT_SIZE: u32 = 20;
+
+// range the font size can be zoomed in
+pub const MIN_FONT_SIZE: u32 = 8;
+pub const MAX_FONT_SIZE: u32 = 96;
+
+// what the symbol picker offers, in the order of its grid
+const SYMBOLS: &[&str] = &[
+    "±", "×", "÷", "·", "≠", "≈", "≤", "≥",
+    "∞", "π", "θ", "α", "β", "γ", "δ", "ε",
+    "λ", "μ", "σ", "φ", "ω", "Σ", "Δ", "Ω",
+    "√", "∫", "∂", "∇", "∈", "∉", "⊂", "∪",
+    "∩", "→", "⇒", "⇔", "∀", "∃", "¬", "∧"
+];
+
+const SYMBOL_COLUMNS: usize = 8;
+
+// what cycling a lines color goes through after the default one
+const LINE_COLORS: &[[u8; 3]] = &[[200, 30, 30], [30, 90, 220], [20, 140, 50]];
+
+// how many seconds the lines of a new structure take to grow in
+const INSERT_ANIMATION: f32 = 0.1;
+
+// every builtin \command
+const COMMANDS: &[&str] = &[
+    "ldots", "cdots", "vdots", "ddots",
+    "degree", "prime", "dprime",
+    "text", "cancel",
+    "!", "ans", "cdot", "middle", "table"
+];
+
+// commands made of a single symbol like \! instead of letters
+const CONTROL_SYMBOLS: &[&str] = &["!"];
+
+// marks copied values on the clipboard so pasting them keeps their structure
+const CLIPBOARD_PREFIX: &str = "matheditor:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FractionStyle
+{
+    Stacked,
+    // a⁄b with the parts on either side of a slanted line
+    Beveled,
+    // a/b on one line
+    Inline
+}
+
+impl FractionStyle
+{
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            Self::Stacked => Self::Beveled,
+            Self::Beveled => Self::Inline,
+            Self::Inline => Self::Stacked
+        }
+    }
+}
+
+// the sizes tex switches between, each script level is exponent_scale smaller than the one before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleLevel
+{
+    Display,
+    Text,
+    Script,
+    ScriptScript
+}
+
+// what a part gets rendered in, passed down through the structures its inside of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MathStyle
+{
+    pub level: StyleLevel,
+    // inside a denominator or a script, scripts come out cramped_scale smaller
+    pub cramped: bool
+}
+
+impl MathStyle
+{
+    pub const DISPLAY: Self = Self{level: StyleLevel::Display, cramped: false};
+
+    // stacked fractions stay full size in the editor so text style doesnt go down to script like in tex
+    pub fn numerator(self) -> Self
+    {
+        let level = if self.level == StyleLevel::Display { StyleLevel::Text } else { self.level };
+
+        Self{level, ..self}
+    }
+
+    pub fn denominator(self) -> Self
+    {
+        Self{cramped: true, ..self.numerator()}
+    }
+
+    // scripts of scripts are cramped and it bottoms out at scriptscript
+    pub fn script(self) -> Self
+    {
+        match self.level
+        {
+            StyleLevel::Display | StyleLevel::Text => Self{level: StyleLevel::Script, ..self},
+            StyleLevel::Script | StyleLevel::ScriptScript => Self{level: StyleLevel::ScriptScript, cramped: true}
+        }
+    }
+
+    // size of text relative to the font size
+    pub fn scale(self, layout: &LayoutConfig) -> f32
+    {
+        let scale = match self.level
+        {
+            StyleLevel::Display | StyleLevel::Text => return 1.0,
+            StyleLevel::Script => layout.exponent_scale,
+            StyleLevel::ScriptScript => layout.exponent_scale * layout.exponent_scale
+        };
+
+        if self.cramped { scale * layout.cramped_scale } else { scale }
+    }
+
+    // how much smaller text in other is than text in this
+    pub fn relative_scale(self, other: Self, layout: &LayoutConfig) -> f32
+    {
+        other.scale(layout) / self.scale(layout)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LayoutConfig
+{
+    // gap between the fraction bar and its numerator/denominator relative to the font size
+    pub fraction_gap: f32,
+    // width of an empty fraction part relative to the font size
+    pub placeholder_width: f32,
+    pub line_thickness: u32,
+    pub cursor_width: u32,
+    pub fraction_style: FractionStyle,
+    // strip on the right showing what each line evaluates to, relative to the font size
+    pub gutter_width: f32,
+    // space for equation numbers at the right edge, relative to the font size
+    pub numbering_width: f32,
+    // space kept free around content that doesnt fit the window, relative to the font size
+    pub margin: f32,
+    // gap between consecutive lines, relative to the font size
+    pub line_spacing: f32,
+    // cursor colors by how many structures its inside of, the last one is used for anything deeper
+    pub cursor_colors: Vec<[u8; 3]>,
+    // lines wider than this continue on more rows, relative to the font size
+    pub wrap_width: Option<f32>,
+    // how far rows after the first are pushed in, relative to the font size
+    pub wrap_indent: f32,
+    // gap between factors multiplied by being next to each other like 2x, relative to the font size
+    pub implicit_product_space: f32,
+    // size of the text in exponents relative to the font size
+    pub exponent_scale: f32,
+    // scripts in a cramped style are this much smaller again, like in a denominator or another script
+    pub cramped_scale: f32,
+    // how far a superscripts axis is raised above the axis of its base relative to the font size
+    pub superscript_shift: f32,
+    // how far a subscripts axis is lowered below the axis of its base relative to the font size
+    pub subscript_shift: f32,
+    // size of the degree of a root relative to the font size
+    pub root_degree_scale: f32,
+    // width of a stretching bracket relative to the font size
+    pub bracket_width: f32,
+    // space between the columns of a matrix relative to the font size
+    pub matrix_gap: f32,
+    // smallest size of the summation sign relative to the font size, it grows with taller bodies
+    pub sum_scale: f32,
+    // smallest size of the integral sign relative to the font size, it grows with taller bodies
+    pub integral_scale: f32,
+    // size of the text in pixels, the fonts have to be loaded at this size
+    pub font_size: u32
+}
+
+impl Default for LayoutConfig
+{
+    fn default() -> Self
+    {
+        Self{
+            fraction_gap: 0.15,
+            placeholder_width: 0.6,
+            line_thickness: 2,
+            cursor_width: 4,
+            fraction_style: FractionStyle::Stacked,
+            gutter_width: 8.0,
+            numbering_width: 3.0,
+            margin: 0.5,
+            line_spacing: 0.25,
+            cursor_colors: vec![[0, 0, 0], [30, 90, 220]],
+            wrap_width: None,
+            wrap_indent: 2.0,
+            implicit_product_space: 1.0 / 18.0,
+            exponent_scale: 0.5,
+            cramped_scale: 0.8,
+            // around where tex puts them for most fonts
+            superscript_shift: 0.45,
+            subscript_shift: 0.3,
+            root_degree_scale: 0.6,
+            bracket_width: 0.4,
+            matrix_gap: 0.6,
+            sum_scale: 1.5,
+            integral_scale: 1.2,
+            font_size: FONT_SIZE
+        }
+    }
+}
+
+// floats get hashed by their bits, only used to tell if a frame would look different
+impl Hash for LayoutConfig
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        // listed out so a new field cant be forgotten here
+        let Self{
+            fraction_gap,
+            placeholder_width,
+            line_thickness,
+            cursor_width,
+            fraction_style,
+            gutter_width,
+            numbering_width,
+            margin,
+            line_spacing,
+            cursor_colors,
+            wrap_width,
+            wrap_indent,
+            implicit_product_space,
+            exponent_scale,
+            cramped_scale,
+            superscript_shift,
+            subscript_shift,
+            root_degree_scale,
+            bracket_width,
+            matrix_gap,
+            sum_scale,
+            integral_scale,
+            font_size
+        } = self;
+
+        [
+            fraction_gap,
+            placeholder_width,
+            gutter_width,
+            numbering_width,
+            margin,
+            line_spacing,
+            wrap_indent,
+            implicit_product_space,
+            exponent_scale,
+            cramped_scale,
+            superscript_shift,
+            subscript_shift,
+            root_degree_scale,
+            bracket_width,
+            matrix_gap,
+            sum_scale,
+            integral_scale
+        ].map(|value| value.to_bits()).hash(state);
+
+        wrap_width.map(f32::to_bits).hash(state);
+        (line_thickness, cursor_width, fraction_style, cursor_colors, font_size).hash(state);
+    }
+}
+
+impl LayoutConfig
+{
+    // distance between the axes of a script and its base, bases taller than a line push it out further
+    pub fn script_shift(&self, shift: f32, base_height: f32) -> f32
+    {
+        let font_size = self.font_size as f32;
+
+        shift * font_size + (base_height - font_size).max(0.0) / 2.0
+    }
+
+    pub fn fraction_gap(&self) -> f32
+    {
+        self.font_size as f32 * self.fraction_gap
+    }
+
+    pub fn placeholder_width(&self) -> f32
+    {
+        self.font_size as f32 * self.placeholder_width
+    }
+
+    pub fn gutter_width(&self) -> f32
+    {
+        self.font_size as f32 * self.gutter_width
+    }
+
+    pub fn numbering_width(&self) -> f32
+    {
+        self.font_size as f32 * self.numbering_width
+    }
+
+    pub fn margin(&self) -> f32
+    {
+        self.font_size as f32 * self.margin
+    }
+
+    pub fn line_spacing(&self) -> f32
+    {
+        self.font_size as f32 * self.line_spacing
+    }
+
+    pub fn implicit_product_space(&self) -> f32
+    {
+        self.font_size as f32 * self.implicit_product_space
+    }
+
+    pub fn bracket_width(&self) -> f32
+    {
+        self.font_size as f32 * self.bracket_width
+    }
+
+    pub fn matrix_gap(&self) -> f32
+    {
+        self.font_size as f32 * self.matrix_gap
+    }
+
+    pub fn wrap_width(&self) -> Option<f32>
+    {
+        self.wrap_width.map(|width| self.font_size as f32 * width)
+    }
+
+    pub fn wrap_indent(&self) -> f32
+    {
+        self.font_size as f32 * self.wrap_indent
+    }
+
+    pub fn cursor_color(&self, depth: usize) -> [u8; 3]
+    {
+        self.cursor_colors.get(depth).or(self.cursor_colors.last()).copied().unwrap_or([0, 0, 0])
+    }
+}
+
+// measures text for the layout, this is the only thing the core needs from a font
+pub trait TextMeasure
+{
+    fn text_size(&self, text: &str) -> (u32, u32);
+    fn has_glyph(&self, c: char) -> bool;
+    fn height(&self) -> u32;
+}
+
+#[cfg(feature = "sdl")]
+impl TextMeasure for Font<'_, '_>
+{
+    fn text_size(&self, text: &str) -> (u32, u32)
+    {
+        self.size_of(text).unwrap()
+    }
+
+    fn has_glyph(&self, c: char) -> bool
+    {
+        self.find_glyph(c).is_some()
+    }
+
+    fn height(&self) -> u32
+    {
+        Font::height(self) as u32
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RenderValue<'a>
+{
+    Text{x: f32, y: f32, text: Cow<'a, str>},
+    Line{x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32},
+    Dots{x: f32, y: f32, kind: DotsKind},
+    Unit{x: f32, y: f32, kind: UnitKind},
+    // dashed outline where an empty slot still needs input
+    Placeholder{x: f32, y: f32, width: f32, height: f32},
+    // opaque background that overlays get drawn on
+    Panel{x: f32, y: f32, width: f32, height: f32},
+    Cursor{x: f32, y: f32, color: [u8; 3]},
+    // root sign in the first half height of the width with the bar over the rest along the top
+    Radical{x: f32, y: f32, width: f32, height: f32, thickness: f32},
+    // stretched to the height of whats inside, as wide as LayoutConfig::bracket_width
+    Bracket{x: f32, y: f32, height: f32, side: BracketSide, kind: BracketKind},
+    // drawn faintly, for things that are shown but not typed yet
+    Ghost(Box<RenderValue<'a>>),
+    // drawn in a color other than the default one
+    Colored{color: [u8; 3], value: Box<RenderValue<'a>>},
+    // text drawn at this fraction of the font size, like exponents
+    Scaled{scale: f32, value: Box<RenderValue<'a>>}
+}
+
+impl RenderValue<'_>
+{
+    pub fn horizontal_line(x: f32, y: f32, width: f32, layout: &LayoutConfig) -> Self
+    {
+        Self::Line{x1: x, y1: y, x2: x + width, y2: y, thickness: layout.line_thickness as f32}
+    }
+
+    pub fn into_owned(self) -> RenderValue<'static>
+    {
+        match self
+        {
+            Self::Text{x, y, text} => RenderValue::Text{x, y, text: Cow::Owned(text.into_owned())},
+            Self::Line{x1, y1, x2, y2, thickness} => RenderValue::Line{x1, y1, x2, y2, thickness},
+            Self::Dots{x, y, kind} => RenderValue::Dots{x, y, kind},
+            Self::Unit{x, y, kind} => RenderValue::Unit{x, y, kind},
+            Self::Placeholder{x, y, width, height} => RenderValue::Placeholder{x, y, width, height},
+            Self::Panel{x, y, width, height} => RenderValue::Panel{x, y, width, height},
+            Self::Cursor{x, y, color} => RenderValue::Cursor{x, y, color},
+            Self::Radical{x, y, width, height, thickness} => RenderValue::Radical{x, y, width, height, thickness},
+            Self::Bracket{x, y, height, side, kind} => RenderValue::Bracket{x, y, height, side, kind},
+            Self::Ghost(value) => RenderValue::Ghost(Box::new(value.into_owned())),
+            Self::Colored{color, value} => RenderValue::Colored{color, value: Box::new(value.into_owned())},
+            Self::Scaled{scale, value} => RenderValue::Scaled{scale, value: Box::new(value.into_owned())}
+        }
+    }
+
+    // corners of a radical from the start of its tick to the end of its bar
+    pub fn radical_points(x: f32, y: f32, width: f32, height: f32) -> [(f32, f32); 5]
+    {
+        let sign_width = height / 2.0;
+
+        [
+            (x, y + height * 0.6),
+            (x + sign_width * 0.25, y + height * 0.5),
+            (x + sign_width * 0.5, y + height),
+            (x + sign_width, y),
+            (x + width, y)
+        ]
+    }
+
+    pub fn new_cursor(x: f32, y: f32, layout: &LayoutConfig) -> Self
+    {
+        Self::Cursor{x, y: y - layout.font_size as f32 / 2.0, color: [0, 0, 0]}
+    }
+
+    pub fn shift(&mut self, shift_x: f32, shift_y: f32)
+    {
+        match self
+        {
+            Self::Text{x, y, ..} =>
+            {
+                *x += shift_x;
+                *y += shift_y;
+            },
+            Self::Line{x1, y1, x2, y2, ..} =>
+            {
+                *x1 += shift_x;
+                *y1 += shift_y;
+                *x2 += shift_x;
+                *y2 += shift_y;
+            },
+            Self::Dots{x, y, ..} =>
+            {
+                *x += shift_x;
+                *y += shift_y;
+            },
+            Self::Unit{x, y, ..} =>
+            {
+                *x += shift_x;
+                *y += shift_y;
+            },
+            Self::Placeholder{x, y, ..} =>
+            {
+                *x += shift_x;
+                *y += shift_y;
+            },
+            Self::Panel{x, y, ..} =>
+            {
+                *x += shift_x;
+                *y += shift_y;
+            },
+            Self::Cursor{x, y, ..} =>
+            {
+                *x += shift_x;
+                *y += shift_y;
+            },
+            Self::Radical{x, y, ..} =>
+            {
+                *x += shift_x;
+                *y += shift_y;
+            },
+            Self::Bracket{x, y, ..} =>
+            {
+                *x += shift_x;
+                *y += shift_y;
+            },
+            Self::Ghost(value)
+            | Self::Colored{value, ..}
+            | Self::Scaled{value, ..} => value.shift(shift_x, shift_y)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DotsKind
+{
+    Low,
+    Center,
+    Vertical,
+    Diagonal
+}
+
+impl DotsKind
+{
+    pub fn as_char(self) -> char
+    {
+        match self
+        {
+            Self::Low => '…',
+            Self::Center => '⋯',
+            Self::Vertical => '⋮',
+            Self::Diagonal => '⋱'
+        }
+    }
+
+    pub fn dot_size(font_size: u32) -> u32
+    {
+        (font_size / 8).max(2)
+    }
+
+    // centers of the dots relative to the top left of the token
+    pub fn dots(self, width: f32, height: f32) -> [(f32, f32); 3]
+    {
+        let xs = [width / 6.0, width / 2.0, width * 5.0 / 6.0];
+        let ys = [height / 4.0, height / 2.0, height * 3.0 / 4.0];
+
+        match self
+        {
+            Self::Low => xs.map(|x| (x, ys[2])),
+            Self::Center => xs.map(|x| (x, ys[1])),
+            Self::Vertical => ys.map(|y| (xs[1], y)),
+            Self::Diagonal => [(xs[0], ys[0]), (xs[1], ys[1]), (xs[2], ys[2])]
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BracketKind
+{
+    Round,
+    Square,
+    Curly
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BracketSide
+{
+    Left,
+    Right
+}
+
+impl BracketKind
+{
+    pub fn from_open(c: &str) -> Option<Self>
+    {
+        match c
+        {
+            "(" => Some(Self::Round),
+            "[" => Some(Self::Square),
+            "{" => Some(Self::Curly),
+            _ => None
+        }
+    }
+
+    pub fn from_close(c: &str) -> Option<Self>
+    {
+        match c
+        {
+            ")" => Some(Self::Round),
+            "]" => Some(Self::Square),
+            "}" => Some(Self::Curly),
+            _ => None
+        }
+    }
+
+    pub fn as_char(self, side: BracketSide) -> char
+    {
+        match (self, side)
+        {
+            (Self::Round, BracketSide::Left) => '(',
+            (Self::Round, BracketSide::Right) => ')',
+            (Self::Square, BracketSide::Left) => '[',
+            (Self::Square, BracketSide::Right) => ']',
+            (Self::Curly, BracketSide::Left) => '{',
+            (Self::Curly, BracketSide::Right) => '}'
+        }
+    }
+
+    // outline of the bracket from top to bottom relative to the top left of its box
+    pub fn points(self, side: BracketSide, width: f32, height: f32) -> Vec<(f32, f32)>
+    {
+        let left: Vec<(f32, f32)> = match self
+        {
+            Self::Round =>
+            {
+                (0..=16).map(|i|
+                {
+                    let t = i as f32 / 16.0;
+
+                    (width * (0.8 - 0.6 * (t * std::f32::consts::PI).sin()), height * t)
+                }).collect()
+            },
+            Self::Square =>
+            {
+                vec![(width * 0.8, 0.0), (width * 0.3, 0.0), (width * 0.3, height), (width * 0.8, height)]
+            },
+            Self::Curly =>
+            {
+                [(0.8, 0.0), (0.5, 0.1), (0.5, 0.4), (0.2, 0.5), (0.5, 0.6), (0.5, 0.9), (0.8, 1.0)]
+                    .into_iter()
+                    .map(|(x, y)| (width * x, height * y))
+                    .collect()
+            }
+        };
+
+        match side
+        {
+            BracketSide::Left => left,
+            BracketSide::Right => left.into_iter().map(|(x, y)| (width - x, y)).collect()
+        }
+    }
+}
+
+// how the cells of a table column line up inside it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColumnAlign
+{
+    Left,
+    #[default]
+    Center,
+    Right
+}
+
+impl ColumnAlign
+{
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            Self::Left => Self::Center,
+            Self::Center => Self::Right,
+            Self::Right => Self::Left
+        }
+    }
+
+    // the letter for it in the column spec of a latex array
+    pub fn as_char(self) -> char
+    {
+        match self
+        {
+            Self::Left => 'l',
+            Self::Center => 'c',
+            Self::Right => 'r'
+        }
+    }
+
+    // how far into its column a cell goes when the column is wider than it by space
+    pub fn offset(self, space: f32) -> f32
+    {
+        match self
+        {
+            Self::Left => 0.0,
+            Self::Center => space / 2.0,
+            Self::Right => space
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitKind
+{
+    Degree,
+    Prime,
+    DoublePrime
+}
+
+impl UnitKind
+{
+    pub fn as_char(self) -> char
+    {
+        match self
+        {
+            Self::Degree => '°',
+            Self::Prime => '′',
+            Self::DoublePrime => '″'
+        }
+    }
+
+    // text to use if the font doesnt have the glyph, degree gets drawn as a circle instead
+    pub fn fallback(self) -> Option<&'static str>
+    {
+        match self
+        {
+            Self::Degree => None,
+            Self::Prime => Some("'"),
+            Self::DoublePrime => Some("''")
+        }
+    }
+
+    pub fn circle_radius(font_size: u32) -> u32
+    {
+        (font_size / 8).max(2)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpaceKind
+{
+    // \! from latex, pulls the next value left
+    NegativeThin
+}
+
+impl SpaceKind
+{
+    // in fractions of the font size, negative ones move the next value back
+    pub fn width(self) -> f32
+    {
+        match self
+        {
+            Self::NegativeThin => -3.0 / 18.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationErrorKind
+{
+    EmptyValue,
+    EmptyFractionPart(CursorFollow),
+    EmptyExponent,
+    EmptySubscript,
+    EmptyDegree,
+    EmptyBody,
+    EmptyCell
+}
+
+// path is the index of each value on the way to the broken one,
+// structures add the index of their part (counting from the top one) before descending
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError
+{
+    pub path: Vec<usize>,
+    pub kind: ValidationErrorKind
+}
+
+impl fmt::Display for ValidationError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let path = self.path.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(".");
+
+        match &self.kind
+        {
+            ValidationErrorKind::EmptyValue => write!(f, "empty value at {path}"),
+            ValidationErrorKind::EmptyFractionPart(part) =>
+            {
+                let part = if *part == CursorFollow::Top { "numerator" } else { "denominator" };
+
+                write!(f, "empty {part} in fraction at {path}")
+            },
+            ValidationErrorKind::EmptyExponent => write!(f, "empty exponent at {path}"),
+            ValidationErrorKind::EmptySubscript => write!(f, "empty subscript at {path}"),
+            ValidationErrorKind::EmptyDegree => write!(f, "empty root degree at {path}"),
+            ValidationErrorKind::EmptyBody => write!(f, "empty body at {path}"),
+            ValidationErrorKind::EmptyCell => write!(f, "empty matrix cell at {path}")
+        }
+    }
+}
+
+#[derive(Debug, Hash)]
+pub struct InputValues(pub Vec<InputValue>);
+
+#[derive(Debug, Hash)]
+pub enum InputValue
+{
+    Value(String),
+    Dots(DotsKind),
+    Unit(UnitKind),
+    // invisible spacing that only changes where the next value goes
+    Space(SpaceKind),
+    // result of the line this many lines above, 1 is the previous line
+    Answer{line_ref: usize},
+    // upright prose edited as a single string
+    TextRun{content: String},
+    Extension(Box<dyn CustomValue>),
+    Fraction{top: InputValues, bottom: InputValues},
+    // struck through content, the cursor goes into it through the top
+    Cancel{content: InputValues},
+    // base raised to the exponent, the exponent is the top part and the base the bottom one
+    Superscript{base: InputValues, exponent: InputValues},
+    // base with a lowered index, the base is the top part and the subscript the bottom one
+    Subscript{base: InputValues, subscript: InputValues},
+    // both scripts on the same base lined up above each other, the base is the middle part
+    SubSuperscript{base: InputValues, subscript: InputValues, superscript: InputValues},
+    // square root, the cursor goes into it through the top
+    Sqrt{inner: InputValues},
+    // root of any degree, the degree is the top part and whats under the root the bottom one
+    NthRoot{degree: InputValues, inner: InputValues},
+    // brackets around a group that grow with it, the cursor goes into it through the top
+    Paren{inner: InputValues, kind: BracketKind},
+    // brackets split by a bar like in set builder notation, the left part is the top one and the right part the bottom one
+    Divided{left: InputValues, right: InputValues, kind: BracketKind},
+    // absolute value bars that grow with whats inside, the cursor goes into it through the top
+    Abs{inner: InputValues},
+    // summation sign with its bounds above and below it, the body is the middle part
+    Sum{lower: InputValues, upper: InputValues, body: InputValues},
+    // integral sign as tall as its body with the bounds at its corners, parts are the same as a sum
+    Integral{lower: InputValues, upper: InputValues, body: InputValues},
+    // grid of cells inside brackets, every row has the same number of cells
+    Matrix{rows: Vec<Vec<InputValues>>, bracket: BracketKind},
+    // grid of cells without brackets like an array, columns has the alignment of every column
+    // and rules draws lines between all the rows and columns
+    Table{rows: Vec<Vec<InputValues>>, columns: Vec<ColumnAlign>, rules: bool}
+}
+
+// cells of a matrix or table row by row
+type Grid = Vec<Vec<InputValues>>;
+
+impl Default for InputValue
+{
+    fn default() -> Self
+    {
+        Self::Value(String::new())
+    }
+}
+
+impl InputValue
+{
+    pub fn part(&self, direction: CursorFollow) -> Option<&InputValues>
+    {
+        match (self, direction)
+        {
+            (Self::Fraction{top, ..}, CursorFollow::Top) => Some(top),
+            (Self::Fraction{bottom, ..}, CursorFollow::Bottom) => Some(bottom),
+            (Self::Cancel{content}, CursorFollow::Top) => Some(content),
+            (Self::Sqrt{inner} | Self::Paren{inner, ..} | Self::Abs{inner}, CursorFollow::Top) => Some(inner),
+            (Self::NthRoot{degree, ..}, CursorFollow::Top) => Some(degree),
+            (Self::Divided{left, ..}, CursorFollow::Top) => Some(left),
+            (Self::Divided{right, ..}, CursorFollow::Bottom) => Some(right),
+            (Self::NthRoot{inner, ..}, CursorFollow::Bottom) => Some(inner),
+            (Self::Superscript{exponent, ..}, CursorFollow::Top) => Some(exponent),
+            (Self::Superscript{base, ..}, CursorFollow::Bottom) => Some(base),
+            (Self::Subscript{base, ..}, CursorFollow::Top) => Some(base),
+            (Self::Subscript{subscript, ..}, CursorFollow::Bottom) => Some(subscript),
+            (Self::SubSuperscript{superscript, ..}, CursorFollow::Top) => Some(superscript),
+            (Self::SubSuperscript{base, ..}, CursorFollow::Middle) => Some(base),
+            (Self::SubSuperscript{subscript, ..}, CursorFollow::Bottom) => Some(subscript),
+            (Self::Sum{upper, ..} | Self::Integral{upper, ..}, CursorFollow::Top) => Some(upper),
+            (Self::Sum{body, ..} | Self::Integral{body, ..}, CursorFollow::Middle) => Some(body),
+            (Self::Sum{lower, ..} | Self::Integral{lower, ..}, CursorFollow::Bottom) => Some(lower),
+            (Self::Matrix{rows, ..} | Self::Table{rows, ..}, CursorFollow::Cell{row, column}) => rows.get(row)?.get(column),
+            // listed out so a new structure cant be skipped over by traversal
+            (
+                Self::Value(_)
+                | Self::Dots(_)
+                | Self::Unit(_)
+                | Self::Space(_)
+                | Self::Answer{..}
+                | Self::TextRun{..}
+                | Self::Extension(_),
+                _
+            ) => None,
+            // a part the structure doesnt have
+            (
+                Self::Fraction{..}
+                | Self::Cancel{..}
+                | Self::Superscript{..}
+                | Self::Subscript{..}
+                | Self::SubSuperscript{..}
+                | Self::Sqrt{..}
+                | Self::NthRoot{..}
+                | Self::Paren{..}
+                | Self::Divided{..}
+                | Self::Abs{..}
+                | Self::Sum{..}
+                | Self::Integral{..}
+                | Self::Matrix{..}
+                | Self::Table{..},
+                _
+            ) => None
+        }
+    }
+
+    pub fn part_mut(&mut self, direction: CursorFollow) -> Option<&mut InputValues>
+    {
+        match (self, direction)
+        {
+            (Self::Fraction{top, ..}, CursorFollow::Top) => Some(top),
+            (Self::Fraction{bottom, ..}, CursorFollow::Bottom) => Some(bottom),
+            (Self::Cancel{content}, CursorFollow::Top) => Some(content),
+            (Self::Sqrt{inner} | Self::Paren{inner, ..} | Self::Abs{inner}, CursorFollow::Top) => Some(inner),
+            (Self::NthRoot{degree, ..}, CursorFollow::Top) => Some(degree),
+            (Self::Divided{left, ..}, CursorFollow::Top) => Some(left),
+            (Self::Divided{right, ..}, CursorFollow::Bottom) => Some(right),
+            (Self::NthRoot{inner, ..}, CursorFollow::Bottom) => Some(inner),
+            (Self::Superscript{exponent, ..}, CursorFollow::Top) => Some(exponent),
+            (Self::Superscript{base, ..}, CursorFollow::Bottom) => Some(base),
+            (Self::Subscript{base, ..}, CursorFollow::Top) => Some(base),
+            (Self::Subscript{subscript, ..}, CursorFollow::Bottom) => Some(subscript),
+            (Self::SubSuperscript{superscript, ..}, CursorFollow::Top) => Some(superscript),
+            (Self::SubSuperscript{base, ..}, CursorFollow::Middle) => Some(base),
+            (Self::SubSuperscript{subscript, ..}, CursorFollow::Bottom) => Some(subscript),
+            (Self::Sum{upper, ..} | Self::Integral{upper, ..}, CursorFollow::Top) => Some(upper),
+            (Self::Sum{body, ..} | Self::Integral{body, ..}, CursorFollow::Middle) => Some(body),
+            (Self::Sum{lower, ..} | Self::Integral{lower, ..}, CursorFollow::Bottom) => Some(lower),
+            (Self::Matrix{rows, ..} | Self::Table{rows, ..}, CursorFollow::Cell{row, column}) => rows.get_mut(row)?.get_mut(column),
+            // leaves dont have any parts
+            (
+                Self::Value(_)
+                | Self::Dots(_)
+                | Self::Unit(_)
+                | Self::Space(_)
+                | Self::Answer{..}
+                | Self::TextRun{..}
+                | Self::Extension(_),
+                _
+            ) => None,
+            // a part the structure doesnt have
+            (
+                Self::Fraction{..}
+                | Self::Cancel{..}
+                | Self::Superscript{..}
+                | Self::Subscript{..}
+                | Self::SubSuperscript{..}
+                | Self::Sqrt{..}
+                | Self::NthRoot{..}
+                | Self::Paren{..}
+                | Self::Divided{..}
+                | Self::Abs{..}
+                | Self::Sum{..}
+                | Self::Integral{..}
+                | Self::Matrix{..}
+                | Self::Table{..},
+                _
+            ) => None
+        }
+    }
+
+    // every part this has in reading order, cells of a matrix go row by row
+    pub fn parts(&self) -> Vec<CursorFollow>
+    {
+        if let Self::Matrix{rows, ..} | Self::Table{rows, ..} = self
+        {
+            return rows.iter().enumerate().flat_map(|(row, cells)|
+            {
+                (0..cells.len()).map(move |column| CursorFollow::Cell{row, column})
+            }).collect();
+        }
+
+        CursorFollow::ALL.into_iter().filter(|part| self.part(*part).is_some()).collect()
+    }
+
+    // the cells of a matrix or a table along with the column alignments of a table
+    fn grid_mut(&mut self) -> Option<(&mut Grid, Option<&mut Vec<ColumnAlign>>)>
+    {
+        match self
+        {
+            Self::Matrix{rows, ..} => Some((rows, None)),
+            Self::Table{rows, columns, ..} => Some((rows, Some(columns))),
+            _ => None
+        }
+    }
+
+    // divides a numeric fraction by the gcd of its parts, false if its not a numeric fraction
+    pub fn simplify(&mut self) -> bool
+    {
+        let Self::Fraction{top, bottom} = self
+        else
+        {
+            return false;
+        };
+
+        let (Some(a), Some(b)) = (top.as_integer(), bottom.as_integer())
+        else
+        {
+            return false;
+        };
+
+        if b == 0
+        {
+            return false;
+        }
+
+        let gcd = {
+            let (mut a, mut b) = (a, b);
+            while b != 0
+            {
+                (a, b) = (b, a % b);
+            }
+
+            a
+        };
+
+        *top = InputValues::from_integer(a / gcd);
+        *bottom = InputValues::from_integer(b / gcd);
+
+        true
+    }
+
+    // turns a fraction into its reciprocal, false if its not a fraction
+    pub fn swap_parts(&mut self) -> bool
+    {
+        let Self::Fraction{top, bottom} = self
+        else
+        {
+            return false;
+        };
+
+        mem::swap(top, bottom);
+
+        true
+    }
+
+    pub fn is_structure(&self) -> bool
+    {
+        matches!(
+            self,
+            Self::Fraction{..}
+            | Self::Cancel{..}
+            | Self::Superscript{..}
+            | Self::Subscript{..}
+            | Self::SubSuperscript{..}
+            | Self::Sqrt{..}
+            | Self::NthRoot{..}
+            | Self::Paren{..}
+            | Self::Divided{..}
+            | Self::Abs{..}
+            | Self::Sum{..}
+            | Self::Integral{..}
+            | Self::Matrix{..}
+            | Self::Table{..}
+        )
+    }
+
+    // the part the cursor goes into when it moves onto this from the left or from the right
+    pub fn entry_part(&self, from_left: bool) -> CursorFollow
+    {
+        match self
+        {
+            Self::Superscript{..} if from_left => CursorFollow::Bottom,
+            Self::Subscript{..} | Self::NthRoot{..} | Self::Divided{..} if !from_left => CursorFollow::Bottom,
+            Self::SubSuperscript{..} if from_left => CursorFollow::Middle,
+            Self::Sum{..} | Self::Integral{..} if from_left => CursorFollow::Bottom,
+            Self::Sum{..} | Self::Integral{..} => CursorFollow::Middle,
+            Self::Matrix{..} | Self::Table{..} if from_left => CursorFollow::Cell{row: 0, column: 0},
+            Self::Matrix{rows, ..} | Self::Table{rows, ..} =>
+            {
+                let row = rows.len() - 1;
+
+                CursorFollow::Cell{row, column: rows[row].len() - 1}
+            },
+            _ => CursorFollow::Top
+        }
+    }
+
+    // the part that comes next when moving sideways out of this one, none if it leaves the structure
+    fn next_part(&self, direction: CursorFollow, forward: bool) -> Option<CursorFollow>
+    {
+        if let Self::Matrix{..} | Self::Table{..} = self
+        {
+            let parts = self.parts();
+            let position = parts.iter().position(|part| *part == direction)?;
+            let position = if forward { position + 1 } else { position.checked_sub(1)? };
+
+            return parts.get(position).copied();
+        }
+
+        match (self, direction, forward)
+        {
+            (Self::Superscript{..}, CursorFollow::Bottom, true) => Some(CursorFollow::Top),
+            (Self::Superscript{..}, CursorFollow::Top, false) => Some(CursorFollow::Bottom),
+            (Self::Subscript{..}, CursorFollow::Top, true) => Some(CursorFollow::Bottom),
+            (Self::Subscript{..}, CursorFollow::Bottom, false) => Some(CursorFollow::Top),
+            // the degree is written before whats under the root
+            (Self::NthRoot{..}, CursorFollow::Top, true) => Some(CursorFollow::Bottom),
+            (Self::NthRoot{..}, CursorFollow::Bottom, false) => Some(CursorFollow::Top),
+            (Self::Divided{..}, CursorFollow::Top, true) => Some(CursorFollow::Bottom),
+            (Self::Divided{..}, CursorFollow::Bottom, false) => Some(CursorFollow::Top),
+            // sideways the subscript comes before the superscript like in x_i^2
+            (Self::SubSuperscript{..}, CursorFollow::Middle, true) => Some(CursorFollow::Bottom),
+            (Self::SubSuperscript{..}, CursorFollow::Bottom, true) => Some(CursorFollow::Top),
+            (Self::SubSuperscript{..}, CursorFollow::Top, false) => Some(CursorFollow::Bottom),
+            (Self::SubSuperscript{..}, CursorFollow::Bottom, false) => Some(CursorFollow::Middle),
+            // the bounds come before the body like in \sum_{i=1}^{n} i
+            (Self::Sum{..} | Self::Integral{..}, CursorFollow::Bottom, true) => Some(CursorFollow::Top),
+            (Self::Sum{..} | Self::Integral{..}, CursorFollow::Top, true) => Some(CursorFollow::Middle),
+            (Self::Sum{..} | Self::Integral{..}, CursorFollow::Middle, false) => Some(CursorFollow::Top),
+            (Self::Sum{..} | Self::Integral{..}, CursorFollow::Top, false) => Some(CursorFollow::Bottom),
+            _ => None
+        }
+    }
+
+    // the part above or below this one, none at the edge of the structure
+    fn vertical_part(&self, direction: CursorFollow, up: bool) -> Option<CursorFollow>
+    {
+        // both sides of the bar are on the same level
+        if let Self::Divided{..} = self
+        {
+            return None;
+        }
+
+        // cells go to the one in the same column of the row above or below
+        if let CursorFollow::Cell{row, column} = direction
+        {
+            let row = if up { row.checked_sub(1)? } else { row + 1 };
+            let next = CursorFollow::Cell{row, column};
+
+            return self.part(next).is_some().then_some(next);
+        }
+
+        let parts = self.parts();
+
+        let position = parts.iter().position(|part| *part == direction)?;
+        let position = if up { position.checked_sub(1)? } else { position + 1 };
+
+        parts.get(position).copied()
+    }
+
+    // how many structures are nested inside each other here, zero for plain values
+    pub fn depth(&self) -> usize
+    {
+        self.parts().into_iter()
+            .filter_map(|direction| self.part(direction))
+            .map(|part| part.depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_space(&self) -> bool
+    {
+        matches!(self, Self::Space(_))
+    }
+
+    // whether this followed by after is a multiplication without a sign like 2x or (a)(b)
+    pub fn is_implicit_product(&self, after: &Self) -> bool
+    {
+        let factor_end = match self
+        {
+            Self::Value(x) => x.ends_with(|c: char| c.is_ascii_digit() || c == ')'),
+            Self::Fraction{..}
+            | Self::Superscript{..}
+            | Self::Subscript{..}
+            | Self::SubSuperscript{..}
+            | Self::Sqrt{..}
+            | Self::NthRoot{..}
+            | Self::Paren{..}
+            | Self::Divided{..}
+            | Self::Abs{..}
+            | Self::Matrix{..}
+            | Self::Table{..}
+            | Self::Answer{..} => true,
+            _ => false
+        };
+
+        // letters after letters could be a name like sin so only numbers and groups count before
+        let factor_start = match after
+        {
+            Self::Value(x) => x.starts_with(|c: char| c.is_alphabetic() || c == '('),
+            Self::Fraction{..}
+            | Self::Superscript{..}
+            | Self::Subscript{..}
+            | Self::SubSuperscript{..}
+            | Self::Sqrt{..}
+            | Self::NthRoot{..}
+            | Self::Paren{..}
+            | Self::Divided{..}
+            | Self::Abs{..}
+            | Self::Sum{..}
+            | Self::Integral{..}
+            | Self::Matrix{..}
+            | Self::Table{..}
+            | Self::Answer{..} => true,
+            _ => false
+        };
+
+        factor_end && factor_start
+    }
+
+    pub fn is_digits(&self) -> bool
+    {
+        matches!(self, Self::Value(x) if !x.is_empty() && x.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    #[allow(dead_code)]
+    pub fn is_value(&self) -> bool
+    {
+        if let Self::Value(_) = self
+        {
+            true
+        } else
+        {
+            false
+        }
+    }
+
+    pub fn render(
+        &self,
+        layout: &LayoutConfig,
+        style: MathStyle,
+        cursor: Option<&(CursorFollow, Box<ValueCursor>)>,
+        x: f32,
+        y: f32,
+        f: &impl Fn(RenderValue) -> RenderResult
+    ) -> RenderResult<'_>
+    {
+        match self
+        {
+            Self::Value(text) => f(RenderValue::Text{x, y, text: text.into()}),
+            Self::Dots(kind) => f(RenderValue::Dots{x, y, kind: *kind}),
+            Self::Unit(kind) => f(RenderValue::Unit{x, y, kind: *kind}),
+            // the row its in does the moving
+            Self::Space(_) => RenderResult::empty(RenderRect{x, y, width: 0.0, height: 0.0}),
+            Self::Answer{line_ref} => f(RenderValue::Text{x, y, text: export::answer_name(*line_ref).into()}),
+            Self::TextRun{content} =>
+            {
+                if content.is_empty()
+                {
+                    RenderResult::empty(RenderRect{x, y, width: 0.0, height: layout.font_size as f32})
+                } else
+                {
+                    f(RenderValue::Text{x, y, text: visual_text(content)})
+                }
+            },
+            Self::Extension(value) =>
+            {
+                let start = RenderResult::empty(RenderRect{x, y, width: 0.0, height: 0.0});
+
+                value.render(x, y).into_iter().fold(start, |acc, render| acc.combine(f(render)))
+            },
+            Self::Fraction{top, bottom} =>
+            {
+                let (top_empty, bottom_empty) = (top.0.is_empty(), bottom.0.is_empty());
+
+                let top_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Top).then_some(&*x.1)
+                });
+
+                let mut top = top.render(layout, style.numerator(), top_cursor, x, y, f);
+
+                let bottom_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Bottom).then_some(&*x.1)
+                });
+
+                let denominator = style.denominator();
+                let scale = style.relative_scale(denominator, layout);
+                let mut bottom = bottom.render_scaled(layout, denominator, scale, bottom_cursor, x, y, f);
+
+                // empty parts would collapse the bar to nothing
+                if top_empty
+                {
+                    top.placeholder(layout);
+                }
+
+                if bottom_empty
+                {
+                    bottom.placeholder(layout);
+                }
+
+                let axis = y + layout.font_size as f32 / 2.0;
+                let gap = layout.fraction_gap() + layout.line_thickness as f32 / 2.0;
+
+                let (rect, line) = match layout.fraction_style
+                {
+                    FractionStyle::Stacked =>
+                    {
+                        let (top_shift_x, bottom_shift_x) = if top.rect.width < bottom.rect.width
+                        {
+                            ((bottom.rect.width - top.rect.width) / 2.0, 0.0)
+                        } else
+                        {
+                            (0.0, (top.rect.width - bottom.rect.width) / 2.0)
+                        };
+
+                        // the bar sits on the math axis with the parts stacked around it
+                        let top_end = top.rect.y + top.rect.height;
+                        top.shift(top_shift_x, axis - gap - top_end);
+                        bottom.shift(bottom_shift_x, axis + gap - bottom.rect.y);
+
+                        let width = top.rect.width.max(bottom.rect.width);
+
+                        (bottom.rect.combine(top.rect), f(RenderValue::horizontal_line(x, axis, width, layout)))
+                    },
+                    FractionStyle::Beveled =>
+                    {
+                        // numerator raised and denominator lowered by a quarter of the font around a slash
+                        let raise = layout.font_size as f32 / 4.0;
+                        let slant = layout.font_size as f32 / 2.0;
+                        let half_height = layout.font_size as f32 / 2.0;
+
+                        top.shift(0.0, axis - raise - top.axis);
+
+                        let slash_x = top.rect.x + top.rect.width + gap;
+                        let line = f(RenderValue::Line{
+                            x1: slash_x,
+                            y1: axis + half_height,
+                            x2: slash_x + slant,
+                            y2: axis - half_height,
+                            thickness: layout.line_thickness as f32
+                        });
+
+                        bottom.shift(slash_x + slant + gap - bottom.rect.x, axis + raise - bottom.axis);
+
+                        (top.rect.combine(line.rect).combine(bottom.rect), line)
+                    },
+                    FractionStyle::Inline =>
+                    {
+                        top.shift(0.0, axis - top.axis);
+
+                        let mut slash = f(RenderValue::Text{x: 0.0, y: 0.0, text: "/".into()});
+                        slash.shift(top.rect.x + top.rect.width, axis - slash.axis);
+
+                        bottom.shift(slash.rect.x + slash.rect.width - bottom.rect.x, axis - bottom.axis);
+
+                        (top.rect.combine(slash.rect).combine(bottom.rect), slash)
+                    }
+                };
+
+                let mut render = top.render;
+                render.extend(bottom.render);
+                render.extend(line.render);
+
+                RenderResult{rect, axis, render}
+            },
+            Self::Cancel{content} =>
+            {
+                let empty = content.0.is_empty();
+
+                let content_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Top).then_some(&*x.1)
+                });
+
+                let mut content = content.render(layout, style, content_cursor, x, y, f);
+
+                if empty
+                {
+                    content.placeholder(layout);
+                }
+
+                // strike from the bottom left to the top right corner
+                let rect = content.rect;
+                let line = f(RenderValue::Line{
+                    x1: rect.x,
+                    y1: rect.y + rect.height,
+                    x2: rect.x + rect.width,
+                    y2: rect.y,
+                    thickness: layout.line_thickness as f32
+                });
+
+                content.combine(line)
+            },
+            Self::Superscript{base, exponent} =>
+            {
+                let base_empty = base.0.is_empty();
+
+                let base_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Bottom).then_some(&*x.1)
+                });
+
+                let mut base = base.render(layout, style, base_cursor, x, y, f);
+
+                if base_empty
+                {
+                    base.placeholder(layout);
+                }
+
+                let exponent_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Top).then_some(&*x.1)
+                });
+
+                let mut exponent = exponent.render_script(layout, style, exponent_cursor, x, y, f);
+
+                // right after the base with its axis raised above the bases axis
+                let base_end = base.rect.x + base.rect.width;
+                let raise = layout.script_shift(layout.superscript_shift, base.rect.height);
+                exponent.shift(base_end - exponent.rect.x, base.axis - exponent.axis - raise);
+
+                let rect = base.rect.combine(exponent.rect);
+
+                let mut render = base.render;
+                render.extend(exponent.render);
+
+                RenderResult{rect, axis: base.axis, render}
+            },
+            Self::Subscript{base, subscript} =>
+            {
+                let base_empty = base.0.is_empty();
+
+                let base_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Top).then_some(&*x.1)
+                });
+
+                let mut base = base.render(layout, style, base_cursor, x, y, f);
+
+                if base_empty
+                {
+                    base.placeholder(layout);
+                }
+
+                let subscript_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Bottom).then_some(&*x.1)
+                });
+
+                let mut subscript = subscript.render_script(layout, style, subscript_cursor, x, y, f);
+
+                // right after the base with its axis lowered below the bases axis
+                let base_end = base.rect.x + base.rect.width;
+                let lower = layout.script_shift(layout.subscript_shift, base.rect.height);
+                subscript.shift(base_end - subscript.rect.x, base.axis - subscript.axis + lower);
+
+                let rect = base.rect.combine(subscript.rect);
+
+                let mut render = base.render;
+                render.extend(subscript.render);
+
+                RenderResult{rect, axis: base.axis, render}
+            },
+            Self::SubSuperscript{base, subscript, superscript} =>
+            {
+                let base_empty = base.0.is_empty();
+
+                let part_cursor = |which: CursorFollow|
+                {
+                    cursor.and_then(|x@(follow, _)| (*follow == which).then_some(&*x.1))
+                };
+
+                let mut base = base.render(layout, style, part_cursor(CursorFollow::Middle), x, y, f);
+
+                if base_empty
+                {
+                    base.placeholder(layout);
+                }
+
+                let mut subscript = subscript.render_script(layout, style, part_cursor(CursorFollow::Bottom), x, y, f);
+                let mut superscript = superscript.render_script(layout, style, part_cursor(CursorFollow::Top), x, y, f);
+
+                // both start at the end of the base, one raised and one lowered like on their own
+                let base_end = base.rect.x + base.rect.width;
+                let raise = layout.script_shift(layout.superscript_shift, base.rect.height);
+                let lower = layout.script_shift(layout.subscript_shift, base.rect.height);
+
+                subscript.shift(base_end - subscript.rect.x, base.axis - subscript.axis + lower);
+                superscript.shift(base_end - superscript.rect.x, base.axis - superscript.axis - raise);
+
+                let rect = base.rect.combine(subscript.rect).combine(superscript.rect);
+
+                let mut render = base.render;
+                render.extend(subscript.render);
+                render.extend(superscript.render);
+
+                RenderResult{rect, axis: base.axis, render}
+            },
+            Self::Sqrt{inner} =>
+            {
+                let empty = inner.0.is_empty();
+
+                let inner_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Top).then_some(&*x.1)
+                });
+
+                let mut inner = inner.render(layout, style, inner_cursor, x, y, f);
+
+                if empty
+                {
+                    inner.placeholder(layout);
+                }
+
+                // the sign grows with the content and its bar stays a gap above it
+                let gap = layout.fraction_gap();
+                let height = inner.rect.height + gap;
+                let sign_width = height / 2.0;
+
+                inner.shift(x + sign_width - inner.rect.x, 0.0);
+
+                let radical = f(RenderValue::Radical{
+                    x,
+                    y: inner.rect.y - gap,
+                    width: sign_width + inner.rect.width,
+                    height,
+                    thickness: layout.line_thickness as f32
+                });
+
+                inner.combine(radical)
+            },
+            Self::NthRoot{degree, inner} =>
+            {
+                let (inner_empty, degree_empty) = (inner.0.is_empty(), degree.0.is_empty());
+
+                let part_cursor = |which: CursorFollow|
+                {
+                    cursor.and_then(|x@(follow, _)| (*follow == which).then_some(&*x.1))
+                };
+
+                let mut inner = inner.render(layout, style, part_cursor(CursorFollow::Bottom), x, y, f);
+
+                if inner_empty
+                {
+                    inner.placeholder(layout);
+                }
+
+                let scale = layout.root_degree_scale;
+                let mut degree = degree.render_scaled(layout, style.script().script(), scale, part_cursor(CursorFollow::Top), x, y, f);
+
+                if degree_empty
+                {
+                    degree.scaled_placeholder(layout, scale);
+                }
+
+                let gap = layout.fraction_gap();
+                let height = inner.rect.height + gap;
+                let sign_width = height / 2.0;
+                let top = inner.rect.y - gap;
+
+                // the degree sits above the tick of the sign, a wide one pushes the sign over
+                let notch = sign_width / 2.0;
+                let radical_x = x + (degree.rect.width - notch).max(0.0);
+
+                inner.shift(radical_x + sign_width - inner.rect.x, 0.0);
+
+                let degree_end = (degree.rect.x + degree.rect.width, degree.rect.y + degree.rect.height);
+                degree.shift(radical_x + notch - degree_end.0, top + height / 2.0 - gap - degree_end.1);
+
+                let radical = f(RenderValue::Radical{
+                    x: radical_x,
+                    y: top,
+                    width: sign_width + inner.rect.width,
+                    height,
+                    thickness: layout.line_thickness as f32
+                });
+
+                inner.combine(radical).combine(degree)
+            },
+            Self::Paren{inner, kind} =>
+            {
+                let empty = inner.0.is_empty();
+
+                let inner_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Top).then_some(&*x.1)
+                });
+
+                let mut inner = inner.render(layout, style, inner_cursor, x, y, f);
+
+                if empty
+                {
+                    inner.placeholder(layout);
+                }
+
+                inner.shift(x + layout.bracket_width() - inner.rect.x, 0.0);
+
+                let (top, height) = (inner.rect.y, inner.rect.height);
+                let bracket = |x, side| f(RenderValue::Bracket{x, y: top, height, side, kind: *kind});
+
+                let left = bracket(x, BracketSide::Left);
+                let right = bracket(inner.rect.x + inner.rect.width, BracketSide::Right);
+
+                inner.combine(left).combine(right)
+            },
+            Self::Divided{left, right, kind} =>
+            {
+                let (left_empty, right_empty) = (left.0.is_empty(), right.0.is_empty());
+
+                let part_cursor = |which: CursorFollow|
+                {
+                    cursor.and_then(|x@(follow, _)| (*follow == which).then_some(&*x.1))
+                };
+
+                let mut left = left.render(layout, style, part_cursor(CursorFollow::Top), x, y, f);
+                let mut right = right.render(layout, style, part_cursor(CursorFollow::Bottom), x, y, f);
+
+                if left_empty
+                {
+                    left.placeholder(layout);
+                }
+
+                if right_empty
+                {
+                    right.placeholder(layout);
+                }
+
+                // the bar sits in a gap as wide as a bracket between the parts
+                let width = layout.bracket_width();
+                left.shift(x + width - left.rect.x, 0.0);
+
+                let bar_x = left.rect.x + left.rect.width + width / 2.0;
+                right.shift(bar_x + width / 2.0 - right.rect.x, 0.0);
+
+                let inner = left.combine(right);
+
+                let (top, height) = (inner.rect.y, inner.rect.height);
+                let bracket = |x, side| f(RenderValue::Bracket{x, y: top, height, side, kind: *kind});
+
+                let bar = f(RenderValue::Line{x1: bar_x, y1: top, x2: bar_x, y2: top + height, thickness: layout.line_thickness as f32});
+                let open = bracket(x, BracketSide::Left);
+                let close = bracket(inner.rect.x + inner.rect.width, BracketSide::Right);
+
+                inner.combine(bar).combine(open).combine(close)
+            },
+            Self::Abs{inner} =>
+            {
+                let empty = inner.0.is_empty();
+
+                let inner_cursor = cursor.and_then(|x@(follow, _)|
+                {
+                    (*follow == CursorFollow::Top).then_some(&*x.1)
+                });
+
+                let mut inner = inner.render(layout, style, inner_cursor, x, y, f);
+
+                if empty
+                {
+                    inner.placeholder(layout);
+                }
+
+                let width = layout.bracket_width();
+                inner.shift(x + width - inner.rect.x, 0.0);
+
+                let (top, height) = (inner.rect.y, inner.rect.height);
+                let bar = |x: f32|
+                {
+                    let x = x + width / 2.0;
+                    f(RenderValue::Line{x1: x, y1: top, x2: x, y2: top + height, thickness: layout.line_thickness as f32})
+                };
+
+                let left = bar(x);
+                let right = bar(inner.rect.x + inner.rect.width);
+
+                inner.combine(left).combine(right)
+            },
+            Self::Sum{lower, upper, body} =>
+            {
+                let body_empty = body.0.is_empty();
+
+                let part_cursor = |which: CursorFollow|
+                {
+                    cursor.and_then(|x@(follow, _)| (*follow == which).then_some(&*x.1))
+                };
+
+                let mut body = body.render(layout, style, part_cursor(CursorFollow::Middle), x, y, f);
+
+                if body_empty
+                {
+                    body.placeholder(layout);
+                }
+
+                // grows with the body like the integral sign does
+                let scale = (body.rect.height / layout.font_size as f32).max(layout.sum_scale);
+
+                let sign = RenderValue::Text{x, y, text: "Σ".into()};
+                let mut sign = f(RenderValue::Scaled{scale, value: Box::new(sign)});
+
+                let mut upper = upper.render_script(layout, style, part_cursor(CursorFollow::Top), x, y, f);
+                let mut lower = lower.render_script(layout, style, part_cursor(CursorFollow::Bottom), x, y, f);
+
+                // the sign and both bounds are centered in a column as wide as the widest of them
+                let column = sign.rect.width.max(upper.rect.width).max(lower.rect.width);
+                let center = |rect: RenderRect| x + (column - rect.width) / 2.0 - rect.x;
+
+                sign.shift(center(sign.rect), y + layout.font_size as f32 / 2.0 - sign.axis);
+
+                let upper_end = upper.rect.y + upper.rect.height;
+                upper.shift(center(upper.rect), sign.rect.y - upper_end);
+                lower.shift(center(lower.rect), sign.rect.y + sign.rect.height - lower.rect.y);
+
+                body.shift(x + column + layout.fraction_gap() - body.rect.x, sign.axis - body.axis);
+
+                sign.combine(upper).combine(lower).combine(body)
+            },
+            Self::Integral{lower, upper, body} =>
+            {
+                let body_empty = body.0.is_empty();
+
+                let part_cursor = |which: CursorFollow|
+                {
+                    cursor.and_then(|x@(follow, _)| (*follow == which).then_some(&*x.1))
+                };
+
+                let mut body = body.render(layout, style, part_cursor(CursorFollow::Middle), x, y, f);
+
+                if body_empty
+                {
+                    body.placeholder(layout);
+                }
+
+                // the sign is as tall as the body but never smaller than integral_scale,
+                // so an integral in the body of another one doesnt make the outer sign grow
+                let scale = (body.rect.height / layout.font_size as f32).max(layout.integral_scale);
+
+                let sign = RenderValue::Text{x, y, text: "∫".into()};
+                let mut sign = f(RenderValue::Scaled{scale, value: Box::new(sign)});
+
+                let body_center = body.rect.y + body.rect.height / 2.0;
+                sign.shift(x - sign.rect.x, body_center - (sign.rect.y + sign.rect.height / 2.0));
+
+                let mut upper = upper.render_script(layout, style, part_cursor(CursorFollow::Top), x, y, f);
+                let mut lower = lower.render_script(layout, style, part_cursor(CursorFollow::Bottom), x, y, f);
+
+                // the bounds sit in the top right and bottom right corners of the sign without going past it
+                let sign_end = sign.rect.x + sign.rect.width;
+                let sign_bottom = sign.rect.y + sign.rect.height;
+
+                upper.shift(sign_end - upper.rect.x, sign.rect.y - upper.rect.y);
+                lower.shift(sign_end - lower.rect.x, sign_bottom - (lower.rect.y + lower.rect.height));
+
+                let bounds_end = (upper.rect.x + upper.rect.width).max(lower.rect.x + lower.rect.width);
+                body.shift(bounds_end + layout.fraction_gap() - body.rect.x, 0.0);
+
+                body.combine(sign).combine(upper).combine(lower)
+            },
+            Self::Matrix{rows, ..} | Self::Table{rows, ..} =>
+            {
+                let mut cells: Vec<Vec<RenderResult>> = rows.iter().enumerate().map(|(row, cells)|
+                {
+                    cells.iter().enumerate().map(|(column, cell)|
+                    {
+                        let cell_cursor = cursor.and_then(|x@(follow, _)|
+                        {
+                            (*follow == CursorFollow::Cell{row, column}).then_some(&*x.1)
+                        });
+
+                        let mut render = cell.render(layout, style, cell_cursor, x, y, f);
+
+                        if cell.0.is_empty()
+                        {
+                            render.placeholder(layout);
+                        }
+
+                        render
+                    }).collect()
+                }).collect();
+
+                let columns = cells[0].len();
+                let widths: Vec<f32> = (0..columns).map(|column|
+                {
+                    cells.iter().map(|cells| cells[column].rect.width).fold(0.0, f32::max)
+                }).collect();
+
+                // every row is as tall as its tallest cell above and below the axis so the cells line up on it
+                let extents: Vec<(f32, f32)> = cells.iter().map(|cells|
+                {
+                    cells.iter().fold((0.0, 0.0), |(above, below): (f32, f32), cell|
+                    {
+                        let cell_above = cell.axis - cell.rect.y;
+                        let cell_below = cell.rect.y + cell.rect.height - cell.axis;
+
+                        (above.max(cell_above), below.max(cell_below))
+                    })
+                }).collect();
+
+                let gap = layout.fraction_gap();
+                let height = extents.iter().map(|(above, below)| above + below).sum::<f32>()
+                    + gap * (extents.len() - 1) as f32;
+
+                let axis = y + layout.font_size as f32 / 2.0;
+                let top = axis - height / 2.0;
+
+                let (align, start) = match self
+                {
+                    Self::Table{columns, ..} => (columns.clone(), x),
+                    _ => (vec![ColumnAlign::Center; columns], x + layout.bracket_width())
+                };
+
+                let mut row_top = top;
+                let mut render = Vec::new();
+                let mut end = start;
+
+                cells.iter_mut().zip(&extents).for_each(|(cells, &(above, below))|
+                {
+                    let mut column_x = start;
+
+                    cells.iter_mut().zip(&widths).zip(&align).for_each(|((cell, width), align)|
+                    {
+                        let offset = align.offset(width - cell.rect.width);
+                        cell.shift(column_x + offset - cell.rect.x, row_top + above - cell.axis);
+
+                        render.append(&mut cell.render);
+
+                        column_x += width + layout.matrix_gap();
+                    });
+
+                    end = column_x - layout.matrix_gap();
+                    row_top += above + below + gap;
+                });
+
+                let rect = RenderRect{x, y: top, width: end - x, height};
+                let grid = RenderResult{rect, axis, render};
+
+                match self
+                {
+                    Self::Matrix{bracket, ..} =>
+                    {
+                        let bracket = |x, side| f(RenderValue::Bracket{x, y: top, height, side, kind: *bracket});
+
+                        let left = bracket(x, BracketSide::Left);
+                        let right = bracket(end, BracketSide::Right);
+
+                        grid.combine(left).combine(right)
+                    },
+                    Self::Table{rules: true, ..} =>
+                    {
+                        let thickness = layout.line_thickness as f32;
+
+                        // the lines go through the middle of the gaps between the cells
+                        let mut column_x = start;
+                        let columns = widths[..widths.len() - 1].iter().fold(grid, |grid, width|
+                        {
+                            column_x += width + layout.matrix_gap();
+
+                            let line_x = column_x - layout.matrix_gap() / 2.0;
+                            grid.combine(f(RenderValue::Line{x1: line_x, y1: top, x2: line_x, y2: top + height, thickness}))
+                        });
+
+                        let mut row_top = top;
+                        extents[..extents.len() - 1].iter().fold(columns, |grid, (above, below)|
+                        {
+                            row_top += above + below + gap;
+
+                            let line_y = row_top - gap / 2.0;
+                            grid.combine(f(RenderValue::Line{x1: start, y1: line_y, x2: end, y2: line_y, thickness}))
+                        })
+                    },
+                    _ => grid
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderRect
+{
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32
+}
+
+// pixels only get snapped to when drawing, layout stays in floats
+#[cfg(feature = "sdl")]
+impl From<RenderRect> for Rect
+{
+    fn from(v: RenderRect) -> Self
+    {
+        let (x, y) = (v.x.round() as i32, v.y.round() as i32);
+        let (end_x, end_y) = v.end();
+
+        Self::new(x, y, (end_x.round() as i32 - x) as u32, (end_y.round() as i32 - y) as u32)
+    }
+}
+
+#[cfg(feature = "sdl")]
+impl From<Rect> for RenderRect
+{
+    fn from(v: Rect) -> Self
+    {
+        Self{x: v.x as f32, y: v.y as f32, width: v.width() as f32, height: v.height() as f32}
+    }
+}
+
+impl RenderRect
+{
+    pub fn empty() -> Self
+    {
+        Self{x: 0.0, y: 0.0, width: 0.0, height: 0.0}
+    }
+
+    fn end(self) -> (f32, f32)
+    {
+        (self.x + self.width, self.y + self.height)
+    }
+
+    pub fn combine(self, other: Self) -> Self
+    {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+
+        let this_end = self.end();
+        let other_end = other.end();
+        let end_x = this_end.0.max(other_end.0);
+        let end_y = this_end.1.max(other_end.1);
+
+        Self{x, y, width: end_x - x, height: end_y - y}
+    }
+}
+
+pub struct RenderResult<'a>
+{
+    rect: RenderRect,
+    // height of the math axis (the line fractions sit on) in the same space as rect
+    axis: f32,
+    render: Vec<RenderValue<'a>>
+}
+
+impl<'a> RenderResult<'a>
+{
+    pub fn new(rect: RenderRect, render: RenderValue<'a>) -> Self
+    {
+        Self{rect, axis: rect.y + rect.height / 2.0, render: vec![render]}
+    }
+
+    pub fn empty(rect: RenderRect) -> Self
+    {
+        Self{rect, axis: rect.y + rect.height / 2.0, render: Vec::new()}
+    }
+
+    fn is_cursor(&self) -> bool
+    {
+        if self.render.len() == 1
+        {
+            match &self.render[0]
+            {
+                RenderValue::Cursor{..} => return true,
+                _ => ()
+            }
+        }
+
+        false
+    }
+
+    pub fn combine(mut self, other: RenderResult<'a>) -> Self
+    {
+        if !other.is_cursor()
+        {
+            self.rect = self.rect.combine(other.rect);
+        }
+
+        self.render.extend(other.render);
+
+        self
+    }
+
+    // gives an empty region a visible size with the cursor in the middle of it
+    fn placeholder(&mut self, layout: &LayoutConfig)
+    {
+        self.scaled_placeholder(layout, 1.0);
+    }
+
+    fn scaled_placeholder(&mut self, layout: &LayoutConfig, scale: f32)
+    {
+        let width = layout.placeholder_width() * scale;
+        let height = layout.font_size as f32 * scale;
+
+        self.rect = RenderRect{x: self.rect.x, y: self.axis - height / 2.0, width, height};
+
+        let cursor_shift = (width - layout.cursor_width as f32) / 2.0;
+        self.render.iter_mut().for_each(|r| r.shift(cursor_shift, 0.0));
+
+        let inset = layout.line_thickness as f32;
+        self.render.push(RenderValue::Placeholder{
+            x: self.rect.x + inset,
+            y: self.rect.y + inset,
+            width: width - inset * 2.0,
+            height: height - inset * 2.0
+        });
+    }
+
+    pub fn shift(&mut self, x: f32, y: f32)
+    {
+        self.rect.x += x;
+        self.rect.y += y;
+        self.axis += y;
+
+        self.render.iter_mut().for_each(|r| r.shift(x, y));
+    }
+
+    pub fn render(&self, renderer: impl FnMut(&RenderValue))
+    {
+        self.render.iter().for_each(renderer);
+    }
+}
+
+trait CursorTrait
+{
+    fn next(self) -> Self;
+    fn follow(&self) -> Option<CursorFollow>;
+    fn index(&self) -> usize;
+}
+
+impl CursorTrait for &ValueCursor
+{
+    fn next(self) -> Self
+    {
+        &*self.follow.as_ref().unwrap().1
+    }
+
+    fn follow(&self) -> Option<CursorFollow>
+    {
+        self.follow.as_ref().map(|x| x.0)
+    }
+
+    fn index(&self) -> usize
+    {
+        self.index
+    }
+}
+
+impl CursorTrait for &mut ValueCursor
+{
+    fn next(self) -> Self
+    {
+        &mut *self.follow.as_mut().unwrap().1
+    }
+
+    fn follow(&self) -> Option<CursorFollow>
+    {
+        self.follow.as_ref().map(|x| x.0)
+    }
+
+    fn index(&self) -> usize
+    {
+        self.index
+    }
+}
+
+macro_rules! define_traverse
+{
+    ($name:ident, $part:ident, $($ref_t:tt)*) =>
+    {
+        fn $name<'a, T, C: CursorTrait>(
+            &'a $($ref_t)* self,
+            cursor: C,
+            finish: impl FnOnce(&'a $($ref_t)* Self, C) -> T
+        ) -> T
+        {
+            if let Some(direction) = cursor.follow()
+            {
+                let part = (& $($ref_t)* self.0[cursor.index() - 1]).$part(direction).unwrap();
+
+                part.$name(cursor.next(), finish)
+            } else
+            {
+                finish(self, cursor)
+            }
+        }
+    }
+}
+
+impl Default for InputValues
+{
+    fn default() -> Self
+    {
+        Self(Vec::new())
+    }
+}
+
+// a line of the document with things about it that arent part of the equation
+#[derive(Debug, Hash, Default)]
+pub struct Line
+{
+    pub values: InputValues,
+    // referenced from elsewhere, becomes \label in latex
+    pub label: Option<String>,
+    // text color instead of the default one, only used while rendering
+    pub color: Option<[u8; 3]>,
+    // hidden inside a collapsed block of lines
+    pub folded: bool
+}
+
+impl From<InputValues> for Line
+{
+    fn from(values: InputValues) -> Self
+    {
+        Self{values, label: None, color: None, folded: false}
+    }
+}
+
+impl Line
+{
+    // joins the other line onto the end, its label and color only stay if this one doesnt have them
+    pub fn append(&mut self, other: Line)
+    {
+        self.values.0.extend(other.values.0);
+        self.label = self.label.take().or(other.label);
+        self.color = self.color.or(other.color);
+    }
+}
+
+impl InputValues
+{
+    define_traverse!{traverse, part, }
+    define_traverse!{traverse_mut, part_mut, mut}
+
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>>
+    {
+        let mut errors = Vec::new();
+        self.validate_inner(&mut Vec::new(), &mut errors);
+
+        if errors.is_empty()
+        {
+            Ok(())
+        } else
+        {
+            Err(errors)
+        }
+    }
+
+    fn validate_inner(&self, path: &mut Vec<usize>, errors: &mut Vec<ValidationError>)
+    {
+        self.0.iter().enumerate().for_each(|(index, value)|
+        {
+            path.push(index);
+
+            let mut error = |kind| errors.push(ValidationError{path: path.clone(), kind});
+
+            match value
+            {
+                InputValue::Value(text) =>
+                {
+                    if text.is_empty()
+                    {
+                        error(ValidationErrorKind::EmptyValue);
+                    }
+                },
+                InputValue::Fraction{top, bottom} =>
+                {
+                    if top.0.is_empty()
+                    {
+                        error(ValidationErrorKind::EmptyFractionPart(CursorFollow::Top));
+                    }
+
+                    if bottom.0.is_empty()
+                    {
+                        error(ValidationErrorKind::EmptyFractionPart(CursorFollow::Bottom));
+                    }
+
+                    [top, bottom].into_iter().enumerate().for_each(|(part, values)|
+                    {
+                        path.push(part);
+                        values.validate_inner(path, errors);
+                        path.pop();
+                    });
+                },
+                InputValue::Cancel{content}
+                | InputValue::Sqrt{inner: content}
+                | InputValue::Paren{inner: content, ..}
+                | InputValue::Abs{inner: content} =>
+                {
+                    path.push(0);
+                    content.validate_inner(path, errors);
+                    path.pop();
+                },
+                InputValue::NthRoot{degree, inner} =>
+                {
+                    if degree.0.is_empty()
+                    {
+                        error(ValidationErrorKind::EmptyDegree);
+                    }
+
+                    [degree, inner].into_iter().enumerate().for_each(|(part, values)|
+                    {
+                        path.push(part);
+                        values.validate_inner(path, errors);
+                        path.pop();
+                    });
+                },
+                InputValue::Divided{left, right, ..} =>
+                {
+                    [left, right].into_iter().enumerate().for_each(|(part, values)|
+                    {
+                        path.push(part);
+                        values.validate_inner(path, errors);
+                        path.pop();
+                    });
+                },
+                InputValue::Superscript{base, exponent} =>
+                {
+                    if exponent.0.is_empty()
+                    {
+                        error(ValidationErrorKind::EmptyExponent);
+                    }
+
+                    [exponent, base].into_iter().enumerate().for_each(|(part, values)|
+                    {
+                        path.push(part);
+                        values.validate_inner(path, errors);
+                        path.pop();
+                    });
+                },
+                InputValue::Subscript{base, subscript} =>
+                {
+                    if subscript.0.is_empty()
+                    {
+                        error(ValidationErrorKind::EmptySubscript);
+                    }
+
+                    [base, subscript].into_iter().enumerate().for_each(|(part, values)|
+                    {
+                        path.push(part);
+                        values.validate_inner(path, errors);
+                        path.pop();
+                    });
+                },
+                InputValue::SubSuperscript{base, subscript, superscript} =>
+                {
+                    if superscript.0.is_empty()
+                    {
+                        error(ValidationErrorKind::EmptyExponent);
+                    }
+
+                    if subscript.0.is_empty()
+                    {
+                        error(ValidationErrorKind::EmptySubscript);
+                    }
+
+                    [superscript, base, subscript].into_iter().enumerate().for_each(|(part, values)|
+                    {
+                        path.push(part);
+                        values.validate_inner(path, errors);
+                        path.pop();
+                    });
+                },
+                InputValue::Matrix{rows, ..} | InputValue::Table{rows, ..} =>
+                {
+                    rows.iter().flatten().enumerate().for_each(|(part, cell)|
+                    {
+                        path.push(part);
+
+                        if cell.0.is_empty()
+                        {
+                            errors.push(ValidationError{path: path.clone(), kind: ValidationErrorKind::EmptyCell});
+                        }
+
+                        cell.validate_inner(path, errors);
+                        path.pop();
+                    });
+                },
+                InputValue::Sum{lower, upper, body} | InputValue::Integral{lower, upper, body} =>
+                {
+                    // a sum without bounds goes over everything so only the body has to be there
+                    if body.0.is_empty()
+                    {
+                        error(ValidationErrorKind::EmptyBody);
+                    }
+
+                    [upper, body, lower].into_iter().enumerate().for_each(|(part, values)|
+                    {
+                        path.push(part);
+                        values.validate_inner(path, errors);
+                        path.pop();
+                    });
+                },
+                InputValue::Dots(_)
+                | InputValue::Unit(_)
+                | InputValue::Space(_)
+                | InputValue::Answer{..}
+                | InputValue::TextRun{..}
+                | InputValue::Extension(_) => ()
+            }
+
+            path.pop();
+        });
+    }
+
+    pub fn depth(&self) -> usize
+    {
+        self.0.iter().map(InputValue::depth).max().unwrap_or(0)
+    }
+
+    // depth of the value before the cursor
+    pub fn depth_before(&self, cursor: &ValueCursor) -> usize
+    {
+        self.traverse(cursor, |this, cursor|
+        {
+            cursor.index.checked_sub(1).map(|index| this.0[index].depth()).unwrap_or(0)
+        })
+    }
+
+    // depth of the whole region the cursor is in
+    pub fn region_depth(&self, cursor: &ValueCursor) -> usize
+    {
+        self.traverse(cursor, |this, _| this.depth())
+    }
+
+    pub fn as_integer(&self) -> Option<u64>
+    {
+        let text = self.0.iter().map(|value|
+        {
+            if let InputValue::Value(x) = value
+            {
+                Some(x.as_str())
+            } else
+            {
+                None
+            }
+        }).collect::<Option<String>>()?;
+
+        if text.is_empty() || !text.chars().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+
+        text.parse().ok()
+    }
+
+    pub fn from_integer(value: u64) -> Self
+    {
+        Self(value.to_string().chars().map(|c| InputValue::Value(c.to_string())).collect())
+    }
+
+    // simplifies the fraction before the cursor, otherwise the innermost fraction the cursor is in
+    pub fn simplify_fraction(&mut self, cursor: &ValueCursor) -> bool
+    {
+        if let Some((index, direction, follow)) = cursor.inside()
+        {
+            let value = &mut self.0[index - 1];
+
+            let inner = value.part_mut(direction).map(|part| part.simplify_fraction(follow));
+
+            inner.unwrap_or(false) || value.simplify()
+        } else
+        {
+            cursor.index.checked_sub(1).map(|index| self.0[index].simplify()).unwrap_or(false)
+        }
+    }
+
+    // flattens the innermost fraction at the cursor into its numerator, a slash and its denominator
+    pub fn collapse_fraction(&mut self, cursor: &mut ValueCursor) -> bool
+    {
+        if let Some((direction, follow)) = cursor.follow.as_mut()
+        {
+            let index = cursor.index - 1;
+
+            let inner = self.0[index].part_mut(*direction).map(|part| part.collapse_fraction(follow));
+            if inner.unwrap_or(false)
+            {
+                return true;
+            }
+
+            self.collapse_at(index, cursor)
+        } else
+        {
+            cursor.index.checked_sub(1).map(|index| self.collapse_at(index, cursor)).unwrap_or(false)
+        }
+    }
+
+    // swaps the parts of the innermost fraction at the cursor, the cursor moves along with the part its in
+    pub fn swap_fraction(&mut self, cursor: &mut ValueCursor) -> bool
+    {
+        if let Some((direction, follow)) = cursor.follow.as_mut()
+        {
+            let value = &mut self.0[cursor.index - 1];
+
+            let inner = value.part_mut(*direction).map(|part| part.swap_fraction(follow));
+            if inner.unwrap_or(false)
+            {
+                return true;
+            }
+
+            let swapped = value.swap_parts();
+            if swapped
+            {
+                *direction = direction.opposite();
+            }
+
+            swapped
+        } else
+        {
+            cursor.index.checked_sub(1).map(|index| self.0[index].swap_parts()).unwrap_or(false)
+        }
+    }
+
+    fn collapse_at(&mut self, index: usize, cursor: &mut ValueCursor) -> bool
+    {
+        if !matches!(self.0[index], InputValue::Fraction{..})
+        {
+            return false;
+        }
+
+        let InputValue::Fraction{top, bottom} = self.0.remove(index) else { unreachable!() };
+
+        let values: Vec<_> = top.0.into_iter()
+            .chain([InputValue::Value("/".to_owned())])
+            .chain(bottom.0)
+            .collect();
+
+        let length = values.len();
+        self.0.splice(index..index, values);
+
+        *cursor = ValueCursor{index: index + length, follow: None};
+
+        true
+    }
+
+    // paths to every editable region in reading order, starting with this one
+    pub fn regions(&self, path: &mut RegionPath, regions: &mut Vec<RegionPath>)
+    {
+        regions.push(path.clone());
+
+        self.0.iter().enumerate().for_each(|(index, value)|
+        {
+            value.parts().into_iter().for_each(|direction|
+            {
+                if let Some(values) = value.part(direction)
+                {
+                    path.push((index + 1, direction));
+                    values.regions(path, regions);
+                    path.pop();
+                }
+            });
+        });
+    }
+
+    pub fn add_text(&mut self, cursor: &ValueCursor, text: String)
+    {
+        self.add_value(cursor, InputValue::Value(text));
+    }
+
+    pub fn add_value(&mut self, cursor: &ValueCursor, value: InputValue)
+    {
+        self.traverse_mut(cursor, |this, cursor| this.0.insert(cursor.index, value));
+    }
+
+    pub fn add_fraction(&mut self, cursor: &ValueCursor, mode: FractionEntryMode)
+    {
+        self.traverse_mut(cursor, |this, cursor|
+        {
+            match mode
+            {
+                FractionEntryMode::WrapPrevious =>
+                {
+                    if let Some(index) = cursor.index.checked_sub(1)
+                    {
+                        let value = mem::take(&mut this.0[index]);
+
+                        this.0[index] = InputValue::Fraction{top: Self(vec![value]), bottom: Self(Vec::new())};
+                    }
+                },
+                FractionEntryMode::EmptyTopFirst =>
+                {
+                    let value = InputValue::Fraction{top: Self(Vec::new()), bottom: Self(Vec::new())};
+
+                    this.0.insert(cursor.index, value);
+                }
+            }
+        });
+    }
+
+    // raises the value before the cursor to an empty exponent, nothing happens without one,
+    // a subscript gets the exponent on its own base next to it instead of becoming the base
+    pub fn add_superscript(&mut self, cursor: &ValueCursor)
+    {
+        self.traverse_mut(cursor, |this, cursor|
+        {
+            if let Some(index) = cursor.index.checked_sub(1)
+            {
+                let value = mem::take(&mut this.0[index]);
+
+                this.0[index] = match value
+                {
+                    InputValue::Subscript{base, subscript} =>
+                    {
+                        InputValue::SubSuperscript{base, subscript, superscript: Self(Vec::new())}
+                    },
+                    value => InputValue::Superscript{base: Self(vec![value]), exponent: Self(Vec::new())}
+                };
+            }
+        });
+    }
+
+    // lowers an empty subscript onto the value before the cursor, nothing happens without one,
+    // an exponent gets the subscript on its own base under it instead of becoming the base
+    pub fn add_subscript(&mut self, cursor: &ValueCursor)
+    {
+        self.traverse_mut(cursor, |this, cursor|
+        {
+            if let Some(index) = cursor.index.checked_sub(1)
+            {
+                let value = mem::take(&mut this.0[index]);
+
+                this.0[index] = match value
+                {
+                    InputValue::Superscript{base, exponent} =>
+                    {
+                        InputValue::SubSuperscript{base, subscript: Self(Vec::new()), superscript: exponent}
+                    },
+                    value => InputValue::Subscript{base: Self(vec![value]), subscript: Self(Vec::new())}
+                };
+            }
+        });
+    }
+
+    pub fn cancel_previous(&mut self, cursor: &ValueCursor)
+    {
+        self.traverse_mut(cursor, |this, cursor|
+        {
+            if let Some(index) = cursor.index.checked_sub(1)
+            {
+                let value = mem::take(&mut this.0[index]);
+
+                this.0[index] = InputValue::Cancel{content: Self(vec![value])};
+            }
+        });
+    }
+
+    // strikes through everything between two cursors, none if they arent in the same region
+    pub fn cancel_range(&mut self, a: &ValueCursor, b: &ValueCursor) -> Option<ValueCursor>
+    {
+        match (a.inside(), b.inside())
+        {
+            (Some((index, direction, a)), Some((b_index, b_direction, b))) =>
+            {
+                if index != b_index || direction != b_direction
+                {
+                    return None;
+                }
+
+                let follow = self.0[index - 1].part_mut(direction)?.cancel_range(a, b)?;
+
+                Some(ValueCursor{index, follow: Some((direction, Box::new(follow)))})
+            },
+            (None, None) =>
+            {
+                let (start, end) = (a.index.min(b.index), a.index.max(b.index));
+
+                if start == end
+                {
+                    return None;
+                }
+
+                let content = Self(self.0.drain(start..end).collect());
+                self.0.insert(start, InputValue::Cancel{content});
+
+                Some(ValueCursor{index: start + 1, follow: None})
+            },
+            _ => None
+        }
+    }
+
+    // values between two cursors in the same region, none if theyre in different ones or nothing is between them
+    pub fn selected(&self, a: &ValueCursor, b: &ValueCursor) -> Option<&[InputValue]>
+    {
+        match (a.inside(), b.inside())
+        {
+            (Some((index, direction, a)), Some((b_index, b_direction, b))) =>
+            {
+                if index != b_index || direction != b_direction
+                {
+                    return None;
+                }
+
+                self.0[index - 1].part(direction)?.selected(a, b)
+            },
+            (None, None) =>
+            {
+                let (start, end) = (a.index.min(b.index), a.index.max(b.index));
+
+                (start != end).then(|| &self.0[start..end])
+            },
+            _ => None
+        }
+    }
+
+    // puts the values at the cursor and moves it after them
+    pub fn insert_values(&mut self, cursor: &mut ValueCursor, values: InputValues)
+    {
+        self.traverse_mut(cursor, |this, cursor|
+        {
+            let length = values.0.len();
+            this.0.splice(cursor.index..cursor.index, values.0);
+
+            cursor.index += length;
+        });
+    }
+
+    // drops empty values and collapses runs of spaces into one space inside the range,
+    // structures in it get cleaned up whole and the cursor stays next to the same value
+    pub fn normalize_whitespace(&mut self, range: Range<usize>, mut cursor: Option<&mut ValueCursor>)
+    {
+        let ends_in_space = |value: &InputValue|
+        {
+            matches!(value, InputValue::Value(x) if x.ends_with(char::is_whitespace))
+        };
+
+        let mut index = range.start;
+        let mut end = range.end.min(self.0.len());
+
+        while index < end
+        {
+            let after_space = index > 0 && ends_in_space(&self.0[index - 1]);
+
+            if let InputValue::Value(x) = &mut self.0[index]
+            {
+                let mut previous_space = after_space;
+
+                *x = x.chars().filter_map(|c|
+                {
+                    let is_space = c.is_whitespace();
+                    let keep = !(is_space && previous_space);
+
+                    previous_space = is_space;
+
+                    keep.then_some(if is_space { ' ' } else { c })
+                }).collect();
+
+                if x.is_empty()
+                {
+                    self.0.remove(index);
+                    end -= 1;
+
+                    if let Some(cursor) = cursor.as_deref_mut()
+                    {
+                        if cursor.index > index
+                        {
+                            cursor.index -= 1;
+                        }
+                    }
+
+                    continue;
+                }
+            }
+
+            self.0[index].parts().into_iter().for_each(|direction|
+            {
+                let inner = cursor.as_deref_mut().filter(|cursor| cursor.index == index + 1).and_then(|cursor|
+                {
+                    cursor.follow.as_mut().filter(|(follow, _)| *follow == direction).map(|(_, follow)| &mut **follow)
+                });
+
+                if let Some(part) = self.0[index].part_mut(direction)
+                {
+                    let length = part.0.len();
+                    part.normalize_whitespace(0..length, inner);
+                }
+            });
+
+            index += 1;
+        }
+    }
+
+    // wraps the whole region the cursor is in with parentheses and puts the cursor after them
+    pub fn wrap_region(&mut self, cursor: &mut ValueCursor)
+    {
+        self.traverse_mut(cursor, |this, cursor|
+        {
+            let inner = mem::take(this);
+            this.0.push(InputValue::Paren{inner, kind: BracketKind::Round});
+
+            cursor.index = 1;
+        });
+    }
+
+    // moves the cursor out of the group its directly inside of if closes accepts it, false if it doesnt
+    pub fn close_group(&self, cursor: &mut ValueCursor, closes: &dyn Fn(&InputValue) -> bool) -> bool
+    {
+        let Some((direction, follow)) = cursor.follow.as_mut() else { return false };
+
+        let value = &self.0[cursor.index - 1];
+        if follow.follow.is_some()
+        {
+            return value.part(*direction).unwrap().close_group(follow, closes);
+        }
+
+        if closes(value)
+        {
+            cursor.follow = None;
+
+            true
+        } else
+        {
+            false
+        }
+    }
+
+    // moves to the next or previous cell of the innermost matrix or table the cursor is in, false if its not in one,
+    // going forward from the last column adds a new column while theres only one row,
+    // after that it goes to the start of the next row and adds a new one at the bottom
+    pub fn step_cell(&mut self, cursor: &mut ValueCursor, forward: bool) -> bool
+    {
+        let Some((direction, follow)) = cursor.follow.as_mut() else { return false };
+
+        let value = &mut self.0[cursor.index - 1];
+        if value.part_mut(*direction).unwrap().step_cell(follow, forward)
+        {
+            return true;
+        }
+
+        let (Some((rows, columns)), CursorFollow::Cell{row, column}) = (value.grid_mut(), *direction)
+        else
+        {
+            return false;
+        };
+
+        let (row, column, index) = if forward
+        {
+            if column + 1 < rows[row].len()
+            {
+                (row, column + 1, 0)
+            } else if rows.len() == 1
+            {
+                rows[0].push(InputValues::default());
+                columns.into_iter().for_each(|columns| columns.push(ColumnAlign::default()));
+
+                (row, column + 1, 0)
+            } else
+            {
+                if row + 1 == rows.len()
+                {
+                    let columns = rows[row].len();
+                    rows.push((0..columns).map(|_| InputValues::default()).collect());
+                }
+
+                (row + 1, 0, 0)
+            }
+        } else
+        {
+            let previous = if column > 0
+            {
+                Some((row, column - 1))
+            } else
+            {
+                row.checked_sub(1).map(|row| (row, rows[row].len() - 1))
+            };
+
+            let Some((row, column)) = previous else { return true };
+
+            (row, column, rows[row][column].0.len())
+        };
+
+        *direction = CursorFollow::Cell{row, column};
+        **follow = ValueCursor{index, follow: None};
+
+        true
+    }
+
+    // adds an empty row after the one the cursor is in inside the innermost matrix or table and moves to its start,
+    // false if its not in one
+    pub fn add_matrix_row(&mut self, cursor: &mut ValueCursor) -> bool
+    {
+        let Some((direction, follow)) = cursor.follow.as_mut() else { return false };
+
+        let value = &mut self.0[cursor.index - 1];
+        if value.part_mut(*direction).unwrap().add_matrix_row(follow)
+        {
+            return true;
+        }
+
+        let (InputValue::Matrix{rows, ..} | InputValue::Table{rows, ..}, CursorFollow::Cell{row, ..}) = (value, *direction)
+        else
+        {
+            return false;
+        };
+
+        let width = rows[row].len();
+        rows.insert(row + 1, (0..width).map(|_| InputValues::default()).collect());
+
+        *direction = CursorFollow::Cell{row: row + 1, column: 0};
+        **follow = ValueCursor::default();
+
+        true
+    }
+
+    // changes the innermost table the cursor is in, edit gets the column the cursor is in,
+    // false if its not in a table
+    pub fn edit_table(&mut self, cursor: &ValueCursor, edit: &dyn Fn(&mut Vec<ColumnAlign>, &mut bool, usize)) -> bool
+    {
+        let Some((direction, follow)) = cursor.follow.as_ref() else { return false };
+
+        let value = &mut self.0[cursor.index - 1];
+        if value.part_mut(*direction).unwrap().edit_table(follow, edit)
+        {
+            return true;
+        }
+
+        let (InputValue::Table{columns, rules, ..}, CursorFollow::Cell{column, ..}) = (value, *direction)
+        else
+        {
+            return false;
+        };
+
+        edit(columns, rules, column);
+
+        true
+    }
+
+    // puts a bar at the cursor when its directly inside brackets, the values from start up to the cursor
+    // get dropped and the cursor goes to the start of the part after the bar, false if its not in brackets
+    pub fn divide_group(&mut self, cursor: &mut ValueCursor, start: usize) -> bool
+    {
+        let Some((direction, follow)) = cursor.follow.as_mut() else { return false };
+
+        let value = &mut self.0[cursor.index - 1];
+        if follow.follow.is_some()
+        {
+            return value.part_mut(*direction).unwrap().divide_group(follow, start);
+        }
+
+        let (InputValue::Paren{inner, kind}, CursorFollow::Top) = (&mut *value, *direction)
+        else
+        {
+            return false;
+        };
+
+        let kind = *kind;
+        let right = InputValues(inner.0.split_off(follow.index));
+        inner.0.truncate(start);
+
+        *value = InputValue::Divided{left: mem::take(inner), right, kind};
+
+        *direction = CursorFollow::Bottom;
+        **follow = ValueCursor::default();
+
+        true
+    }
+
+    // removes the number or name right before the cursor along with spaces after it,
+    // never goes past the start of the region or into a structure so structures stay whole
+    pub fn remove_word_left(&mut self, cursor: &mut ValueCursor) -> bool
+    {
+        self.traverse_mut(cursor, |this, cursor|
+        {
+            let is_space = |value: &InputValue| matches!(value, InputValue::Value(x) if x.trim().is_empty());
+
+            let is_number = |c: char| c.is_ascii_digit() || c == '.' || c == ',';
+            let kind = |value: &InputValue| -> Option<bool>
+            {
+                let InputValue::Value(x) = value else { return None };
+
+                if !x.is_empty() && x.chars().all(is_number)
+                {
+                    Some(true)
+                } else if !x.is_empty() && x.chars().all(char::is_alphabetic)
+                {
+                    Some(false)
+                } else
+                {
+                    None
+                }
+            };
+
+            let before = &this.0[..cursor.index];
+
+            let spaces = before.iter().rev().take_while(|value| is_space(value)).count();
+            let word_end = cursor.index - spaces;
+
+            // numbers and names next to each other like 2x are separate words, other symbols go one at a time
+            let word = match this.0[..word_end].last()
+            {
+                Some(value) if kind(value).is_some() =>
+                {
+                    this.0[..word_end].iter().rev().take_while(|x| kind(x) == kind(value)).count()
+                },
+                Some(InputValue::Value(_)) => 1,
+                _ => 0
+            };
+
+            let start = word_end - word;
+
+            if start == cursor.index
+            {
+                return false;
+            }
+
+            this.0.drain(start..cursor.index);
+            cursor.index = start;
+
+            true
+        })
+    }
+
+    // name and start index of a \command directly before the cursor
+    pub fn command_before(&self, cursor: &ValueCursor) -> Option<(usize, String)>
+    {
+        self.traverse(cursor, |this, cursor|
+        {
+            // a cursor past the end shouldnt be able to take rendering down with it
+            let before = this.0.get(..cursor.index)?;
+
+            let is_symbol = matches!(before.last(), Some(InputValue::Value(x)) if CONTROL_SYMBOLS.contains(&x.as_str()));
+
+            let letters = if is_symbol
+            {
+                1
+            } else
+            {
+                before.iter().rev().take_while(|value|
+                {
+                    matches!(value, InputValue::Value(x) if x.chars().all(|c| c.is_ascii_alphabetic()))
+                }).count()
+            };
+
+            let start = cursor.index.checked_sub(letters + 1)?;
+
+            if !matches!(&this.0[start], InputValue::Value(x) if x == "\\")
+            {
+                return None;
+            }
+
+            let name = before[start + 1..].iter().map(|value|
+            {
+                if let InputValue::Value(x) = value
+                {
+                    x.as_str()
+                } else
+                {
+                    unreachable!()
+                }
+            }).collect();
+
+            Some((start, name))
+        })
+    }
+
+    pub fn replace_before(&mut self, cursor: &mut ValueCursor, start: usize, value: InputValue)
+    {
+        self.traverse_mut(cursor, |this, cursor|
+        {
+            this.0.splice(start..cursor.index, [value]);
+            cursor.index = start + 1;
+        });
+    }
+
+    // fixes up a cursor that might point past the values or into structures that dont exist anymore
+    pub fn revalidate_cursor(&self, cursor: &mut ValueCursor)
+    {
+        cursor.index = cursor.index.min(self.0.len());
+
+        if let Some((direction, follow)) = cursor.follow.as_mut()
+        {
+            let part = cursor.index.checked_sub(1).and_then(|index| self.0[index].part(*direction));
+
+            if let Some(part) = part
+            {
+                part.revalidate_cursor(follow);
+            } else
+            {
+                cursor.follow = None;
+            }
+        }
+    }
+
+    // removes everything between two cursors, a structure that is only partially selected
+    // stays and only loses the selected part of its contents, returns the cursor at the start
+    pub fn remove_range(&mut self, a: &ValueCursor, b: &ValueCursor) -> ValueCursor
+    {
+        let (start, end) = if a.is_before(b) { (a, b) } else { (b, a) };
+
+        self.remove_between(Some(start), Some(end));
+
+        let mut cursor = start.clone();
+        self.revalidate_cursor(&mut cursor);
+
+        cursor
+    }
+
+    // none means the start or the end of this region
+    fn remove_between(&mut self, start: Option<&ValueCursor>, end: Option<&ValueCursor>)
+    {
+        let start_follow = start.and_then(ValueCursor::inside);
+        let end_follow = end.and_then(ValueCursor::inside);
+
+        if let (Some((index, direction, start)), Some((end_index, end_direction, end))) = (start_follow, end_follow)
+        {
+            if index == end_index && direction == end_direction
+            {
+                if let Some(part) = self.0[index - 1].part_mut(direction)
+                {
+                    part.remove_between(Some(start), Some(end));
+                }
+
+                return;
+            }
+        }
+
+        let from = if let Some((index, direction, start)) = start_follow
+        {
+            if let Some(part) = self.0[index - 1].part_mut(direction)
+            {
+                part.remove_between(Some(start), None);
+            }
+
+            index
+        } else
+        {
+            start.map(|x| x.index).unwrap_or(0)
+        };
+
+        let to = if let Some((index, direction, end)) = end_follow
+        {
+            if let Some(part) = self.0[index - 1].part_mut(direction)
+            {
+                part.remove_between(None, Some(end));
+            }
+
+            index - 1
+        } else
+        {
+            end.map(|x| x.index).unwrap_or(self.0.len())
+        };
+
+        if from < to
+        {
+            self.0.drain(from..to);
+        }
+    }
+
+    fn replace(&mut self, index: usize, values: InputValues)
+    {
+        self.0.remove(index);
+
+        values.0.into_iter().rev().for_each(|value|
+        {
+            self.0.insert(index, value);
+        });
+    }
+
+    pub fn remove_single(&mut self, cursor: &mut ValueCursor) -> bool
+    {
+        if let Some((direction, follow)) = cursor.follow.as_mut()
+        {
+            let index = cursor.index - 1;
+            let base_part = self.0[index].entry_part(true);
+            let remove_this = match (&mut self.0[index], direction)
+            {
+                (InputValue::Fraction{top, bottom}, CursorFollow::Top) =>
+                {
+                    let remove_this = top.remove_single(follow);
+
+                    if remove_this
+                    {
+                        let value = mem::take(bottom);
+                        self.replace(index, value);
+
+                        cursor.index = index;
+                    }
+
+                    remove_this
+                },
+                (InputValue::Fraction{top, bottom}, CursorFollow::Bottom) =>
+                {
+                    let remove_this = bottom.remove_single(follow);
+
+                    if remove_this
+                    {
+                        let top_length = top.0.len();
+
+                        let value = mem::take(top);
+                        self.replace(index, value);
+
+                        cursor.index = index + top_length;
+                    }
+
+                    remove_this
+                },
+                (
+                    InputValue::Cancel{content}
+                    | InputValue::Sqrt{inner: content}
+                    | InputValue::Paren{inner: content, ..}
+                    | InputValue::Abs{inner: content},
+                    _
+                ) =>
+                {
+                    let remove_this = content.remove_single(follow);
+
+                    // backspacing at the start takes the strike, root, brackets or bars away but keeps the content
+                    if remove_this
+                    {
+                        let value = mem::take(content);
+                        self.replace(index, value);
+
+                        cursor.index = index;
+                    }
+
+                    remove_this
+                },
+                (value@InputValue::Divided{..}, direction) =>
+                {
+                    let InputValue::Divided{left, right, kind} = value else { unreachable!() };
+
+                    let in_right = *direction == CursorFollow::Bottom;
+                    let remove_this = if in_right
+                    {
+                        right.remove_single(follow)
+                    } else
+                    {
+                        left.remove_single(follow)
+                    };
+
+                    if !remove_this
+                    {
+                        false
+                    } else if in_right
+                    {
+                        // backspacing at the start of the right part only takes the bar away
+                        let (left_length, kind) = (left.0.len(), *kind);
+
+                        let mut inner = mem::take(left);
+                        inner.0.append(&mut right.0);
+                        *value = InputValue::Paren{inner, kind};
+
+                        *direction = CursorFollow::Top;
+                        **follow = ValueCursor{index: left_length, follow: None};
+
+                        false
+                    } else
+                    {
+                        // at the start of the left part the brackets go too
+                        let mut inner = mem::take(left);
+                        inner.0.append(&mut right.0);
+                        self.replace(index, inner);
+
+                        cursor.index = index;
+
+                        true
+                    }
+                },
+                (InputValue::Superscript{base, exponent: script}
+                | InputValue::Subscript{base, subscript: script}
+                | InputValue::NthRoot{degree: base, inner: script}, direction) =>
+                {
+                    let in_script = *direction != base_part;
+                    let remove_this = if in_script
+                    {
+                        script.remove_single(follow)
+                    } else
+                    {
+                        base.remove_single(follow)
+                    };
+
+                    // backspacing at the start of either part flattens it back into both parts in the order theyre typed
+                    if remove_this
+                    {
+                        let base_length = base.0.len();
+
+                        let mut value = mem::take(base);
+                        value.0.append(&mut script.0);
+                        self.replace(index, value);
+
+                        cursor.index = if in_script { index + base_length } else { index };
+                    }
+
+                    remove_this
+                },
+                (InputValue::SubSuperscript{base, subscript, superscript}, direction) =>
+                {
+                    let remove_this = match direction
+                    {
+                        CursorFollow::Top => superscript.remove_single(follow),
+                        CursorFollow::Middle => base.remove_single(follow),
+                        CursorFollow::Bottom => subscript.remove_single(follow),
+                        CursorFollow::Cell{..} => unreachable!()
+                    };
+
+                    // flattens into the base, subscript and superscript in the order theyre typed
+                    if remove_this
+                    {
+                        let offset = match direction
+                        {
+                            CursorFollow::Top => base.0.len() + subscript.0.len(),
+                            CursorFollow::Middle => 0,
+                            CursorFollow::Bottom => base.0.len(),
+                            CursorFollow::Cell{..} => unreachable!()
+                        };
+
+                        let mut value = mem::take(base);
+                        value.0.append(&mut subscript.0);
+                        value.0.append(&mut superscript.0);
+                        self.replace(index, value);
+
+                        cursor.index = index + offset;
+                    }
+
+                    remove_this
+                },
+                (InputValue::Sum{lower, upper, body} | InputValue::Integral{lower, upper, body}, direction) =>
+                {
+                    let remove_this = match direction
+                    {
+                        CursorFollow::Top => upper.remove_single(follow),
+                        CursorFollow::Middle => body.remove_single(follow),
+                        CursorFollow::Bottom => lower.remove_single(follow),
+                        CursorFollow::Cell{..} => unreachable!()
+                    };
+
+                    // flattens into the lower bound, upper bound and body in the order theyre typed
+                    if remove_this
+                    {
+                        let offset = match direction
+                        {
+                            CursorFollow::Top => lower.0.len(),
+                            CursorFollow::Middle => lower.0.len() + upper.0.len(),
+                            CursorFollow::Bottom => 0,
+                            CursorFollow::Cell{..} => unreachable!()
+                        };
+
+                        let mut value = mem::take(lower);
+                        value.0.append(&mut upper.0);
+                        value.0.append(&mut body.0);
+                        self.replace(index, value);
+
+                        cursor.index = index + offset;
+                    }
+
+                    remove_this
+                },
+                (value@(InputValue::Matrix{..} | InputValue::Table{..}), direction) =>
+                {
+                    let CursorFollow::Cell{row, column} = *direction else { unreachable!() };
+                    let (rows, columns) = value.grid_mut().unwrap();
+
+                    if !rows[row][column].remove_single(follow)
+                    {
+                        false
+                    } else if rows.iter().flatten().all(|cell| cell.0.is_empty())
+                    {
+                        self.0.remove(index);
+                        cursor.index = index;
+
+                        true
+                    } else
+                    {
+                        // backspacing at the start of a cell takes away its column or row if theyre empty,
+                        // then goes back to the end of the cell before
+                        let column_empty = rows.iter().all(|cells| cells[column].0.is_empty());
+                        let row_empty = rows.len() > 1 && rows[row].iter().all(|cell| cell.0.is_empty());
+
+                        if column > 0 && column_empty
+                        {
+                            rows.iter_mut().for_each(|cells| { cells.remove(column); });
+                            columns.into_iter().for_each(|columns| { columns.remove(column); });
+                        } else if column == 0 && row_empty
+                        {
+                            rows.remove(row);
+                        }
+
+                        let previous = if column > 0
+                        {
+                            Some((row, column - 1))
+                        } else
+                        {
+                            row.checked_sub(1).map(|row| (row, rows[row].len() - 1))
+                        };
+
+                        let (row, column) = previous.unwrap_or((0, 0));
+                        let index = if previous.is_some() { rows[row][column].0.len() } else { 0 };
+
+                        *direction = CursorFollow::Cell{row, column};
+                        **follow = ValueCursor{index, follow: None};
+
+                        false
+                    }
+                },
+                (
+                    InputValue::Value(_)
+                    | InputValue::Dots(_)
+                    | InputValue::Unit(_)
+                    | InputValue::Space(_)
+                    | InputValue::Answer{..}
+                    | InputValue::TextRun{..}
+                    | InputValue::Extension(_),
+                    _
+                ) => unreachable!(),
+                (InputValue::Fraction{..}, CursorFollow::Middle | CursorFollow::Cell{..}) => unreachable!()
+            };
+
+            if remove_this
+            {
+                cursor.follow = None;
+            }
+
+            false
+        } else
+        {
+            if let Some(index) = cursor.index.checked_sub(1)
+            {
+                self.0.remove(index);
+                cursor.index = index;
+
+                false
+            } else
+            {
+                true
+            }
+        }
+    }
+
+    // removes whatever comes after the cursor
+    pub fn remove_next(&mut self, cursor: &mut ValueCursor, mode: DeleteStructure)
+    {
+        if let Some((direction, follow)) = cursor.follow.as_mut()
+        {
+            let part = self.0[cursor.index - 1].part_mut(*direction).unwrap();
+
+            // deleting doesnt cross out of a fraction part
+            part.remove_next(follow, mode);
+
+            return;
+        }
+
+        let index = cursor.index;
+        let Some(next) = self.0.get_mut(index) else { return };
+
+        let direction = next.entry_part(true);
+        match (next, mode)
+        {
+            (next, DeleteStructure::StepInto) if next.part(direction).is_some_and(|part| !part.0.is_empty()) =>
+            {
+                let mut follow = ValueCursor::default();
+                next.part_mut(direction).unwrap().remove_next(&mut follow, mode);
+
+                cursor.index += 1;
+                cursor.follow = Some((direction, Box::new(follow)));
+            },
+            _ =>
+            {
+                self.0.remove(index);
+            }
+        }
+    }
+
+    fn move_right_inner(&self, cursor: &mut ValueCursor) -> bool
+    {
+        if let Some((direction, follow)) = cursor.follow.as_mut()
+        {
+            let this = &self.0[cursor.index - 1];
+            let move_this = this.part(*direction).unwrap().move_right_inner(follow);
+
+            if move_this
+            {
+                if let Some(next) = this.next_part(*direction, true)
+                {
+                    *direction = next;
+                    **follow = ValueCursor::default();
+
+                    return false;
+                }
+
+                cursor.follow = None;
+            }
+
+            move_this
+        } else
+        {
+            if cursor.index < self.0.len()
+            {
+                cursor.index += 1;
+
+                false
+            } else
+            {
+                true
+            }
+        }
+    }
+
+    fn move_left_inner(&self, cursor: &mut ValueCursor) -> bool
+    {
+        if let Some((direction, follow)) = cursor.follow.as_mut()
+        {
+            let this = &self.0[cursor.index - 1];
+
+            if this.part(*direction).unwrap().move_left_inner(follow)
+            {
+                if let Some(previous) = this.next_part(*direction, false)
+                {
+                    let index = this.part(previous).unwrap().0.len();
+
+                    *direction = previous;
+                    **follow = ValueCursor{index, ..Default::default()};
+
+                    return false;
+                }
+
+                cursor.follow = None;
+                self.move_left_inner(cursor);
+            }
+        } else
+        {
+            if let Some(index) = cursor.index.checked_sub(1)
+            {
+                cursor.index = index;
+            } else
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn step_in(&self, cursor: &mut ValueCursor, right: bool) -> bool
+    {
+        self.traverse(cursor, |this, cursor|
+        {
+            if let Some(index) = cursor.index.checked_sub(1)
+            {
+                let direction = this.0[index].entry_part(!right);
+                if let Some(part) = this.0[index].part(direction)
+                {
+                    let index = if right { part.0.len() } else { 0 };
+                    let new_cursor = ValueCursor{index, ..Default::default()};
+
+                    cursor.follow = Some((direction, Box::new(new_cursor)));
+
+                    return true;
+                }
+            }
+
+            false
+        })
+    }
+
+    pub fn move_left(&self, cursor: &mut ValueCursor)
+    {
+        if !self.step_in(cursor, true)
+        {
+            self.move_left_inner(cursor);
+        }
+    }
+
+    pub fn move_right(&self, cursor: &mut ValueCursor)
+    {
+        if !self.move_right_inner(cursor)
+        {
+            self.step_in(cursor, false);
+        }
+    }
+
+    fn move_vertical(
+        &self,
+        cursor: &mut ValueCursor,
+        up: bool
+    ) -> bool
+    {
+        if let Some((direction, follow)) = cursor.follow.as_mut()
+        {
+            let this = &self.0[cursor.index - 1];
+
+            if follow.follow.is_none()
+            {
+                // structures with a single part have nowhere to go
+                let Some(next) = this.vertical_part(*direction, up) else { return false };
+
+                let a = this.part(*direction).unwrap().0.len();
+                let b = this.part(next).unwrap().0.len();
+
+                *direction = next;
+
+                let diff = a as i32 - b as i32;
+                let half_diff = diff / 2;
+
+                let limit = b as i32;
+                follow.index = (follow.index as i32 - half_diff).clamp(0, limit) as usize;
+
+                true
+            } else
+            {
+                this.part(*direction).unwrap().move_vertical(&mut **follow, up)
+            }
+        } else
+        {
+            false
+        }
+    }
+
+    pub fn move_up(&self, cursor: &mut ValueCursor) -> bool
+    {
+        self.move_vertical(cursor, true)
+    }
+
+    pub fn move_down(&self, cursor: &mut ValueCursor) -> bool
+    {
+        self.move_vertical(cursor, false)
+    }
+
+    // single values or runs of digits which get rendered as one piece of text
+    fn render_chunks(&self) -> Vec<Range<usize>>
+    {
+        let mut chunks: Vec<Range<usize>> = Vec::new();
+
+        self.0.iter().enumerate().for_each(|(index, value)|
+        {
+            if let Some(last) = chunks.last_mut()
+            {
+                if value.is_digits() && self.0[last.start].is_digits()
+                {
+                    last.end = index + 1;
+                    return;
+                }
+            }
+
+            chunks.push(index..index + 1);
+        });
+
+        chunks
+    }
+
+    fn render_number<'a>(
+        &'a self,
+        layout: &LayoutConfig,
+        range: Range<usize>,
+        cursor: Option<&ValueCursor>,
+        x: f32,
+        y: f32,
+        f: &impl Fn(RenderValue) -> RenderResult
+    ) -> RenderResult<'a>
+    {
+        let axis = y + layout.font_size as f32 / 2.0;
+
+        let digits: Vec<&str> = self.0[range.clone()].iter().map(|value|
+        {
+            if let InputValue::Value(x) = value
+            {
+                x.as_str()
+            } else
+            {
+                unreachable!()
+            }
+        }).collect();
+
+        let mut render = f(RenderValue::Text{x, y, text: digits.concat().into()});
+        render.shift(0.0, axis - render.axis);
+
+        if let Some(ValueCursor{index, follow: None}) = cursor
+        {
+            if range.start < *index && *index <= range.end
+            {
+                let prefix = digits[..index - range.start].concat();
+                let width = f(RenderValue::Text{x: 0.0, y: 0.0, text: prefix.into()}).rect.width;
+
+                render = render.combine(f(RenderValue::new_cursor(x + width, axis, layout)));
+            }
+        }
+
+        render
+    }
+
+    pub fn render(
+        &self,
+        layout: &LayoutConfig,
+        style: MathStyle,
+        cursor: Option<&ValueCursor>,
+        x: f32,
+        y: f32,
+        f: &impl Fn(RenderValue) -> RenderResult
+    ) -> RenderResult<'_>
+    {
+        self.render_range(layout, style, cursor, x, y, f, 0..self.0.len())
+    }
+
+    // renders in the script style of the style its in, empty ones get a placeholder as small
+    fn render_script(
+        &self,
+        layout: &LayoutConfig,
+        style: MathStyle,
+        cursor: Option<&ValueCursor>,
+        x: f32,
+        y: f32,
+        f: &impl Fn(RenderValue) -> RenderResult
+    ) -> RenderResult<'_>
+    {
+        let script = style.script();
+        let scale = style.relative_scale(script, layout);
+
+        let mut render = self.render_scaled(layout, script, scale, cursor, x, y, f);
+
+        if self.0.is_empty()
+        {
+            render.scaled_placeholder(layout, scale);
+        }
+
+        render
+    }
+
+    // renders with the text shrunk by the scale relative to whatever its inside of
+    #[allow(clippy::too_many_arguments)]
+    fn render_scaled(
+        &self,
+        layout: &LayoutConfig,
+        style: MathStyle,
+        scale: f32,
+        cursor: Option<&ValueCursor>,
+        x: f32,
+        y: f32,
+        f: &impl Fn(RenderValue) -> RenderResult
+    ) -> RenderResult<'_>
+    {
+        if scale == 1.0
+        {
+            return self.render(layout, style, cursor, x, y, f);
+        }
+
+        // only text shrinks, lines and the cursor keep their size
+        let scaled: &dyn Fn(RenderValue) -> RenderResult = &|value| match value
+        {
+            RenderValue::Text{..} => f(RenderValue::Scaled{scale, value: Box::new(value)}),
+            // scripts of scripts get smaller again
+            RenderValue::Scaled{scale: inner, value} => f(RenderValue::Scaled{scale: scale * inner, value}),
+            value => f(value)
+        };
+
+        self.render(layout, style, cursor, x, y, &scaled)
+    }
+
+    // same as render but too wide lines get split into rows after operators, only for the top level of a line
+    pub fn render_wrapped(
+        &self,
+        layout: &LayoutConfig,
+        cursor: Option<&ValueCursor>,
+        x: f32,
+        y: f32,
+        f: &impl Fn(RenderValue) -> RenderResult<'_>
+    ) -> RenderResult<'_>
+    {
+        let whole = self.render(layout, MathStyle::DISPLAY, cursor, x, y, f);
+
+        let Some(max_width) = layout.wrap_width().filter(|max_width| whole.rect.width > *max_width)
+        else
+        {
+            return whole;
+        };
+
+        let rows = self.wrap_rows(layout, f, max_width);
+
+        if rows.len() < 2
+        {
+            return whole;
+        }
+
+        rows.into_iter().enumerate().fold(None, |acc: Option<RenderResult>, (index, range)|
+        {
+            let row_x = if index == 0 { x } else { x + layout.wrap_indent() };
+            let mut row = self.render_range(layout, MathStyle::DISPLAY, cursor, row_x, y, f, range);
+
+            // the axis of the whole thing stays on the first row
+            Some(match acc
+            {
+                Some(acc) =>
+                {
+                    row.shift(0.0, acc.rect.y + acc.rect.height + layout.line_spacing() - row.rect.y);
+
+                    acc.combine(row)
+                },
+                None => row
+            })
+        }).unwrap()
+    }
+
+    // ranges of values on each row, breaking after the last operator that still fits
+    fn wrap_rows(
+        &self,
+        layout: &LayoutConfig,
+        f: &impl Fn(RenderValue) -> RenderResult,
+        max_width: f32
+    ) -> Vec<Range<usize>>
+    {
+        let length = self.0.len();
+
+        let is_break = |value: &InputValue|
+        {
+            matches!(value, InputValue::Value(x) if ["+", "-", "−", "=", "<", ">", "≤", "≥", "≠", "±", "*", "×", "·"].contains(&x.as_str()))
+        };
+
+        // the end of the line is the last place a row can end
+        let ends: Vec<usize> = (1..length).filter(|index| is_break(&self.0[index - 1])).chain([length]).collect();
+
+        let mut rows = Vec::new();
+        let mut start = 0;
+        let mut fitting = None;
+        let mut index = 0;
+
+        while index < ends.len()
+        {
+            let end = ends[index];
+            let available = if rows.is_empty() { max_width } else { max_width - layout.wrap_indent() };
+
+            if self.render_range(layout, MathStyle::DISPLAY, None, 0.0, 0.0, f, start..end).rect.width <= available
+            {
+                fitting = Some(end);
+                index += 1;
+
+                continue;
+            }
+
+            // if nothing fits it overflows until the first place it can break
+            let end = fitting.take().unwrap_or(end);
+
+            if end == length
+            {
+                break;
+            }
+
+            rows.push(start..end);
+            start = end;
+
+            if end == ends[index]
+            {
+                index += 1;
+            }
+        }
+
+        rows.push(start..length);
+
+        rows
+    }
+
+    // width of everything before the first = at this level
+    pub fn equals_offset(&self, layout: &LayoutConfig, f: &impl Fn(RenderValue) -> RenderResult) -> Option<f32>
+    {
+        let index = self.0.iter().position(|value| matches!(value, InputValue::Value(x) if x == "="))?;
+
+        Some(self.render_range(layout, MathStyle::DISPLAY, None, 0.0, 0.0, f, 0..index).rect.width)
+    }
+
+    // renders only the values in the range, starting at x
+    #[allow(clippy::too_many_arguments)]
+    fn render_range(
+        &self,
+        layout: &LayoutConfig,
+        style: MathStyle,
+        cursor: Option<&ValueCursor>,
+        x: f32,
+        y: f32,
+        f: &impl Fn(RenderValue) -> RenderResult,
+        values: Range<usize>
+    ) -> RenderResult<'_>
+    {
+        let axis = y + layout.font_size as f32 / 2.0;
+
+        let mut start = RenderResult::empty(RenderRect{x, y, width: 0.0, height: 0.0});
+        start.axis = axis;
+
+        // a cursor at the start of a later row is drawn at the end of the one before it
+        if let (0, Some(ValueCursor{index: 0, follow: None})) = (values.start, cursor)
+        {
+            start = start.combine(f(RenderValue::new_cursor(x, axis, layout)));
+        }
+
+        // how far back negative spaces pull the next value and the width of the value before them
+        let mut pull = 0.0;
+        let mut last_width = 0.0;
+
+        self.render_chunks().into_iter().filter(|range| values.contains(&range.start)).fold(start, |acc, range|
+        {
+            let index = range.start;
+            let value = &self.0[index];
+
+            if let InputValue::Space(kind) = value
+            {
+                // a run of them only pulls over half the value before so nothing ends up on top of it
+                pull = (pull - kind.width() * layout.font_size as f32).clamp(0.0, last_width / 2.0);
+            }
+
+            let x = if value.is_space() { x } else { x - mem::take(&mut pull) };
+
+            // rows dont start with a gap
+            let implicit_product = index > values.start && self.0[index - 1].is_implicit_product(value);
+            let x = if implicit_product { x + layout.implicit_product_space() } else { x };
+
+            let start_x = x + acc.rect.width;
+
+            let combined = if range.len() > 1
+            {
+                acc.combine(self.render_number(layout, range, cursor, start_x, y, f))
+            } else
+            {
+                let this_index = Some(index + 1) == cursor.map(|x| x.index);
+                let cursor = cursor.and_then(|cursor|
+                {
+                    this_index.then(|| { cursor.follow.as_ref() }).flatten()
+                });
+
+                let mut render = value.render(layout, style, cursor, start_x, y, f);
+                render.shift(0.0, axis - render.axis);
+
+                let rect = render.rect;
+
+                let mut combined = acc.combine(render);
+                if this_index && cursor.is_none()
+                {
+                    combined = combined.combine(f(RenderValue::new_cursor(rect.x + rect.width, axis, layout)));
+                }
+
+                combined
+            };
+
+            if !value.is_space()
+            {
+                last_width = combined.rect.x + combined.rect.width - start_x;
+            }
+
+            combined
+        })
+    }
+}
+
+// ordered the way the parts are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CursorFollow
+{
+    Top,
+    // only in structures with three parts stacked on each other, like a base between two scripts
+    Middle,
+    Bottom,
+    // a cell of a matrix, not part of ALL since the cells depend on the size of the matrix
+    Cell{row: usize, column: usize}
+}
+
+impl CursorFollow
+{
+    pub const ALL: [Self; 3] = [Self::Top, Self::Middle, Self::Bottom];
+
+    pub fn opposite(self) -> Self
+    {
+        match self
+        {
+            Self::Top => Self::Bottom,
+            Self::Middle => Self::Middle,
+            Self::Bottom => Self::Top,
+            Self::Cell{..} => self
+        }
+    }
+}
+
+// index of the structure (same as ValueCursor's index) and which part of it to follow
+type RegionPath = Vec<(usize, CursorFollow)>;
+
+#[derive(Debug, Clone, Hash)]
+pub struct ValueCursor
+{
+    index: usize,
+    follow: Option<(CursorFollow, Box<ValueCursor>)>
+}
+
+impl Default for ValueCursor
+{
+    fn default() -> Self
+    {
+        Self{index: 0, follow: None}
+    }
+}
+
+impl ValueCursor
+{
+    pub fn add_fraction(&mut self, mode: FractionEntryMode)
+    {
+        if let Some((_, follow)) = self.follow.as_mut()
+        {
+            follow.add_fraction(mode);
+        } else
+        {
+            match mode
+            {
+                FractionEntryMode::WrapPrevious =>
+                {
+                    if self.index != 0
+                    {
+                        self.follow = Some((CursorFollow::Bottom, Box::new(Self::default())));
+                    }
+                },
+                FractionEntryMode::EmptyTopFirst =>
+                {
+                    self.index += 1;
+                    self.follow = Some((CursorFollow::Top, Box::new(Self::default())));
+                }
+            }
+        }
+    }
+
+    pub fn add_superscript(&mut self)
+    {
+        if let Some((_, follow)) = self.follow.as_mut()
+        {
+            follow.add_superscript();
+        } else if self.index != 0
+        {
+            self.follow = Some((CursorFollow::Top, Box::new(Self::default())));
+        }
+    }
+
+    pub fn add_subscript(&mut self)
+    {
+        if let Some((_, follow)) = self.follow.as_mut()
+        {
+            follow.add_subscript();
+        } else if self.index != 0
+        {
+            self.follow = Some((CursorFollow::Bottom, Box::new(Self::default())));
+        }
+    }
+
+    // how many structures the cursor is inside of
+    pub fn depth(&self) -> usize
+    {
+        let mut depth = 0;
+
+        let mut current = self;
+        while let Some((_, follow)) = current.follow.as_ref()
+        {
+            depth += 1;
+            current = follow;
+        }
+
+        depth
+    }
+
+    pub fn innermost(&self) -> &Self
+    {
+        match self.follow
+        {
+            Some((_, ref follow)) => follow.innermost(),
+            None => self
+        }
+    }
+
+    pub fn innermost_mut(&mut self) -> &mut Self
+    {
+        match self.follow
+        {
+            Some((_, ref mut follow)) => follow.innermost_mut(),
+            None => self
+        }
+    }
+
+    pub fn added(&mut self)
+    {
+        if let Some((_direction, follow)) = self.follow.as_mut()
+        {
+            follow.added();
+        } else
+        {
+            self.index += 1;
+        }
+    }
+
+    // the follow chain without the innermost index
+    pub fn path(&self) -> RegionPath
+    {
+        let mut path = Vec::new();
+
+        let mut current = self;
+        while let Some((direction, follow)) = current.follow.as_ref()
+        {
+            path.push((current.index, *direction));
+            current = follow;
+        }
+
+        path
+    }
+
+    // index, part and cursor of the structure this cursor is inside of
+    pub fn inside(&self) -> Option<(usize, CursorFollow, &Self)>
+    {
+        self.follow.as_ref().map(|(direction, follow)| (self.index, *direction, &**follow))
+    }
+
+    // key that sorts cursors by where they are in the reading order
+    fn order_key(&self) -> Vec<usize>
+    {
+        let mut key = Vec::new();
+
+        let mut current = self;
+        while let Some((direction, follow)) = current.follow.as_ref()
+        {
+            // inside of a structure is between the positions before and after it
+            key.push(current.index * 2 - 1);
+
+            match *direction
+            {
+                CursorFollow::Top => key.push(0),
+                CursorFollow::Middle => key.push(1),
+                CursorFollow::Bottom => key.push(2),
+                // cells of the same matrix sort by row and then by column
+                CursorFollow::Cell{row, column} => key.extend([3, row, column])
+            }
+
+            current = follow;
+        }
+
+        key.push(current.index * 2);
+
+        key
+    }
+
+    pub fn is_before(&self, other: &Self) -> bool
+    {
+        self.order_key() < other.order_key()
+    }
+
+    pub fn from_path(path: &[(usize, CursorFollow)], index: usize) -> Self
+    {
+        path.iter().rev().fold(Self{index, follow: None}, |follow, (index, direction)|
+        {
+            Self{index: *index, follow: Some((*direction, Box::new(follow)))}
+        })
+    }
+}
+
+// a position in the document a cursor could be at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorLocation
+{
+    pub line: usize,
+    // for every structure its inside of from the outside in, the index after that structure and its part
+    pub path: Vec<(usize, CursorFollow)>,
+    // gap between values in the innermost region
+    pub index: usize
+}
+
+#[derive(Clone, Hash)]
+struct Cursor
+{
+    line: usize,
+    value: ValueCursor
+}
+
+// lines as a share code along with what share codes dont keep
+struct SavedLines
+{
+    code: String,
+    // color and whether its folded for every line
+    display: Vec<(Option<[u8; 3]>, bool)>
+}
+
+// what undoing or redoing goes back to
+struct Snapshot
+{
+    lines: SavedLines,
+    cursor: Cursor
+}
+
+struct History
+{
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    // hash and copy of the lines after the last change, none until the first input after a reset
+    current: Option<(u64, SavedLines)>,
+    // the last change was typing so typing more goes into the same step
+    typing: bool,
+    // most steps that can be undone, older ones get dropped
+    depth: usize
+}
+
+impl History
+{
+    fn new(depth: usize) -> Self
+    {
+        Self{undo: Vec::new(), redo: Vec::new(), current: None, typing: false, depth}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key
+{
+    Backspace,
+    Delete,
+    Return,
+    Tab,
+    Left,
+    Right,
+    Up,
+    Down,
+    F5,
+    Escape,
+    Character(char)
+}
+
+// what typing / does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FractionEntryMode
+{
+    // the token before the cursor becomes the numerator
+    #[default]
+    WrapPrevious,
+    // inserts an empty fraction with the cursor in the numerator
+    EmptyTopFirst
+}
+
+// what delete does right before a fraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeleteStructure
+{
+    // moves into the numerator and removes its first value, empty fractions get removed whole
+    #[default]
+    StepInto,
+    Whole
+}
+
+// what gets put at the vertical middle of the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum VerticalCentering
+{
+    #[default]
+    Bounds,
+    // the math axis, halfway between the first and last lines axes with more than one line
+    Axis
+}
+
+// (1), (2), ... flush right on each line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EquationNumbering
+{
+    #[default]
+    Off,
+    All,
+    // blank lines dont get a number and dont advance the counter
+    NonEmpty
+}
+
+impl EquationNumbering
+{
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            Self::Off => Self::All,
+            Self::All => Self::NonEmpty,
+            Self::NonEmpty => Self::Off
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers
+{
+    pub ctrl: bool,
+    pub shift: bool
+}
+
+// one input to the editor, a sequence of them can be recorded and replayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordedInput
+{
+    Key(Key, Modifiers),
+    Text(String)
+}
+
+fn byte_index(text: &str, chars: usize) -> usize
+{
+    text.char_indices().nth(chars).map(|(index, _)| index).unwrap_or(text.len())
+}
+
+// hebrew, arabic, syriac, thaana, nko and their presentation forms
+fn is_rtl(c: char) -> bool
+{
+    matches!(c, '\u{0590}'..='\u{08ff}' | '\u{fb1d}'..='\u{fdff}' | '\u{fe70}'..='\u{feff}')
+}
+
+fn is_ltr(c: char) -> bool
+{
+    c.is_alphanumeric() && !is_rtl(c)
+}
+
+// a run goes right to left if the first letter in it does
+fn is_rtl_text(text: &str) -> bool
+{
+    text.chars().find(|c| c.is_alphabetic()).map(is_rtl).unwrap_or(false)
+}
+
+// char indices of a right to left run in the order they get drawn,
+// left to right words and numbers inside it keep their own order
+fn rtl_order(text: &str) -> Vec<usize>
+{
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut segments: Vec<Range<usize>> = Vec::new();
+    chars.iter().enumerate().for_each(|(index, c)|
+    {
+        if let Some(last) = segments.last_mut()
+        {
+            if is_ltr(*c) && is_ltr(chars[index - 1])
+            {
+                last.end = index + 1;
+                return;
+            }
+        }
+
+        segments.push(index..index + 1);
+    });
+
+    segments.into_iter().rev().flatten().collect()
+}
+
+// the run as it appears on screen
+fn visual_text(text: &str) -> Cow<'_, str>
+{
+    if is_rtl_text(text)
+    {
+        let chars: Vec<char> = text.chars().collect();
+
+        rtl_order(text).into_iter().map(|index| chars[index]).collect::<String>().into()
+    } else
+    {
+        text.into()
+    }
+}
+
+pub struct ProgramState
+{
+    cursor: Cursor,
+    // other end of the selection, always on the same line as the cursor
+    selection: Option<ValueCursor>,
+    lines: Vec<Line>,
+    layout: LayoutConfig,
+    // decimal places of evaluated results
+    precision: usize,
+    decimal_separator: char,
+    fraction_entry: FractionEntryMode,
+    delete_structure: DeleteStructure,
+    numbering: EquationNumbering,
+    align_equals: bool,
+    vertical_centering: VerticalCentering,
+    // fixed top left corner for the content instead of centering it
+    origin: Option<(f32, f32)>,
+    // char offset into the text run before the cursor while editing it
+    text_entry: Option<usize>,
+    custom: HashMap<String, CustomConstructor>,
+    presentation: bool,
+    // selected cell of the open symbol picker
+    symbol_picker: Option<usize>,
+    // structure being dragged as its line, region and index in that region
+    drag: Option<(usize, RegionPath, usize)>,
+    // how many structures can be inside each other, everything recurses so this keeps the stack safe
+    max_depth: usize,
+    // inputs since recording started, none when not recording
+    recording: Option<Vec<RecordedInput>>,
+    // the last finished recording
+    recorded: Vec<RecordedInput>,
+    // crosshairs at the window and content centers to see where centering put things
+    debug_centers: bool,
+    // line, region and index of the last inserted structure and how many seconds ago it was inserted
+    inserted: Option<(usize, RegionPath, usize, f32)>,
+    history: History
+}
+
+impl Default for ProgramState
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl ProgramState
+{
+    pub fn new() -> Self
+    {
+        Self{
+            cursor: Cursor{line: 0, value: ValueCursor::default()},
+            selection: None,
+            lines: vec![Line::default()],
+            layout: LayoutConfig::default(),
+            precision: 6,
+            decimal_separator: '.',
+            fraction_entry: FractionEntryMode::default(),
+            delete_structure: DeleteStructure::default(),
+            numbering: EquationNumbering::default(),
+            align_equals: false,
+            vertical_centering: VerticalCentering::default(),
+            origin: None,
+            text_entry: None,
+            custom: HashMap::new(),
+            presentation: false,
+            symbol_picker: None,
+            drag: None,
+            max_depth: 64,
+            recording: None,
+            recorded: Vec::new(),
+            debug_centers: false,
+            inserted: None,
+            history: History::new(100)
+        }
+    }
+
+    pub fn set_fraction_entry(&mut self, mode: FractionEntryMode)
+    {
+        self.fraction_entry = mode;
+    }
+
+    pub fn set_delete_structure(&mut self, mode: DeleteStructure)
+    {
+        self.delete_structure = mode;
+    }
+
+    pub fn set_decimal_separator(&mut self, separator: char)
+    {
+        self.decimal_separator = separator;
+    }
+
+    // digits after the decimal point in evaluated results
+    pub fn set_precision(&mut self, precision: usize)
+    {
+        self.precision = precision;
+    }
+
+    pub fn set_vertical_centering(&mut self, mode: VerticalCentering)
+    {
+        self.vertical_centering = mode;
+    }
+
+    // in font sizes, none never wraps
+    pub fn set_wrap_width(&mut self, width: Option<f32>)
+    {
+        self.layout.wrap_width = width;
+    }
+
+    // scale is the size of scripts and the shifts how far theyre moved from their base, all in font sizes
+    pub fn set_scripts(&mut self, scale: f32, superscript_shift: f32, subscript_shift: f32)
+    {
+        self.layout.exponent_scale = scale;
+        self.layout.superscript_shift = superscript_shift;
+        self.layout.subscript_shift = subscript_shift;
+    }
+
+    // the fonts used for measuring and drawing have to be reloaded at the new size too
+    pub fn set_font_size(&mut self, size: u32)
+    {
+        self.layout.font_size = size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+    }
+
+    pub fn font_size(&self) -> u32
+    {
+        self.layout.font_size
+    }
+
+    // none goes back to centering in the window
+    pub fn set_origin(&mut self, origin: Option<(f32, f32)>)
+    {
+        self.origin = origin;
+    }
+
+    pub fn set_max_depth(&mut self, depth: usize)
+    {
+        self.max_depth = depth;
+    }
+
+    // how many changes can be undone, the oldest ones get dropped past it
+    pub fn set_undo_depth(&mut self, depth: usize)
+    {
+        self.history.depth = depth;
+        self.trim_history();
+    }
+
+    // starts the animation of the structure the cursor was just put inside of
+    fn animate_inserted(&mut self)
+    {
+        let mut path = self.cursor.value.path();
+
+        if let Some((index, _)) = path.pop()
+        {
+            self.inserted = Some((self.cursor.line, path, index - 1, 0.0));
+        }
+    }
+
+    // how far along the animation of a new structure is, none when nothings animating
+    fn insert_progress(&self) -> Option<f32>
+    {
+        let (.., age) = self.inserted.as_ref()?;
+
+        let progress = age / INSERT_ANIMATION;
+
+        (progress < 1.0).then_some(progress)
+    }
+
+    // frontends should keep redrawing while this is true
+    pub fn animating(&self) -> bool
+    {
+        self.insert_progress().is_some()
+    }
+
+    // moves animations forward by the seconds since the last tick, the core never reads the clock
+    // itself because theres no clock to read on some targets
+    pub fn tick(&mut self, seconds: f32)
+    {
+        if let Some((.., age)) = self.inserted.as_mut()
+        {
+            *age += seconds;
+        }
+    }
+
+    // whether a new structure at the cursor holding something this deep stays within the limit
+    fn fits_structure(&self, inner_depth: usize) -> bool
+    {
+        self.cursor.value.depth() + 1 + inner_depth <= self.max_depth
+    }
+
+    pub fn layout(&self) -> &LayoutConfig
+    {
+        &self.layout
+    }
+
+    // same hash means render would draw the exact same frame
+    pub fn frame_hash(&self, width: u32, height: u32) -> u64
+    {
+        let mut hasher = DefaultHasher::new();
+
+        (width, height).hash(&mut hasher);
+        self.lines.hash(&mut hasher);
+        self.cursor.hash(&mut hasher);
+        self.selection.hash(&mut hasher);
+        self.numbering.hash(&mut hasher);
+        self.align_equals.hash(&mut hasher);
+        self.vertical_centering.hash(&mut hasher);
+        self.origin.map(|(x, y)| (x.to_bits(), y.to_bits())).hash(&mut hasher);
+        self.layout.hash(&mut hasher);
+        self.precision.hash(&mut hasher);
+        self.text_entry.hash(&mut hasher);
+        self.presentation.hash(&mut hasher);
+        self.symbol_picker.hash(&mut hasher);
+        self.decimal_separator.hash(&mut hasher);
+        self.debug_centers.hash(&mut hasher);
+        self.inserted.as_ref().map(|(line, path, index, _)| (line, path, index)).hash(&mut hasher);
+        self.insert_progress().map(f32::to_bits).hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    pub fn toggle_symbol_picker(&mut self)
+    {
+        self.symbol_picker = if self.symbol_picker.is_some() { None } else { Some(0) };
+    }
+
+    // arrows move around the grid and return inserts, false for keys the picker doesnt use
+    fn symbol_picker_key(&mut self, key: Key, selected: usize) -> bool
+    {
+        let last = SYMBOLS.len() - 1;
+
+        let selected = match key
+        {
+            Key::Left => selected.saturating_sub(1),
+            Key::Right => (selected + 1).min(last),
+            Key::Up => selected.checked_sub(SYMBOL_COLUMNS).unwrap_or(selected),
+            Key::Down => if selected + SYMBOL_COLUMNS <= last { selected + SYMBOL_COLUMNS } else { selected },
+            Key::Return =>
+            {
+                self.symbol_picker = None;
+
+                if !self.presentation
+                {
+                    self.selection = None;
+                    self.add_normal(SYMBOLS[selected].to_owned());
+                }
+
+                return true;
+            },
+            Key::Escape =>
+            {
+                self.symbol_picker = None;
+
+                return true;
+            },
+            _ => return false
+        };
+
+        self.symbol_picker = Some(selected);
+
+        true
+    }
+
+    // grid of symbols centered at the bottom of the window
+    fn render_symbol_picker(
+        &self,
+        measurer: &impl TextMeasure,
+        selected: usize,
+        width: u32,
+        height: u32
+    ) -> RenderResult<'static>
+    {
+        let cell = self.layout.font_size as f32 * 1.5;
+        let margin = self.layout.margin();
+
+        let rows = SYMBOLS.len().div_ceil(SYMBOL_COLUMNS);
+        let (panel_width, panel_height) = (cell * SYMBOL_COLUMNS as f32, cell * rows as f32);
+
+        let x = ((width as f32 - panel_width) / 2.0).max(0.0);
+        let y = (height as f32 - panel_height - margin).max(0.0);
+
+        let panel = self.measure(measurer, RenderValue::Panel{x, y, width: panel_width, height: panel_height});
+
+        let cell_position = |index: usize|
+        {
+            (x + (index % SYMBOL_COLUMNS) as f32 * cell, y + (index / SYMBOL_COLUMNS) as f32 * cell)
+        };
+
+        let symbols = SYMBOLS.iter().enumerate().fold(panel, |acc, (index, symbol)|
+        {
+            let (cell_x, cell_y) = cell_position(index);
+
+            let mut value = self.measure(measurer, RenderValue::Text{x: 0.0, y: 0.0, text: (*symbol).into()});
+            value.shift(
+                cell_x + (cell - value.rect.width) / 2.0,
+                cell_y + (cell - value.rect.height) / 2.0
+            );
+
+            acc.combine(value)
+        });
+
+        let (cell_x, cell_y) = cell_position(selected);
+        let thickness = self.layout.line_thickness as f32;
+
+        let corners = [
+            (cell_x, cell_y),
+            (cell_x + cell, cell_y),
+            (cell_x + cell, cell_y + cell),
+            (cell_x, cell_y + cell)
+        ];
+
+        (0..corners.len()).fold(symbols, |acc, index|
+        {
+            let ((x1, y1), (x2, y2)) = (corners[index], corners[(index + 1) % corners.len()]);
+
+            acc.combine(self.measure(measurer, RenderValue::Line{x1, y1, x2, y2, thickness}))
+        })
+    }
+
+    pub fn toggle_presentation(&mut self)
+    {
+        self.presentation = !self.presentation;
+    }
+
+    pub fn toggle_debug_centers(&mut self)
+    {
+        self.debug_centers = !self.debug_centers;
+    }
+
+    pub fn cycle_fraction_style(&mut self)
+    {
+        self.layout.fraction_style = self.layout.fraction_style.next();
+    }
+
+    pub fn cycle_numbering(&mut self)
+    {
+        self.numbering = self.numbering.next();
+    }
+
+    pub fn toggle_align_equals(&mut self)
+    {
+        self.align_equals = !self.align_equals;
+    }
+
+    // returns true if anything changed
+    pub fn text_input(&mut self, text: String) -> bool
+    {
+        if self.presentation
+        {
+            return false;
+        }
+
+        if let Some(recording) = self.recording.as_mut()
+        {
+            recording.push(RecordedInput::Text(text.clone()));
+        }
+
+        // letters and digits typed one after another get undone together
+        let typing = text.chars().all(|c| c.is_alphanumeric() || c == '.');
+        self.tracked(typing, |this| this.add_text(text));
+
+        true
+    }
+
+    fn lines_hash(&self) -> u64
+    {
+        let mut hasher = DefaultHasher::new();
+        self.lines.hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    fn save_lines(&self) -> SavedLines
+    {
+        SavedLines{
+            code: compact::encode(&self.lines),
+            display: self.lines.iter().map(|line| (line.color, line.folded)).collect()
+        }
+    }
+
+    // runs a change and adds a step to undo if it changed the lines, typing continues the last step if it was typing too
+    fn tracked<T>(&mut self, typing: bool, change: impl FnOnce(&mut Self) -> T) -> T
+    {
+        if self.history.current.is_none()
+        {
+            self.history.current = Some((self.lines_hash(), self.save_lines()));
+        }
+
+        let cursor = self.cursor.clone();
+        let result = change(self);
+
+        let hash = self.lines_hash();
+        if self.history.current.as_ref().is_some_and(|(current, _)| *current == hash)
+        {
+            self.history.typing &= typing;
+
+            return result;
+        }
+
+        let (_, previous) = self.history.current.replace((hash, self.save_lines())).unwrap();
+
+        if !(typing && self.history.typing)
+        {
+            self.history.undo.push(Snapshot{lines: previous, cursor});
+            self.trim_history();
+        }
+
+        self.history.redo.clear();
+        self.history.typing = typing;
+
+        result
+    }
+
+    fn trim_history(&mut self)
+    {
+        let excess = self.history.undo.len().saturating_sub(self.history.depth);
+        self.history.undo.drain(..excess);
+    }
+
+    // puts the lines and cursor back to how they were in the snapshot and returns the current ones
+    fn restore(&mut self, snapshot: Snapshot) -> Option<Snapshot>
+    {
+        let Ok(mut lines) = compact::decode(&snapshot.lines.code, &self.custom, self.max_depth)
+        else
+        {
+            return None;
+        };
+
+        lines.iter_mut().zip(&snapshot.lines.display).for_each(|(line, (color, folded))|
+        {
+            line.color = *color;
+            line.folded = *folded;
+        });
+
+        let current = Snapshot{lines: self.save_lines(), cursor: self.cursor.clone()};
+
+        self.lines = lines;
+        self.cursor = snapshot.cursor;
+        self.selection = None;
+        self.text_entry = None;
+        self.drag = None;
+        self.revalidate_cursor();
+
+        self.history.current = Some((self.lines_hash(), snapshot.lines));
+        self.history.typing = false;
+
+        Some(current)
+    }
+
+    pub fn undo(&mut self) -> bool
+    {
+        if self.presentation
+        {
+            return false;
+        }
+
+        let Some(snapshot) = self.history.undo.pop() else { return false };
+
+        let Some(current) = self.restore(snapshot) else { return false };
+        self.history.redo.push(current);
+
+        true
+    }
+
+    pub fn redo(&mut self) -> bool
+    {
+        if self.presentation
+        {
+            return false;
+        }
+
+        let Some(snapshot) = self.history.redo.pop() else { return false };
+
+        let Some(current) = self.restore(snapshot) else { return false };
+        self.history.undo.push(current);
+
+        true
+    }
+
+    fn text_run(&self) -> Option<&String>
+    {
+        self.lines[self.cursor.line].values.traverse(&self.cursor.value, |this, cursor|
+        {
+            match this.0.get(cursor.index.checked_sub(1)?)
+            {
+                Some(InputValue::TextRun{content}) => Some(content),
+                _ => None
+            }
+        })
+    }
+
+    fn text_run_mut(&mut self) -> Option<&mut String>
+    {
+        self.lines[self.cursor.line].values.traverse_mut(&self.cursor.value, |this, cursor|
+        {
+            match this.0.get_mut(cursor.index.checked_sub(1)?)
+            {
+                Some(InputValue::TextRun{content}) => Some(content),
+                _ => None
+            }
+        })
+    }
+
+    // moves the cursor into a text run next to it, returns false if there isnt one
+    fn enter_text_run(&mut self, forward: bool) -> bool
+    {
+        let next = self.lines[self.cursor.line].values.traverse(&self.cursor.value, |this, cursor|
+        {
+            let index = if forward { Some(cursor.index) } else { cursor.index.checked_sub(1) };
+
+            index.and_then(|index| this.0.get(index)).and_then(|value|
+            {
+                if let InputValue::TextRun{content} = value
+                {
+                    Some(content.chars().count())
+                } else
+                {
+                    None
+                }
+            })
+        });
+
+        let Some(length) = next else { return false };
+
+        self.selection = None;
+
+        if forward
+        {
+            self.cursor.value.added();
+            self.text_entry = Some(0);
+        } else
+        {
+            self.text_entry = Some(length);
+        }
+
+        true
+    }
+
+    // keys while editing a text run, returns false if the key should be handled normally
+    fn text_run_key(&mut self, key: Key, offset: usize) -> bool
+    {
+        let Some(length) = self.text_run().map(|content| content.chars().count())
+        else
+        {
+            self.text_entry = None;
+            return false;
+        };
+
+        match key
+        {
+            Key::Backspace if offset > 0 =>
+            {
+                let content = self.text_run_mut().unwrap();
+                content.remove(byte_index(content, offset - 1));
+
+                self.text_entry = Some(offset - 1);
+            },
+            Key::Delete if offset < length =>
+            {
+                let content = self.text_run_mut().unwrap();
+                content.remove(byte_index(content, offset));
+            },
+            Key::Delete => (),
+            Key::Left if offset > 0 =>
+            {
+                self.text_entry = Some(offset - 1);
+            },
+            Key::Right if offset < length =>
+            {
+                self.text_entry = Some(offset + 1);
+            },
+            Key::Backspace if length == 0 =>
+            {
+                self.text_entry = None;
+                self.remove_single();
+            },
+            Key::Backspace | Key::Left =>
+            {
+                self.text_entry = None;
+                self.cursor.value.innermost_mut().index -= 1;
+            },
+            Key::Right =>
+            {
+                self.text_entry = None;
+            },
+            _ =>
+            {
+                self.text_entry = None;
+
+                return false;
+            }
+        }
+
+        true
+    }
+
+    // starts recording inputs or stops and keeps what was recorded
+    pub fn toggle_recording(&mut self)
+    {
+        if let Some(recording) = self.recording.take()
+        {
+            self.recorded = recording;
+        } else
+        {
+            self.recording = Some(Vec::new());
+        }
+    }
+
+    pub fn is_recording(&self) -> bool
+    {
+        self.recording.is_some()
+    }
+
+    // applies the last recording again from where the cursor is now, its already inside the step of the key that started it
+    // so the whole replay gets undone at once, returns true if anything changed
+    fn replay(&mut self) -> bool
+    {
+        let inputs = self.recorded.clone();
+
+        // replaying while recording records what got replayed instead of every key on its own
+        let recording = self.recording.take();
+
+        let changed = inputs.iter().cloned().fold(false, |changed, input|
+        {
+            let this_changed = match input
+            {
+                RecordedInput::Key(key, modifiers) => self.handle_key(key, modifiers),
+                // same as text_input, theres nothing to type into in presentation mode
+                RecordedInput::Text(_) if self.presentation => false,
+                RecordedInput::Text(text) =>
+                {
+                    self.add_text(text);
+
+                    true
+                }
+            };
+
+            changed || this_changed
+        });
+
+        self.recording = recording.map(|mut recording|
+        {
+            recording.extend(inputs);
+
+            recording
+        });
+
+        changed
+    }
+
+    pub fn key_pressed(&mut self, key: Key, modifiers: Modifiers) -> bool
+    {
+        let Modifiers{ctrl, shift} = modifiers;
+
+        // undo and redo arent edits so a recording couldnt replay them, they dont work while recording
+        match key
+        {
+            Key::Character('z') if ctrl && !shift => !self.is_recording() && self.undo(),
+            Key::Character('y') if ctrl && !shift => !self.is_recording() && self.redo(),
+            _ => self.tracked(false, |this| this.handle_key(key, modifiers))
+        }
+    }
+
+    fn handle_key(&mut self, key: Key, modifiers: Modifiers) -> bool
+    {
+        let Modifiers{ctrl, shift} = modifiers;
+
+        // the recording keys themselves never end up in a recording
+        match key
+        {
+            Key::Character('q') if ctrl && shift =>
+            {
+                self.toggle_recording();
+
+                return true;
+            },
+            Key::Character('y') if ctrl && shift => return self.replay(),
+            _ => ()
+        }
+
+        if let Some(recording) = self.recording.as_mut()
+        {
+            recording.push(RecordedInput::Key(key, modifiers));
+        }
+
+        if let Some(selected) = self.symbol_picker
+        {
+            if self.symbol_picker_key(key, selected)
+            {
+                return true;
+            }
+        }
+
+        if key == Key::Escape && self.drag.is_some()
+        {
+            self.drag = None;
+
+            return true;
+        }
+
+        if let Some(offset) = self.text_entry
+        {
+            if self.text_run_key(key, offset)
+            {
+                return true;
+            }
+        }
+
+        let editing = !self.presentation;
+
+        match key
+        {
+            Key::F5 =>
+            {
+                self.toggle_presentation();
+            },
+            Key::Character('b') if ctrl && shift =>
+            {
+                self.cycle_fraction_style();
+            },
+            Key::Character('c') if ctrl && shift =>
+            {
+                self.toggle_debug_centers();
+            },
+            Key::Character('a') if ctrl && shift =>
+            {
+                self.toggle_align_equals();
+            },
+            Key::Character('e') if ctrl && shift =>
+            {
+                self.cycle_numbering();
+            },
+            Key::Character('n') if ctrl && shift && editing =>
+            {
+                self.clear();
+            },
+            Key::Character('f') if ctrl && shift && editing =>
+            {
+                self.simplify_fraction();
+            },
+            Key::Character('/') if ctrl && editing =>
+            {
+                self.collapse_fraction();
+            },
+            Key::Escape if editing =>
+            {
+                if !self.cancel_command()
+                {
+                    return false;
+                }
+            },
+            Key::Character('i') if ctrl && shift && editing =>
+            {
+                self.toggle_symbol_picker();
+            },
+            Key::Character('t') if ctrl && shift && editing =>
+            {
+                self.label_from_text();
+            },
+            Key::Character('u') if ctrl && shift && editing =>
+            {
+                self.swap_fraction();
+            },
+            Key::Character('r') if ctrl && shift && editing =>
+            {
+                self.insert_answer();
+            },
+            Key::Character('r') if ctrl && editing =>
+            {
+                self.add_sqrt();
+            },
+            Key::Character('j') if ctrl && shift && editing =>
+            {
+                self.add_nth_root();
+            },
+            Key::Character('=') if ctrl && shift && editing =>
+            {
+                self.add_integral();
+            },
+            Key::Character('=') if ctrl && editing =>
+            {
+                self.add_sum();
+            },
+            Key::Character('m') if ctrl && shift && editing =>
+            {
+                self.normalize_whitespace();
+            },
+            Key::Character('m') if ctrl && editing =>
+            {
+                self.add_matrix();
+            },
+            Key::Character('x') if ctrl && shift && editing =>
+            {
+                self.cancel();
+            },
+            Key::Character('9') if ctrl && editing =>
+            {
+                self.wrap_region();
+            },
+            Key::Character('\\') if ctrl && shift && editing =>
+            {
+                self.toggle_table_rules();
+            },
+            Key::Character('\\') if ctrl && editing =>
+            {
+                self.cycle_column_align();
+            },
+            Key::Character('l') if ctrl && editing =>
+            {
+                self.cycle_line_color();
+            },
+            Key::Character('k') if ctrl =>
+            {
+                self.toggle_fold();
+            },
+            Key::Character('j') if ctrl && editing =>
+            {
+                self.join_lines();
+            },
+            Key::Backspace if ctrl && editing =>
+            {
+                return self.remove_word_left();
+            },
+            Key::Backspace if editing =>
+            {
+                self.remove_single();
+            },
+            Key::Delete if editing =>
+            {
+                self.remove_next_single();
+            },
+            Key::Return if ctrl && editing =>
+            {
+                return self.split_line();
+            },
+            Key::Return if editing && self.add_matrix_row() => (),
+            Key::Return if editing =>
+            {
+                self.new_line();
+            },
+            Key::Tab if editing && self.step_cell(!shift) => (),
+            Key::Tab =>
+            {
+                self.move_region(!shift);
+            },
+            Key::Left =>
+            {
+                self.move_left(shift);
+            },
+            Key::Right =>
+            {
+                self.move_right(shift);
+            },
+            Key::Up =>
+            {
+                self.move_up();
+            },
+            Key::Down =>
+            {
+                self.move_down();
+            },
+            _ => return false
+        }
+
+        true
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.selection = None;
+        self.cursor = Cursor{line: 0, value: ValueCursor::default()};
+        self.lines = vec![Line::default()];
+    }
+
+    pub fn add_text(&mut self, text: String)
+    {
+        // typing over a selection replaces it
+        self.remove_selection();
+
+        if let Some(offset) = self.text_entry
+        {
+            let content = self.text_run().unwrap();
+            let open = content.matches('{').count() > content.matches('}').count();
+
+            // the matching closing brace ends the run like in latex
+            if text == "}" && !open
+            {
+                self.text_entry = None;
+            } else
+            {
+                let content = self.text_run_mut().unwrap();
+                content.insert_str(byte_index(content, offset), &text);
+
+                self.text_entry = Some(offset + text.chars().count());
+            }
+
+            return;
+        }
+
+        match text.as_ref()
+        {
+            "/" => self.add_fraction(),
+            "^" => self.add_superscript(),
+            "_" => self.add_subscript(),
+            x if BracketKind::from_open(x).is_some() => self.add_group(BracketKind::from_open(x).unwrap()),
+            x if BracketKind::from_close(x).is_some_and(|kind| self.close_group(kind)) => (),
+            "|" if self.close_abs() => (),
+            "|" => self.add_abs(),
+            " " if self.run_command() => (),
+            _ => self.add_normal(text)
+        }
+    }
+
+    // adds a custom value that gets inserted with \tag like the builtin commands
+    pub fn register_custom(&mut self, tag: &str, constructor: CustomConstructor)
+    {
+        self.custom.insert(tag.to_owned(), constructor);
+    }
+
+    fn command_value(&self, name: &str) -> Option<InputValue>
+    {
+        if let Some(constructor) = self.custom.get(name)
+        {
+            return Some(InputValue::Extension(constructor()));
+        }
+
+        let value = match name
+        {
+            "ldots" => InputValue::Dots(DotsKind::Low),
+            "cdots" => InputValue::Dots(DotsKind::Center),
+            "vdots" => InputValue::Dots(DotsKind::Vertical),
+            "ddots" => InputValue::Dots(DotsKind::Diagonal),
+            "degree" => InputValue::Unit(UnitKind::Degree),
+            "prime" => InputValue::Unit(UnitKind::Prime),
+            "dprime" => InputValue::Unit(UnitKind::DoublePrime),
+            "text" => InputValue::TextRun{content: String::new()},
+            "cancel" => InputValue::Cancel{content: InputValues::default()},
+            "!" => InputValue::Space(SpaceKind::NegativeThin),
+            "ans" => InputValue::Answer{line_ref: 1},
+            "cdot" => InputValue::Value("·".to_owned()),
+            "table" => InputValue::Table{rows: vec![vec![InputValues::default()]], columns: vec![ColumnAlign::default()], rules: false},
+            _ => return None
+        };
+
+        Some(value)
+    }
+
+    // what the \command being typed would insert, once its the only one that fits
+    fn command_preview(&self) -> Option<InputValue>
+    {
+        if self.text_entry.is_some()
+        {
+            return None;
+        }
+
+        let (_, name) = self.lines[self.cursor.line].values.command_before(&self.cursor.value)?;
+
+        if name.is_empty()
+        {
+            return None;
+        }
+
+        let names = COMMANDS.iter().copied().chain(self.custom.keys().map(String::as_str));
+
+        // a full name wins over longer ones starting with it
+        if names.clone().any(|command| command == name)
+        {
+            return self.command_value(&name);
+        }
+
+        let mut matching = names.filter(|command| command.starts_with(&name));
+
+        let command = matching.next()?;
+
+        if matching.next().is_some()
+        {
+            return None;
+        }
+
+        self.command_value(command)
+    }
+
+    // removes the unfinished \command before the cursor
+    pub fn cancel_command(&mut self) -> bool
+    {
+        let line = &mut self.lines[self.cursor.line].values;
+
+        let Some((start, _)) = line.command_before(&self.cursor.value)
+        else
+        {
+            return false;
+        };
+
+        let mut start_cursor = self.cursor.value.clone();
+        start_cursor.innermost_mut().index = start;
+
+        self.selection = None;
+        self.cursor.value = line.remove_range(&start_cursor, &self.cursor.value);
+
+        true
+    }
+
+    fn run_command(&mut self) -> bool
+    {
+        let Some((start, name)) = self.lines[self.cursor.line].values.command_before(&self.cursor.value)
+        else
+        {
+            return false;
+        };
+
+        // only splits brackets that are already there so it isnt a value of its own
+        if name == "middle"
+        {
+            self.selection = None;
+
+            return self.lines[self.cursor.line].values.divide_group(&mut self.cursor.value, start);
+        }
+
+        if let Some(value) = self.command_value(&name)
+        {
+            if value.is_structure() && !self.fits_structure(0)
+            {
+                return false;
+            }
+
+            let is_text = matches!(value, InputValue::TextRun{..});
+            let enters = matches!(value, InputValue::Cancel{..} | InputValue::Table{..});
+            let entry = value.entry_part(true);
+
+            self.lines[self.cursor.line].values.replace_before(&mut self.cursor.value, start, value);
+
+            if is_text
+            {
+                self.text_entry = Some(0);
+            }
+
+            if enters
+            {
+                self.cursor.value.innermost_mut().follow = Some((entry, Box::default()));
+                self.animate_inserted();
+            }
+
+            true
+        } else
+        {
+            false
+        }
+    }
+
+    pub fn new_line(&mut self)
+    {
+        self.split_line();
+    }
+
+    // everything after the cursor goes onto a new line below with the cursor at its start,
+    // structures cant be cut in half so inside one nothing happens and this returns false
+    pub fn split_line(&mut self) -> bool
+    {
+        if self.cursor.value.follow.is_some()
+        {
+            return false;
+        }
+
+        self.selection = None;
+        self.text_entry = None;
+
+        let rest = self.lines[self.cursor.line].values.0.split_off(self.cursor.value.index);
+
+        self.cursor.line += 1;
+        self.cursor.value = ValueCursor::default();
+
+        // the label stays with the first half
+        self.lines.insert(self.cursor.line, InputValues(rest).into());
+
+        true
+    }
+
+    // collapses the lines into one placeholder, the cursors line cant be hidden so it stays out
+    pub fn fold_lines(&mut self, lines: Range<usize>)
+    {
+        let end = lines.end.min(self.lines.len());
+
+        (lines.start..end).filter(|line| *line != self.cursor.line).for_each(|line|
+        {
+            self.lines[line].folded = true;
+        });
+    }
+
+    // shows the whole collapsed block the line is in again
+    pub fn unfold_at(&mut self, line: usize)
+    {
+        if !self.lines.get(line).is_some_and(|line| line.folded)
+        {
+            return;
+        }
+
+        let start = self.lines[..line].iter().rposition(|line| !line.folded).map(|x| x + 1).unwrap_or(0);
+
+        self.lines[start..].iter_mut().take_while(|line| line.folded).for_each(|line| line.folded = false);
+    }
+
+    // expands the block right after the cursors line,
+    // otherwise collapses the lines after it up to the next empty line
+    pub fn toggle_fold(&mut self)
+    {
+        let next = self.cursor.line + 1;
+
+        if self.lines.get(next).is_some_and(|line| line.folded)
+        {
+            self.unfold_at(next);
+            return;
+        }
+
+        let end = self.lines[next.min(self.lines.len())..].iter()
+            .position(|line| line.values.0.is_empty())
+            .map(|length| next + length)
+            .unwrap_or(self.lines.len());
+
+        self.fold_lines(next..end);
+    }
+
+    // the closest line in the direction that isnt hidden
+    fn shown_line(&self, forward: bool) -> Option<usize>
+    {
+        let line = self.cursor.line;
+
+        if forward
+        {
+            (line + 1..self.lines.len()).find(|index| !self.lines[*index].folded)
+        } else
+        {
+            (0..line).rev().find(|index| !self.lines[*index].folded)
+        }
+    }
+
+    pub fn join_lines(&mut self)
+    {
+        self.selection = None;
+
+        if self.cursor.line + 1 >= self.lines.len()
+        {
+            return;
+        }
+
+        // joining pulls the next line in so it has to be visible
+        self.unfold_at(self.cursor.line + 1);
+
+        let next = self.lines.remove(self.cursor.line + 1);
+        let current = &mut self.lines[self.cursor.line];
+
+        self.cursor.value = ValueCursor{index: current.values.0.len(), follow: None};
+
+        current.append(next);
+    }
+
+    fn add_normal(&mut self, text: String)
+    {
+        self.lines[self.cursor.line].values.add_text(&self.cursor.value, text);
+        self.cursor.value.added();
+    }
+
+    fn add_fraction(&mut self)
+    {
+        let inner_depth = match self.fraction_entry
+        {
+            FractionEntryMode::WrapPrevious => self.lines[self.cursor.line].values.depth_before(&self.cursor.value),
+            FractionEntryMode::EmptyTopFirst => 0
+        };
+
+        if !self.fits_structure(inner_depth)
+        {
+            return;
+        }
+
+        self.lines[self.cursor.line].values.add_fraction(&self.cursor.value, self.fraction_entry);
+        self.cursor.value.add_fraction(self.fraction_entry);
+
+        self.animate_inserted();
+    }
+
+    // wraps the value before the cursor as a base, does nothing when theres no value before it
+    fn add_superscript(&mut self)
+    {
+        if self.cursor.value.innermost().index == 0
+        {
+            return;
+        }
+
+        let inner_depth = self.lines[self.cursor.line].values.depth_before(&self.cursor.value);
+        if !self.fits_structure(inner_depth)
+        {
+            return;
+        }
+
+        self.lines[self.cursor.line].values.add_superscript(&self.cursor.value);
+        self.cursor.value.add_superscript();
+
+        self.animate_inserted();
+    }
+
+    // lowers a subscript onto the value before the cursor, does nothing when theres no value before it
+    fn add_subscript(&mut self)
+    {
+        if self.cursor.value.innermost().index == 0
+        {
+            return;
+        }
+
+        let inner_depth = self.lines[self.cursor.line].values.depth_before(&self.cursor.value);
+        if !self.fits_structure(inner_depth)
+        {
+            return;
+        }
+
+        self.lines[self.cursor.line].values.add_subscript(&self.cursor.value);
+        self.cursor.value.add_subscript();
+
+        self.animate_inserted();
+    }
+
+    // opens an empty group with the cursor inside, typing more just goes into it
+    fn add_group(&mut self, kind: BracketKind)
+    {
+        if !self.fits_structure(0)
+        {
+            self.add_normal(kind.as_char(BracketSide::Left).to_string());
+            return;
+        }
+
+        let value = InputValue::Paren{inner: InputValues::default(), kind};
+        self.lines[self.cursor.line].values.add_value(&self.cursor.value, value);
+        self.cursor.value.added();
+
+        self.cursor.value.innermost_mut().follow = Some((CursorFollow::Top, Box::default()));
+        self.animate_inserted();
+    }
+
+    // steps out of the group the cursor is in when the closing bracket matches it
+    fn close_group(&mut self, kind: BracketKind) -> bool
+    {
+        let closes = |value: &InputValue|
+        {
+            matches!(value, InputValue::Paren{kind: group, ..} | InputValue::Divided{kind: group, ..} if *group == kind)
+        };
+
+        self.lines[self.cursor.line].values.close_group(&mut self.cursor.value, &closes)
+    }
+
+    // opens empty absolute value bars with the cursor inside
+    fn add_abs(&mut self)
+    {
+        if !self.fits_structure(0)
+        {
+            self.add_normal("|".to_owned());
+            return;
+        }
+
+        let value = InputValue::Abs{inner: InputValues::default()};
+        self.lines[self.cursor.line].values.add_value(&self.cursor.value, value);
+        self.cursor.value.added();
+
+        self.cursor.value.innermost_mut().follow = Some((CursorFollow::Top, Box::default()));
+        self.animate_inserted();
+    }
+
+    // a bar typed inside bars that already have something in them closes them instead of nesting
+    fn close_abs(&mut self) -> bool
+    {
+        let closes = |value: &InputValue| matches!(value, InputValue::Abs{inner} if !inner.0.is_empty());
+
+        self.lines[self.cursor.line].values.close_group(&mut self.cursor.value, &closes)
+    }
+
+    // puts an empty square root at the cursor and moves into it
+    pub fn add_sqrt(&mut self)
+    {
+        if !self.fits_structure(0)
+        {
+            return;
+        }
+
+        self.remove_selection();
+        self.text_entry = None;
+
+        self.lines[self.cursor.line].values.add_value(&self.cursor.value, InputValue::Sqrt{inner: InputValues::default()});
+        self.cursor.value.added();
+
+        self.cursor.value.innermost_mut().follow = Some((CursorFollow::Top, Box::default()));
+        self.animate_inserted();
+    }
+
+    // puts an empty sum at the cursor and moves into its lower bound
+    pub fn add_sum(&mut self)
+    {
+        self.add_bounded(InputValue::Sum{lower: InputValues::default(), upper: InputValues::default(), body: InputValues::default()});
+    }
+
+    // puts an empty integral at the cursor and moves into its lower bound
+    pub fn add_integral(&mut self)
+    {
+        let value = InputValue::Integral{
+            lower: InputValues::default(),
+            upper: InputValues::default(),
+            body: InputValues::default()
+        };
+
+        self.add_bounded(value);
+    }
+
+    // puts a matrix with a single empty cell at the cursor and moves into it
+    pub fn add_matrix(&mut self)
+    {
+        if !self.fits_structure(0)
+        {
+            return;
+        }
+
+        self.remove_selection();
+        self.text_entry = None;
+
+        let value = InputValue::Matrix{rows: vec![vec![InputValues::default()]], bracket: BracketKind::Round};
+        self.lines[self.cursor.line].values.add_value(&self.cursor.value, value);
+        self.cursor.value.added();
+
+        self.cursor.value.innermost_mut().follow = Some((CursorFollow::Cell{row: 0, column: 0}, Box::default()));
+        self.animate_inserted();
+    }
+
+    fn step_cell(&mut self, forward: bool) -> bool
+    {
+        self.selection = None;
+
+        self.lines[self.cursor.line].values.step_cell(&mut self.cursor.value, forward)
+    }
+
+    // left, center and right in turn for the column the cursor is in inside a table
+    pub fn cycle_column_align(&mut self)
+    {
+        self.selection = None;
+
+        self.lines[self.cursor.line].values.edit_table(&self.cursor.value, &|columns, _, column|
+        {
+            columns[column] = columns[column].next();
+        });
+    }
+
+    pub fn toggle_table_rules(&mut self)
+    {
+        self.selection = None;
+
+        self.lines[self.cursor.line].values.edit_table(&self.cursor.value, &|_, rules, _| *rules = !*rules);
+    }
+
+    fn add_matrix_row(&mut self) -> bool
+    {
+        self.selection = None;
+
+        self.lines[self.cursor.line].values.add_matrix_row(&mut self.cursor.value)
+    }
+
+    fn add_bounded(&mut self, value: InputValue)
+    {
+        if !self.fits_structure(0)
+        {
+            return;
+        }
+
+        self.remove_selection();
+        self.text_entry = None;
+
+        self.lines[self.cursor.line].values.add_value(&self.cursor.value, value);
+        self.cursor.value.added();
+
+        self.cursor.value.innermost_mut().follow = Some((CursorFollow::Bottom, Box::default()));
+        self.animate_inserted();
+    }
+
+    // puts an empty root at the cursor and moves into its degree
+    pub fn add_nth_root(&mut self)
+    {
+        if !self.fits_structure(0)
+        {
+            return;
+        }
+
+        self.remove_selection();
+        self.text_entry = None;
+
+        let value = InputValue::NthRoot{degree: InputValues::default(), inner: InputValues::default()};
+        self.lines[self.cursor.line].values.add_value(&self.cursor.value, value);
+        self.cursor.value.added();
+
+        self.cursor.value.innermost_mut().follow = Some((CursorFollow::Top, Box::default()));
+        self.animate_inserted();
+    }
+
+    // refers to the previous lines result
+    pub fn insert_answer(&mut self)
+    {
+        self.remove_selection();
+        self.text_entry = None;
+
+        self.lines[self.cursor.line].values.add_value(&self.cursor.value, InputValue::Answer{line_ref: 1});
+        self.cursor.value.added();
+    }
+
+    // the selection in a form that pastes back as the same structures, none without a selection
+    pub fn copy_selection(&self) -> Option<String>
+    {
+        let selection = self.selection.as_ref()?;
+        let values = self.lines[self.cursor.line].values.selected(selection, &self.cursor.value)?;
+
+        Some(format!("{CLIPBOARD_PREFIX}{}", compact::encode_values(values)))
+    }
+
+    // types the text in as if it was typed, lines after the first go on new lines,
+    // copied values go in with their structure instead
+    pub fn paste(&mut self, text: &str)
+    {
+        self.tracked(false, |this| this.paste_text(text));
+    }
+
+    fn paste_text(&mut self, text: &str)
+    {
+        if self.presentation
+        {
+            return;
+        }
+
+        self.remove_selection();
+
+        if let Some(code) = text.strip_prefix(CLIPBOARD_PREFIX)
+        {
+            let max_depth = self.max_depth.saturating_sub(self.cursor.value.depth());
+
+            // anything that wouldnt fit at the cursor doesnt get pasted at all
+            if let Ok(values) = compact::decode_values(code, &self.custom, max_depth)
+            {
+                self.text_entry = None;
+                self.lines[self.cursor.line].values.insert_values(&mut self.cursor.value, values);
+            }
+
+            return;
+        }
+
+        if latex::looks_like_latex(text)
+        {
+            let max_depth = self.max_depth.saturating_sub(self.cursor.value.depth());
+
+            // latex that doesnt parse or fit gets typed in as text instead
+            if let Ok(values) = InputValues::from_latex(text.trim())
+            {
+                if values.depth() <= max_depth
+                {
+                    self.text_entry = None;
+                    self.lines[self.cursor.line].values.insert_values(&mut self.cursor.value, values);
+
+                    return;
+                }
+            }
+        }
+
+        text.lines().enumerate().for_each(|(index, line)|
+        {
+            if index > 0
+            {
+                self.text_entry = None;
+                self.new_line();
+            }
+
+            line.chars().for_each(|c| self.add_text(c.to_string()));
+        });
+    }
+
+    // exactly two lines become the numerator and denominator of a new fraction, anything else is a normal paste
+    pub fn paste_fraction(&mut self, text: &str)
+    {
+        self.tracked(false, |this| this.paste_fraction_text(text));
+    }
+
+    fn paste_fraction_text(&mut self, text: &str)
+    {
+        let lines: Vec<&str> = text.lines().collect();
+
+        let [top, bottom] = lines[..]
+        else
+        {
+            self.paste(text);
+            return;
+        };
+
+        if self.presentation || !self.fits_structure(0)
+        {
+            return;
+        }
+
+        self.remove_selection();
+        self.text_entry = None;
+
+        let start = self.cursor.value.clone();
+
+        self.lines[self.cursor.line].values.add_fraction(&start, FractionEntryMode::EmptyTopFirst);
+
+        let part_cursor = |direction|
+        {
+            let mut cursor = start.clone();
+
+            let innermost = cursor.innermost_mut();
+            innermost.index += 1;
+            innermost.follow = Some((direction, Box::default()));
+
+            cursor
+        };
+
+        [(CursorFollow::Top, top), (CursorFollow::Bottom, bottom)].into_iter().for_each(|(direction, text)|
+        {
+            self.cursor.value = part_cursor(direction);
+            text.chars().for_each(|c| self.add_text(c.to_string()));
+
+            self.text_entry = None;
+        });
+
+        self.cursor.value = part_cursor(CursorFollow::Top);
+        self.animate_inserted();
+
+        self.cursor.value = start;
+        self.cursor.value.innermost_mut().index += 1;
+    }
+
+    pub fn wrap_region(&mut self)
+    {
+        self.selection = None;
+
+        let inner_depth = self.lines[self.cursor.line].values.region_depth(&self.cursor.value);
+        if !self.fits_structure(inner_depth)
+        {
+            return;
+        }
+
+        self.lines[self.cursor.line].values.wrap_region(&mut self.cursor.value);
+    }
+
+    // cleans up spacing in the selection, or everywhere without one
+    pub fn normalize_whitespace(&mut self)
+    {
+        let selection = self.selection.take();
+
+        // a selection only narrows it down when both ends are in the same region
+        let selected = selection.filter(|selection| selection.path() == self.cursor.value.path()).map(|selection|
+        {
+            self.lines[self.cursor.line].values.traverse(&selection, |_, cursor| cursor.index)
+        });
+
+        if let Some(anchor) = selected
+        {
+            self.lines[self.cursor.line].values.traverse_mut(&mut self.cursor.value, |this, cursor|
+            {
+                let range = anchor.min(cursor.index)..anchor.max(cursor.index);
+
+                this.normalize_whitespace(range, Some(cursor));
+            });
+        } else
+        {
+            let current = self.cursor.line;
+
+            self.lines.iter_mut().enumerate().for_each(|(line, Line{values, ..})|
+            {
+                let cursor = (line == current).then_some(&mut self.cursor.value);
+
+                values.normalize_whitespace(0..values.0.len(), cursor);
+            });
+        }
+
+        self.text_entry = None;
+        self.revalidate_cursor();
+    }
+
+    // strikes through the selection or the value before the cursor
+    pub fn cancel(&mut self)
+    {
+        let line = &self.lines[self.cursor.line].values;
+
+        // a selection is always inside one region so that regions depth bounds it
+        let inner_depth = if self.selection.is_some()
+        {
+            line.region_depth(&self.cursor.value)
+        } else
+        {
+            line.depth_before(&self.cursor.value)
+        };
+
+        if !self.fits_structure(inner_depth)
+        {
+            return;
+        }
+
+        let line = &mut self.lines[self.cursor.line].values;
+
+        if let Some(selection) = self.selection.take()
+        {
+            if let Some(cursor) = line.cancel_range(&selection, &self.cursor.value)
+            {
+                self.cursor.value = cursor;
+            }
+        } else
+        {
+            line.cancel_previous(&self.cursor.value);
+        }
+    }
+
+    pub fn simplify_fraction(&mut self)
+    {
+        self.selection = None;
+
+        if self.lines[self.cursor.line].values.simplify_fraction(&self.cursor.value)
+        {
+            self.revalidate_cursor();
+        }
+    }
+
+    pub fn label(&self) -> Option<&str>
+    {
+        self.lines[self.cursor.line].label.as_deref()
+    }
+
+    // none goes back to the default color
+    pub fn set_line_color(&mut self, color: Option<[u8; 3]>)
+    {
+        self.lines[self.cursor.line].color = color;
+    }
+
+    pub fn cycle_line_color(&mut self)
+    {
+        let line = &mut self.lines[self.cursor.line];
+
+        let next = match line.color.and_then(|color| LINE_COLORS.iter().position(|x| *x == color))
+        {
+            Some(index) => LINE_COLORS.get(index + 1).copied(),
+            None if line.color.is_none() => LINE_COLORS.first().copied(),
+            // a color set from outside thats not in the list starts over
+            None => None
+        };
+
+        line.color = next;
+    }
+
+    // an empty label is the same as none
+    pub fn set_label(&mut self, label: Option<String>)
+    {
+        self.lines[self.cursor.line].label = label.filter(|label| !label.trim().is_empty());
+    }
+
+    // the text run before the cursor becomes the lines label, without one the label gets removed
+    pub fn label_from_text(&mut self)
+    {
+        self.selection = None;
+        self.text_entry = None;
+
+        let label = self.lines[self.cursor.line].values.traverse_mut(&mut self.cursor.value, |this, cursor|
+        {
+            let index = cursor.index.checked_sub(1)?;
+
+            let InputValue::TextRun{content} = &this.0[index] else { return None };
+            let label = content.trim().to_owned();
+
+            this.0.remove(index);
+            cursor.index = index;
+
+            Some(label)
+        });
+
+        self.set_label(label);
+    }
+
+    pub fn to_compact(&self) -> String
+    {
+        compact::encode(&self.lines)
+    }
+
+    // replaces the document keeping the cursor where it still fits, no lines means one empty line
+    pub fn set_document(&mut self, lines: Vec<Line>)
+    {
+        // the old document can be brought back with undo
+        self.tracked(false, |this| this.replace_document(lines));
+    }
+
+    fn replace_document(&mut self, mut lines: Vec<Line>)
+    {
+        if lines.is_empty()
+        {
+            lines.push(Line::default());
+        }
+
+        self.lines = lines;
+        self.selection = None;
+        self.text_entry = None;
+
+        self.revalidate_cursor();
+    }
+
+    // replaces the document, custom values need to be registered before this
+    pub fn load_compact(&mut self, text: &str) -> Result<(), CompactError>
+    {
+        // a shared document cant replace the one being presented
+        if self.presentation
+        {
+            return Ok(());
+        }
+
+        let lines = compact::decode(text, &self.custom, self.max_depth)?;
+
+        self.tracked(false, |this|
+        {
+            this.cursor = Cursor{line: 0, value: ValueCursor::default()};
+            this.replace_document(lines);
+        });
+
+        Ok(())
+    }
+
+    pub fn from_compact(text: &str) -> Result<Self, CompactError>
+    {
+        let mut this = Self::new();
+
+        // nothing to undo back to in a new state
+        let lines = compact::decode(text, &this.custom, this.max_depth)?;
+        this.replace_document(lines);
+
+        Ok(this)
+    }
+
+    // every line read out for screen readers, one per line
+    pub fn to_speech(&self) -> String
+    {
+        self.lines.iter().map(|line| line.values.to_speech()).collect::<Vec<_>>().join("\n")
+    }
+
+    // readable tree of every line for bug reports
+    pub fn dump(&self) -> String
+    {
+        let mut output = String::new();
+
+        self.lines.iter().enumerate().for_each(|(index, line)|
+        {
+            output.push_str(&format!("line {}\n", index + 1));
+            line.values.dump(1, &mut output);
+        });
+
+        output
+    }
+
+    pub fn swap_fraction(&mut self)
+    {
+        self.selection = None;
+        self.lines[self.cursor.line].values.swap_fraction(&mut self.cursor.value);
+    }
+
+    pub fn collapse_fraction(&mut self)
+    {
+        self.selection = None;
+        self.lines[self.cursor.line].values.collapse_fraction(&mut self.cursor.value);
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<(usize, ValidationError)>>
+    {
+        let errors: Vec<_> = self.lines.iter().enumerate().flat_map(|(line, values)|
+        {
+            values.values.validate().err().into_iter().flatten().map(move |error| (line, error))
+        }).collect();
+
+        if errors.is_empty()
+        {
+            Ok(())
+        } else
+        {
+            Err(errors)
+        }
+    }
+
+    // exports every line that evaluates to a number followed by its result,
+    // lines that arent valid get left out and their errors come back with the rest
+    pub fn export_results(&self, format: ExportFormat) -> (String, Vec<(usize, ValidationError)>)
+    {
+        let errors = self.validate().err().unwrap_or_default();
+
+        let mut skipped: Vec<usize> = errors.iter().map(|(line, _)| *line).collect();
+        skipped.dedup();
+
+        let text = export::export_with_results(&self.lines, format, self.precision, self.decimal_separator, &skipped);
+
+        (text, errors)
+    }
+
+    pub fn revalidate_cursor(&mut self)
+    {
+        self.cursor.line = self.cursor.line.min(self.lines.len() - 1);
+        self.lines[self.cursor.line].values.revalidate_cursor(&mut self.cursor.value);
+    }
+
+    fn remove_selection(&mut self) -> bool
+    {
+        let Some(selection) = self.selection.take()
+        else
+        {
+            return false;
+        };
+
+        let cursor = &self.cursor.value;
+        if !selection.is_before(cursor) && !cursor.is_before(&selection)
+        {
+            return false;
+        }
+
+        self.cursor.value = self.lines[self.cursor.line].values.remove_range(&selection, cursor);
+        self.revalidate_cursor();
+
+        true
+    }
+
+    // removes a whole number or name at once, false if theres none before the cursor in its region
+    pub fn remove_word_left(&mut self) -> bool
+    {
+        if self.remove_selection()
+        {
+            return true;
+        }
+
+        self.lines[self.cursor.line].values.remove_word_left(&mut self.cursor.value)
+    }
+
+    pub fn remove_single(&mut self)
+    {
+        if self.remove_selection()
+        {
+            return;
+        }
+
+        if self.cursor.value.follow.is_none() && self.cursor.value.index == 0
+        {
+            // the first line has nothing before it to merge into
+            let Some(above) = self.cursor.line.checked_sub(1) else { return };
+
+            self.unfold_at(above);
+
+            let previous = self.lines.remove(self.cursor.line);
+
+            self.cursor.line -= 1;
+
+            // the join point is at the top level even if either side has a structure at the edge
+            let index = self.lines[self.cursor.line].values.0.len();
+            self.cursor.value = ValueCursor{index, follow: None};
+
+            self.lines[self.cursor.line].append(previous);
+        } else
+        {
+            self.lines[self.cursor.line].values.remove_single(&mut self.cursor.value);
+        }
+    }
+
+    pub fn remove_next_single(&mut self)
+    {
+        if self.remove_selection()
+        {
+            return;
+        }
+
+        let line_length = self.lines[self.cursor.line].values.0.len();
+        if self.cursor.value.follow.is_none() && self.cursor.value.index == line_length
+        {
+            if self.lines.len() - 1 > self.cursor.line
+            {
+                self.unfold_at(self.cursor.line + 1);
+
+                let line = self.lines.remove(self.cursor.line + 1);
+
+                self.lines[self.cursor.line].append(line);
+            }
+        } else
+        {
+            self.lines[self.cursor.line].values.remove_next(&mut self.cursor.value, self.delete_structure);
+        }
+    }
+
+    fn update_selection(&mut self, select: bool)
+    {
+        if !select
+        {
+            self.selection = None;
+        } else if self.selection.is_none()
+        {
+            self.selection = Some(self.cursor.value.clone());
+        }
+    }
+
+    pub fn move_left(&mut self, select: bool)
+    {
+        if !select && self.enter_text_run(false)
+        {
+            return;
+        }
+
+        self.update_selection(select);
+        self.lines[self.cursor.line].values.move_left(&mut self.cursor.value);
+    }
+
+    pub fn move_right(&mut self, select: bool)
+    {
+        if !select && self.enter_text_run(true)
+        {
+            return;
+        }
+
+        self.update_selection(select);
+        self.lines[self.cursor.line].values.move_right(&mut self.cursor.value);
+    }
+
+    fn truncate_index(&mut self)
+    {
+        self.cursor.value.index = self.cursor.value.index.min(self.lines[self.cursor.line].values.0.len());
+    }
+
+    pub fn move_up(&mut self)
+    {
+        self.selection = None;
+
+        if !self.lines[self.cursor.line].values.move_up(&mut self.cursor.value)
+        {
+            if let (None, Some(line)) = (&self.cursor.value.follow, self.shown_line(false))
+            {
+                self.cursor.line = line;
+                self.truncate_index();
+            }
+        }
+    }
+
+    pub fn move_down(&mut self)
+    {
+        self.selection = None;
+
+        if !self.lines[self.cursor.line].values.move_down(&mut self.cursor.value)
+        {
+            if let (None, Some(line)) = (&self.cursor.value.follow, self.shown_line(true))
+            {
+                self.cursor.line = line;
+                self.truncate_index();
+            }
+        }
+    }
+
+    // moves the cursor to the end of the next (or previous) editable region, wrapping around
+    pub fn move_region(&mut self, forward: bool)
+    {
+        self.selection = None;
+
+        let shown = self.lines.iter().enumerate().filter(|(_, line)| !line.folded);
+
+        let regions: Vec<(usize, RegionPath)> = shown.flat_map(|(line, values)|
+        {
+            let mut regions = Vec::new();
+            values.values.regions(&mut Vec::new(), &mut regions);
+
+            regions.into_iter().map(move |path| (line, path))
+        }).collect();
+
+        let current = (self.cursor.line, self.cursor.value.path());
+        let position = regions.iter().position(|region| *region == current).unwrap_or(0);
+
+        let next = if forward
+        {
+            (position + 1) % regions.len()
+        } else
+        {
+            position.checked_sub(1).unwrap_or(regions.len() - 1)
+        };
+
+        let (line, path) = &regions[next];
+
+        let length = self.lines[*line].values.traverse(&ValueCursor::from_path(path, 0), |this, _|
+        {
+            this.0.len()
+        });
+
+        let value = ValueCursor::from_path(path, length);
+
+        self.cursor = Cursor{line: *line, value};
+    }
+
+    // where in the document a point in the window is, the innermost region drawn around the point wins
+    // and inside of it the closest gap between values, without one around it its just the closest gap
+    pub fn locate(
+        &mut self,
+        measurer: &impl TextMeasure,
+        width: u32,
+        height: u32,
+        x: f32,
+        y: f32
+    ) -> Option<CursorLocation>
+    {
+        // the cursor isnt drawn in presentation mode and theres nothing to edit anyway
+        if self.presentation
+        {
+            return None;
+        }
+
+        let cursor = mem::replace(&mut self.cursor, Cursor{line: 0, value: ValueCursor::default()});
+        let text_entry = self.text_entry.take();
+        let selection = self.selection.take();
+
+        // line and path of a region with the top left of the cursor at each of its positions
+        type DrawnRegion = (usize, RegionPath, Vec<(usize, f32, f32)>);
+
+        let mut regions: Vec<DrawnRegion> = Vec::new();
+
+        for line in 0..self.lines.len()
+        {
+            let mut paths = Vec::new();
+            self.lines[line].values.regions(&mut Vec::new(), &mut paths);
+
+            for path in paths
+            {
+                let length = self.lines[line].values.traverse(&ValueCursor::from_path(&path, 0), |this, _|
+                {
+                    this.0.len()
+                });
+
+                let positions = (0..=length).filter_map(|index|
+                {
+                    self.cursor = Cursor{line, value: ValueCursor::from_path(&path, index)};
+
+                    let mut position = None;
+                    self.render(measurer, width, height, |_| (), |render|
+                    {
+                        if let RenderValue::Cursor{x, y, ..} = render
+                        {
+                            position = Some((index, *x, *y));
+                        }
+                    });
+
+                    position
+                }).collect::<Vec<_>>();
+
+                if !positions.is_empty()
+                {
+                    regions.push((line, path, positions));
+                }
+            }
+        }
+
+        self.cursor = cursor;
+        self.text_entry = text_entry;
+        self.selection = selection;
+
+        let cursor_height = self.layout.font_size as f32;
+        let cursor_width = self.layout.cursor_width as f32;
+
+        let bounds = |positions: &[(usize, f32, f32)]|
+        {
+            let (left, top, right, bottom) = positions.iter().fold(
+                (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+                |(left, top, right, bottom), (_, x, y)|
+                {
+                    (left.min(*x), top.min(*y), right.max(*x), bottom.max(y + cursor_height))
+                });
+
+            // an empty region is only its placeholder
+            let extra = if positions.len() == 1 { self.layout.placeholder_width() } else { cursor_width };
+
+            RenderRect{x: left, y: top, width: right + extra - left, height: bottom - top}
+        };
+
+        let contains = |rect: &RenderRect| (rect.x..=rect.x + rect.width).contains(&x)
+            && (rect.y..=rect.y + rect.height).contains(&y);
+
+        let area = |positions: &[(usize, f32, f32)]|
+        {
+            let rect = bounds(positions);
+
+            rect.width * rect.height
+        };
+
+        // deeper ones are drawn inside the ones theyre in, for overlapping ones the smaller one is more specific
+        let inside = regions.iter().filter(|(_, _, positions)| contains(&bounds(positions))).max_by(|a, b|
+        {
+            a.1.len().cmp(&b.1.len()).then(area(&b.2).total_cmp(&area(&a.2)))
+        });
+
+        let distance = |(cursor_x, cursor_y): (f32, f32)|
+        {
+            (cursor_x - x).powi(2) + (cursor_y + cursor_height / 2.0 - y).powi(2)
+        };
+
+        let closest = |(line, path, positions): &DrawnRegion|
+        {
+            positions.iter().map(|(index, cursor_x, cursor_y)|
+            {
+                (distance((*cursor_x, *cursor_y)), CursorLocation{line: *line, path: path.clone(), index: *index})
+            }).min_by(|a, b| a.0.total_cmp(&b.0))
+        };
+
+        let closest = match inside
+        {
+            Some(region) => closest(region),
+            None => regions.iter().filter_map(closest).min_by(|a, b| a.0.total_cmp(&b.0))
+        };
+
+        closest.map(|(_, location)| location)
+    }
+
+    fn position_at(
+        &mut self,
+        measurer: &impl TextMeasure,
+        width: u32,
+        height: u32,
+        x: f32,
+        y: f32
+    ) -> Option<(usize, RegionPath, usize)>
+    {
+        self.locate(measurer, width, height, x, y).map(|CursorLocation{line, path, index}| (line, path, index))
+    }
+
+    // picks up the innermost structure under the point, false if there isnt one
+    // puts the cursor in the gap between values closest to the point, false if theres nowhere to put it
+    pub fn cursor_at(&mut self, measurer: &impl TextMeasure, width: u32, height: u32, x: f32, y: f32) -> bool
+    {
+        let Some((line, path, index)) = self.position_at(measurer, width, height, x, y)
+        else
+        {
+            return false;
+        };
+
+        self.selection = None;
+        self.text_entry = None;
+        self.cursor = Cursor{line, value: ValueCursor::from_path(&path, index)};
+
+        // typing somewhere else is a separate step to undo
+        self.history.typing = false;
+
+        true
+    }
+
+    pub fn drag_start(&mut self, measurer: &impl TextMeasure, width: u32, height: u32, x: f32, y: f32) -> bool
+    {
+        if self.presentation
+        {
+            return false;
+        }
+
+        let Some((line, mut path, _)) = self.position_at(measurer, width, height, x, y)
+        else
+        {
+            return false;
+        };
+
+        let Some((index, _)) = path.pop() else { return false };
+
+        self.drag = Some((line, path, index - 1));
+
+        true
+    }
+
+    // moves the dragged structure to the cursor position closest to the point
+    pub fn drag_end(&mut self, measurer: &impl TextMeasure, width: u32, height: u32, x: f32, y: f32) -> bool
+    {
+        self.tracked(false, |this| this.drop_dragged(measurer, width, height, x, y))
+    }
+
+    fn drop_dragged(&mut self, measurer: &impl TextMeasure, width: u32, height: u32, x: f32, y: f32) -> bool
+    {
+        let Some((source_line, source_path, source_index)) = self.drag.take()
+        else
+        {
+            return false;
+        };
+
+        // a drag started before switching to presentation mode gets dropped without editing anything
+        if self.presentation
+        {
+            return false;
+        }
+
+        let Some((line, mut path, mut index)) = self.position_at(measurer, width, height, x, y)
+        else
+        {
+            return false;
+        };
+
+        // taking the structure out shifts everything after it in its region back by one
+        if line == source_line && path.starts_with(&source_path)
+        {
+            let depth = source_path.len();
+
+            if let Some((structure, _)) = path.get_mut(depth)
+            {
+                // cant drop a structure inside of itself
+                if *structure == source_index + 1
+                {
+                    return false;
+                }
+
+                if *structure > source_index + 1
+                {
+                    *structure -= 1;
+                }
+            } else if index > source_index
+            {
+                index -= 1;
+            }
+        }
+
+        let value = self.lines[source_line].values.traverse_mut(&ValueCursor::from_path(&source_path, 0), |this, _|
+        {
+            this.0.remove(source_index)
+        });
+
+        self.lines[line].values.traverse_mut(&ValueCursor::from_path(&path, 0), |this, _|
+        {
+            this.0.insert(index, value);
+        });
+
+        self.selection = None;
+        self.text_entry = None;
+        self.cursor = Cursor{line, value: ValueCursor::from_path(&path, index + 1)};
+
+        true
+    }
+
+    fn measure<'b>(&self, measurer: &impl TextMeasure, render: RenderValue<'b>) -> RenderResult<'b>
+    {
+        let char_size = |c: char|
+        {
+            measurer.text_size(c.encode_utf8(&mut [0; 4]))
+        };
+
+        let rect = match render
+        {
+            RenderValue::Text{x, y, text: ref value} =>
+            {
+                let (width, height) = measurer.text_size(value);
+
+                RenderRect{x, y, width: width as f32, height: height as f32}
+            },
+            RenderValue::Line{x1, y1, x2, y2, thickness} =>
+            {
+                let half = thickness / 2.0;
+
+                let (x, y) = (x1.min(x2), y1.min(y2));
+                let (width, height) = ((x2 - x1).abs(), (y2 - y1).abs());
+
+                // a horizontal line is exactly as long as it says, only its thickness spreads out
+                if height == 0.0
+                {
+                    RenderRect{x, y: y - half, width, height: thickness}
+                } else
+                {
+                    RenderRect{x: x - half, y: y - half, width: width + thickness, height: height + thickness}
+                }
+            },
+            RenderValue::Dots{x, y, kind} =>
+            {
+                let (width, height) = if measurer.has_glyph(kind.as_char())
+                {
+                    char_size(kind.as_char())
+                } else
+                {
+                    (self.layout.font_size, measurer.height())
+                };
+
+                RenderRect{x, y, width: width as f32, height: height as f32}
+            },
+            RenderValue::Unit{x, y, kind} =>
+            {
+                let (width, height) = if measurer.has_glyph(kind.as_char())
+                {
+                    char_size(kind.as_char())
+                } else if let Some(fallback) = kind.fallback()
+                {
+                    measurer.text_size(fallback)
+                } else
+                {
+                    (UnitKind::circle_radius(self.layout.font_size) * 4, measurer.height())
+                };
+
+                RenderRect{x, y, width: width as f32, height: height as f32}
+            },
+            RenderValue::Placeholder{x, y, width, height}
+            | RenderValue::Panel{x, y, width, height} =>
+            {
+                RenderRect{x, y, width, height}
+            },
+            RenderValue::Radical{x, y, width, height, thickness} =>
+            {
+                let half = thickness / 2.0;
+
+                RenderRect{x, y: y - half, width, height: height + half}
+            },
+            RenderValue::Bracket{x, y, height, ..} =>
+            {
+                RenderRect{x, y, width: self.layout.bracket_width(), height}
+            },
+            RenderValue::Cursor{x, y, ..} =>
+            {
+                RenderRect{x, y, width: 0.0, height: 0.0}
+            },
+            RenderValue::Ghost(ref value)
+            | RenderValue::Colored{ref value, ..} => self.measure(measurer, (**value).clone()).rect,
+            RenderValue::Scaled{scale, ref value} =>
+            {
+                let rect = self.measure(measurer, (**value).clone()).rect;
+
+                RenderRect{width: rect.width * scale, height: rect.height * scale, ..rect}
+            }
+        };
+
+        RenderResult::new(rect, render)
+    }
+
+    pub fn render(
+        &self,
+        measurer: &impl TextMeasure,
+        width: u32,
+        height: u32,
+        mut highlight: impl FnMut(RenderRect),
+        mut renderer: impl FnMut(&RenderValue)
+    ) -> RenderRect
+    {
+        let cursor_rect = |render: &RenderResult|
+        {
+            render.render.iter().find_map(|value|
+            {
+                if let RenderValue::Cursor{x, y, ..} = value
+                {
+                    Some(RenderRect{
+                        x: *x,
+                        y: *y,
+                        width: self.layout.cursor_width as f32,
+                        height: self.layout.font_size as f32
+                    })
+                } else
+                {
+                    None
+                }
+            })
+        };
+
+        let mut selection_rect = None;
+        // rect of the cursors line and the cursors x in it
+        let mut current_line = None;
+        // none for lines that arent shown
+        let mut line_axes: Vec<Option<f32>> = Vec::with_capacity(self.lines.len());
+
+        let start = RenderRect::empty();
+        // lines with an = get pushed right so their first = lines up
+        let equals_offsets: Vec<_> = self.lines.iter().map(|line|
+        {
+            self.align_equals.then(||
+            {
+                line.values.equals_offset(&self.layout, &|render| self.measure(measurer, render))
+            }).flatten()
+        }).collect();
+
+        let equals_column = equals_offsets.iter().flatten().copied().fold(0.0, f32::max);
+
+        let mut render = self.lines.iter().zip(equals_offsets).enumerate()
+            .fold(RenderResult::empty(start), |acc, (index, (line, equals_offset))|
+            {
+                let is_current = self.cursor.line == index;
+                let cursor = (!self.presentation && is_current).then_some(&self.cursor.value);
+
+                let x = equals_offset.map(|offset| equals_column - offset).unwrap_or(0.0);
+
+                let spacing = if index == 0 { 0.0 } else { self.layout.line_spacing() };
+
+                let y = acc.rect.y + acc.rect.height + spacing;
+
+                if line.folded
+                {
+                    line_axes.push(None);
+
+                    // a collapsed block shows up once at its first line saying how much is in it
+                    if index.checked_sub(1).is_some_and(|previous| self.lines[previous].folded)
+                    {
+                        return acc;
+                    }
+
+                    let hidden = self.lines[index..].iter().take_while(|line| line.folded).count();
+
+                    let indicator = if measurer.has_glyph('▸') { '▸' } else { '>' };
+                    let lines = if hidden == 1 { "line" } else { "lines" };
+
+                    let text = format!("{indicator} {hidden} {lines}");
+                    let placeholder = self.measure(measurer, RenderValue::Text{x, y, text: text.into()});
+
+                    let render = placeholder.render.into_iter().map(|value| RenderValue::Ghost(Box::new(value))).collect();
+
+                    return acc.combine(RenderResult{render, ..placeholder});
+                }
+                let mut rendered = line.values.render_wrapped(&self.layout, cursor, x, y, &|render|
+                {
+                    self.measure(measurer, render)
+                });
+
+                // the cursor gets placed after the text run, move it back to the offset
+                if let (true, Some(offset)) = (is_current, self.text_entry)
+                {
+                    let content = self.text_run().unwrap();
+
+                    let text_width = |text: String|
+                    {
+                        self.measure(measurer, RenderValue::Text{x: 0.0, y: 0.0, text: text.into()}).rect.width
+                    };
+
+                    let width = if is_rtl_text(content)
+                    {
+                        // the cursor goes on the leading edge of the char before it, left for rtl chars
+                        let chars: Vec<char> = content.chars().collect();
+                        let order = rtl_order(content);
+
+                        let before = offset.checked_sub(1).map(|index|
+                        {
+                            let position = order.iter().position(|x| *x == index).unwrap();
+                            let end = if is_ltr(chars[index]) { position + 1 } else { position };
+
+                            order[..end].iter().map(|index| chars[*index]).collect()
+                        }).unwrap_or_else(|| visual_text(content).into_owned());
+
+                        text_width(content.to_owned()) - text_width(before)
+                    } else
+                    {
+                        text_width(content.chars().skip(offset).collect())
+                    };
+
+                    rendered.render.iter_mut().for_each(|value|
+                    {
+                        if let RenderValue::Cursor{x, ..} = value
+                        {
+                            *x -= width;
+                        }
+                    });
+                }
+
+                if is_current
+                {
+                    let depth_color = self.layout.cursor_color(self.cursor.value.depth());
+
+                    rendered.render.iter_mut().for_each(|value|
+                    {
+                        if let RenderValue::Cursor{color, ..} = value
+                        {
+                            *color = depth_color;
+                        }
+                    });
+                }
+
+                // the lines of a new structure grow out from their middles, the layout itself stays the same
+                let inserted = self.inserted.as_ref().zip(self.insert_progress())
+                    .filter(|((inserted_line, ..), _)| *inserted_line == index);
+
+                if let Some(((_, path, structure, _), progress)) = inserted
+                {
+                    let cursor_x = |index|
+                    {
+                        let cursor = ValueCursor::from_path(path, index);
+                        let render = line.values.render_wrapped(&self.layout, Some(&cursor), x, y, &|render|
+                        {
+                            self.measure(measurer, render)
+                        });
+
+                        cursor_rect(&render).map(|rect| rect.x)
+                    };
+
+                    if let (Some(start), Some(end)) = (cursor_x(*structure), cursor_x(structure + 1))
+                    {
+                        let end = end + self.layout.cursor_width as f32;
+
+                        let inside = |value: &RenderValue| -> Option<f32>
+                        {
+                            match value
+                            {
+                                RenderValue::Line{x1, y1, x2, y2, ..} if x1.min(*x2) >= start && x1.max(*x2) <= end =>
+                                {
+                                    Some((x2 - x1).hypot(y2 - y1))
+                                },
+                                _ => None
+                            }
+                        };
+
+                        // anything nested inside is shorter than the structures own lines
+                        let longest = rendered.render.iter().filter_map(inside).fold(0.0, f32::max);
+
+                        rendered.render.iter_mut().for_each(|value|
+                        {
+                            if inside(value).is_some_and(|length| length >= longest - 0.5)
+                            {
+                                if let RenderValue::Line{x1, y1, x2, y2, ..} = value
+                                {
+                                    let (middle_x, middle_y) = ((*x1 + *x2) / 2.0, (*y1 + *y2) / 2.0);
+
+                                    *x1 = middle_x + (*x1 - middle_x) * progress;
+                                    *x2 = middle_x + (*x2 - middle_x) * progress;
+                                    *y1 = middle_y + (*y1 - middle_y) * progress;
+                                    *y2 = middle_y + (*y2 - middle_y) * progress;
+                                }
+                            }
+                        });
+                    }
+                }
+
+                // faint preview of what the command being typed inserts, right after the cursor
+                let preview = is_current.then(|| self.command_preview()).flatten();
+
+                if let (Some(preview), Some(cursor)) = (preview, cursor_rect(&rendered))
+                {
+                    let x = cursor.x + cursor.width;
+                    let ghost = preview.render(&self.layout, MathStyle::DISPLAY, None, x, cursor.y, &|render|
+                    {
+                        self.measure(measurer, render)
+                    });
+
+                    let render = ghost.render.into_iter().map(|value|
+                    {
+                        RenderValue::Ghost(Box::new(value.into_owned()))
+                    }).collect();
+
+                    rendered = rendered.combine(RenderResult{rect: ghost.rect, axis: ghost.axis, render});
+                }
+
+                let diff = y - rendered.rect.y;
+
+                if let (true, Some(selection)) = (is_current, self.selection.as_ref())
+                {
+                    let anchor = line.values.render_wrapped(&self.layout, Some(selection), x, y, &|render|
+                    {
+                        self.measure(measurer, render)
+                    });
+
+                    selection_rect = cursor_rect(&rendered).zip(cursor_rect(&anchor)).map(|(a, b)|
+                    {
+                        let mut rect = a.combine(b);
+                        rect.y += diff;
+
+                        rect
+                    });
+                }
+
+                // the cursor keeps its own color
+                if let Some(color) = line.color
+                {
+                    rendered.render = rendered.render.into_iter().map(|value|
+                    {
+                        match value
+                        {
+                            RenderValue::Cursor{..} => value,
+                            value => RenderValue::Colored{color, value: Box::new(value)}
+                        }
+                    }).collect();
+                }
+
+                rendered.shift(0.0, diff);
+                line_axes.push(Some(rendered.axis));
+
+                if is_current
+                {
+                    current_line = cursor_rect(&rendered).map(|cursor| (rendered.rect, cursor.x));
+                }
+
+                acc.combine(rendered)
+            });
+
+        let start_position = (render.rect.x, render.rect.y);
+
+        let center = |size, start, other_size|
+        {
+            start + (size - other_size) / 2.0
+        };
+
+        let content_width = (width as f32 - self.reserved_width()).max(0.0);
+
+        let margin = self.layout.margin();
+
+        // where the content would be without the margin clamping
+        let mut centered = render.rect;
+
+        if let Some((x, y)) = self.origin
+        {
+            // the host does all the positioning, so the top left of the content is exactly there
+            render.shift(x - render.rect.x, y - render.rect.y);
+        } else
+        {
+            let x = center(content_width, render.rect.x, render.rect.width);
+            let shown_axes: Vec<f32> = line_axes.iter().flatten().copied().collect();
+
+            let y = match (self.vertical_centering, shown_axes.first(), shown_axes.last())
+            {
+                (VerticalCentering::Axis, Some(first), Some(last)) =>
+                {
+                    height as f32 / 2.0 - (first + last) / 2.0
+                },
+                _ => center(height as f32, render.rect.y, render.rect.height)
+            };
+
+            render.shift(x, y);
+            centered = render.rect;
+
+            // content that doesnt fit stays pinned to the top left instead of clipping
+            if render.rect.y < margin
+            {
+                render.shift(0.0, margin - render.rect.y);
+            }
+
+            if render.rect.x < margin
+            {
+                render.shift(margin - render.rect.x, 0.0);
+            }
+        }
+
+        let content_rect = render.rect;
+
+        if self.debug_centers
+        {
+            let crosshair = |highlight: &mut dyn FnMut(RenderRect), x: f32, y: f32, size: f32|
+            {
+                highlight(RenderRect{x: x - size / 2.0, y, width: size, height: 1.0});
+                highlight(RenderRect{x, y: y - size / 2.0, width: 1.0, height: size});
+            };
+
+            let center_of = |rect: RenderRect| (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+
+            // the window one spans everything, the content ones are small so they can be told apart
+            crosshair(&mut highlight, content_width / 2.0, height as f32 / 2.0, width.max(height) as f32 * 2.0);
+
+            let (x, y) = center_of(centered);
+            crosshair(&mut highlight, x, y, self.layout.font_size as f32);
+
+            // shows how far the clamping moved it
+            if centered != content_rect
+            {
+                let (x, y) = center_of(content_rect);
+                crosshair(&mut highlight, x, y, self.layout.font_size as f32 / 2.0);
+            }
+        }
+
+        let shift_y = render.rect.y - start_position.1;
+
+        if let Some(mut rect) = selection_rect
+        {
+            rect.x += render.rect.x - start_position.0;
+            rect.y += shift_y;
+
+            highlight(rect);
+        }
+
+        // a track along the bottom showing where the cursor is on a line too long for the window
+        let scrubber = current_line.filter(|(rect, _)| rect.width > content_width).map(|(rect, cursor_x)|
+        {
+            let track_width = (content_width - margin * 2.0).max(0.0);
+            let track_y = height as f32 - margin / 2.0;
+
+            let position = ((cursor_x - rect.x) / rect.width).clamp(0.0, 1.0);
+            let marker_x = margin + track_width * position;
+            let marker_height = self.layout.font_size as f32 / 4.0;
+
+            let track = self.measure(measurer, RenderValue::Line{
+                x1: margin,
+                y1: track_y,
+                x2: margin + track_width,
+                y2: track_y,
+                thickness: 1.0
+            });
+
+            let marker = self.measure(measurer, RenderValue::Line{
+                x1: marker_x,
+                y1: track_y - marker_height,
+                x2: marker_x,
+                y2: track_y + marker_height,
+                thickness: self.layout.line_thickness as f32
+            });
+
+            track.combine(marker)
+        });
+
+        let line_axes: Vec<Option<f32>> = line_axes.into_iter().map(|axis| axis.map(|axis| axis + shift_y)).collect();
+
+        // empty lines dont get anything, lines that dont evaluate get a marker
+        let results = evaluate::evaluate_lines(&self.lines, self.decimal_separator);
+
+        let gutter = results.into_iter().zip(line_axes.iter()).filter_map(|(result, axis)|
+        {
+            let axis = (*axis)?;
+
+            let text = match result
+            {
+                Ok(value) => format!("= {}", export::format_number(value, self.precision, self.decimal_separator)),
+                Err(EvalError::Empty) => return None,
+                Err(_) => "= ?".to_owned()
+            };
+
+            let x = content_width + self.layout.fraction_gap();
+            let mut value = self.measure(measurer, RenderValue::Text{x, y: 0.0, text: text.into()});
+            value.shift(0.0, axis - value.axis);
+
+            Some(value)
+        }).fold(render, |acc, value| acc.combine(value));
+
+        let mut number = 0;
+        let numbers = self.lines.iter().zip(line_axes.iter()).flat_map(|(line, axis)|
+        {
+            let numbered = match self.numbering
+            {
+                EquationNumbering::Off => false,
+                EquationNumbering::NonEmpty => !line.values.0.is_empty(),
+                EquationNumbering::All => true
+            };
+
+            // labels are only a hint while editing, they get tagged left of the number
+            let label = line.label.as_ref().filter(|_| !self.presentation).map(|label| format!("[{label}]"));
+
+            let mut right = width as f32 - self.layout.fraction_gap();
+
+            let texts = numbered.then(||
+            {
+                number += 1;
+
+                format!("({number})")
+            }).into_iter().chain(label);
+
+            // hidden lines still count so the numbers dont change when folding
+            let Some(axis) = *axis else { return Vec::new() };
+
+            texts.map(|text|
+            {
+                let mut value = self.measure(measurer, RenderValue::Text{x: 0.0, y: 0.0, text: text.into()});
+
+                let x = right - value.rect.width;
+                value.shift(x, axis - value.axis);
+
+                right = x - self.layout.fraction_gap();
+
+                value
+            }).collect::<Vec<_>>()
+        }).fold(gutter, |acc, value| acc.combine(value));
+
+        let numbers = scrubber.into_iter().fold(numbers, |acc, value| acc.combine(value));
+
+        let numbers = if let Some(selected) = self.symbol_picker
+        {
+            numbers.combine(self.render_symbol_picker(measurer, selected, width, height))
+        } else
+        {
+            numbers
+        };
+
+        // nothing is getting typed in presentation mode so the empty slots dont need marking
+        numbers.render(|value|
+        {
+            if !(self.presentation && matches!(value, RenderValue::Placeholder{..}))
+            {
+                renderer(value);
+            }
+        });
+
+        content_rect
+    }
+
+    // width on the right taken up by the results gutter and equation numbers
+    fn reserved_width(&self) -> f32
+    {
+        let numbering_width = if self.numbering == EquationNumbering::Off
+        {
+            0.0
+        } else
+        {
+            self.layout.numbering_width()
+        };
+
+        self.layout.gutter_width() + numbering_width
+    }
+
+    // smallest window size that shows everything with the margin around it
+    // where render puts the content in a window of this size
+    pub fn bounds(&self, measurer: &impl TextMeasure, width: u32, height: u32) -> RenderRect
+    {
+        self.render(measurer, width, height, |_| (), |_| ())
+    }
+
+    pub fn fit_size(&self, measurer: &impl TextMeasure) -> (u32, u32)
+    {
+        let rect = self.bounds(measurer, 0, 0);
+
+        let margin = self.layout.margin() * 2.0;
+
+        (
+            (rect.width + self.reserved_width() + margin).ceil() as u32,
+            (rect.height + margin).ceil() as u32
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    // every character as its own value like typing would make
+    pub fn line(text: &str) -> InputValues
+    {
+        InputValues(text.chars().map(|c| InputValue::Value(c.to_string())).collect())
+    }
+
+    // measures every character as 10 wide and 20 tall
+    pub struct Fixed;
+
+    impl TextMeasure for Fixed
+    {
+        fn text_size(&self, text: &str) -> (u32, u32)
+        {
+            (text.chars().count() as u32 * 10, 20)
+        }
+
+        fn has_glyph(&self, _c: char) -> bool
+        {
+            true
+        }
+
+        fn height(&self) -> u32
+        {
+            20
+        }
+    }
+
+    pub fn type_text(state: &mut ProgramState, text: &str)
+    {
+        text.chars().for_each(|c| { state.text_input(c.to_string()); });
+    }
+
+    #[test]
+    fn merge_lines_with_structures_at_the_join()
+    {
+        let no = Modifiers::default();
+        let ctrl = Modifiers{ctrl: true, shift: false};
+
+        let mut state = ProgramState::new();
+        type_text(&mut state, "1a/b");
+        state.key_pressed(Key::Right, no);
+        type_text(&mut state, "2");
+        state.key_pressed(Key::Left, no);
+        state.key_pressed(Key::Return, no);
+
+        // the second line starts with a root
+        state.key_pressed(Key::Character('r'), ctrl);
+        type_text(&mut state, "c");
+        state.key_pressed(Key::Left, no);
+        state.key_pressed(Key::Left, no);
+
+        assert_eq!(state.lines.len(), 2);
+        assert_eq!((state.cursor.line, state.cursor.value.index), (1, 0));
+        assert!(state.cursor.value.follow.is_none());
+
+        state.key_pressed(Key::Backspace, no);
+        type_text(&mut state, "x");
+
+        assert_eq!(state.lines.len(), 1);
+        assert_eq!(state.lines[0].values.to_plain(), "1(a/b)xsqrt(c)2");
+    }
+
+    #[test]
+    fn backspace_at_the_start_of_the_first_line()
+    {
+        let no = Modifiers::default();
+
+        let mut state = ProgramState::new();
+        state.paste("12\n3");
+        state.key_pressed(Key::Up, no);
+        (0..4).for_each(|_| { state.key_pressed(Key::Left, no); });
+
+        assert_eq!((state.cursor.line, state.cursor.value.index), (0, 0));
+
+        state.key_pressed(Key::Backspace, no);
+
+        assert_eq!(state.lines.len(), 2);
+        assert_eq!(state.lines[0].values.to_plain(), "12");
+    }
+
+    #[test]
+    fn ctrl_m_and_ctrl_shift_m()
+    {
+        let mut state = ProgramState::new();
+        state.text_input("1  +  2".to_owned());
+        state.key_pressed(Key::Character('m'), Modifiers{ctrl: true, shift: true});
+
+        assert_eq!(state.lines[0].values.to_plain(), "1 + 2");
+
+        state.key_pressed(Key::Character('m'), Modifiers{ctrl: true, shift: false});
+
+        assert!(matches!(state.lines[0].values.0.last(), Some(InputValue::Matrix{..})));
+    }
+
+    #[test]
+    fn insert_animation()
+    {
+        let mut state = ProgramState::new();
+        type_text(&mut state, "1/2");
+
+        let bar_width = |state: &ProgramState|
+        {
+            let mut width = 0.0;
+            state.render(&Fixed, 400, 200, |_| (), |value|
+            {
+                if let RenderValue::Line{x1, x2, y1, y2, ..} = value
+                {
+                    if y1 == y2 { width = x2 - x1; }
+                }
+            });
+
+            width
+        };
+
+        assert!(state.animating());
+        let early = bar_width(&state);
+
+        state.tick(0.05);
+        assert!(state.animating());
+
+        state.tick(0.06);
+        assert!(!state.animating());
+        assert!(early < bar_width(&state));
+    }
+
+    #[test]
+    fn collapse_fraction_from_top()
+    {
+        let mut state = ProgramState::new();
+        type_text(&mut state, "7/");
+        state.key_pressed(Key::Up, Modifiers::default());
+        state.key_pressed(Key::Backspace, Modifiers::default());
+
+        assert!(state.cursor.value.index <= state.lines[0].values.0.len());
+        state.render(&Fixed, 400, 200, |_| (), |_| ());
+    }
+
+    #[test]
+    fn collapse_fraction_from_bottom()
+    {
+        let mut values = line("1");
+        values.0.push(InputValue::Fraction{top: line("ab"), bottom: InputValues(Vec::new())});
+
+        let mut cursor = ValueCursor::from_path(&[(2, CursorFollow::Bottom)], 0);
+        values.remove_single(&mut cursor);
+
+        assert_eq!(values.to_plain(), "1ab");
+        assert_eq!(cursor.path().len(), 0);
+        assert_eq!(cursor.index, 3);
+    }
+
+    // screen position of the cursor placed at the value, somewhere in the middle of its height
+    fn cursor_position(state: &mut ProgramState, value: ValueCursor) -> (f32, f32)
+    {
+        state.cursor.value = value;
+
+        let mut position = None;
+        state.render(&Fixed, 400, 200, |_| (), |value|
+        {
+            if let RenderValue::Cursor{x, y, ..} = value
+            {
+                position = Some((*x, *y + 10.0));
+            }
+        });
+
+        position.unwrap()
+    }
+
+    #[test]
+    fn drag_in_presentation()
+    {
+        let mut state = ProgramState::new();
+        type_text(&mut state, "1/2");
+        state.key_pressed(Key::Right, Modifiers::default());
+        type_text(&mut state, "+3");
+
+        let inside = cursor_position(&mut state, ValueCursor::from_path(&[(1, CursorFollow::Top)], 1));
+        let end = cursor_position(&mut state, ValueCursor{index: 3, follow: None});
+
+        assert!(state.drag_start(&Fixed, 400, 200, inside.0, inside.1));
+
+        state.toggle_presentation();
+        assert!(!state.drag_end(&Fixed, 400, 200, end.0, end.1));
+        assert!(!state.undo());
+
+        state.toggle_presentation();
+        assert_eq!(state.lines[0].values.to_plain(), "1/2+3");
+        assert!(!state.drag_end(&Fixed, 400, 200, end.0, end.1));
+
+        assert!(state.drag_start(&Fixed, 400, 200, inside.0, inside.1));
+        assert!(state.drag_end(&Fixed, 400, 200, end.0, end.1));
+        assert_eq!(state.lines[0].values.to_plain(), "+3(1/2)");
+    }
+
+    #[test]
+    fn load_compact_undo()
+    {
+        let mut shared = ProgramState::new();
+        type_text(&mut shared, "2+3");
+
+        let code = shared.to_compact();
+        assert!(!ProgramState::from_compact(&code).unwrap().undo());
+
+        let mut state = ProgramState::new();
+        type_text(&mut state, "1");
+
+        state.toggle_presentation();
+        state.load_compact(&code).unwrap();
+        assert_eq!(state.lines[0].values.to_plain(), "1");
+
+        state.toggle_presentation();
+        state.load_compact(&code).unwrap();
+        assert_eq!(state.lines[0].values.to_plain(), "2+3");
+
+        assert!(state.undo());
+        assert_eq!(state.lines[0].values.to_plain(), "1");
+
+        assert!(state.redo());
+        assert_eq!(state.lines[0].values.to_plain(), "2+3");
+    }
+
+    // top left of every piece of text drawn
+    fn text_positions(state: &ProgramState, width: u32, height: u32) -> Vec<(String, f32, f32)>
+    {
+        let mut positions = Vec::new();
+        state.render(&Fixed, width, height, |_| (), |value|
+        {
+            if let RenderValue::Text{x, y, text} = value
+            {
+                positions.push((text.to_string(), *x, *y));
+            }
+        });
+
+        positions
+    }
+
+    #[test]
+    fn locate()
+    {
+        let mut state = ProgramState::new();
+        state.paste("11+");
+        state.paste_fraction("1234\n5");
+        state.paste("+7");
+
+        let positions = text_positions(&state, 800, 600);
+        let find = |text: &str| positions.iter().find(|(x, _, _)| x == text).map(|(_, x, y)| (*x, *y)).unwrap();
+
+        // between the 2 and 3 of the numerator
+        let (x, y) = find("1234");
+        let location = state.locate(&Fixed, 800, 600, x + 21.0, y + 10.0).unwrap();
+        assert_eq!(location, CursorLocation{line: 0, path: vec![(4, CursorFollow::Top)], index: 2});
+
+        // closer to the end of the denominator
+        let (x, y) = find("5");
+        let location = state.locate(&Fixed, 800, 600, x + 8.0, y + 10.0).unwrap();
+        assert_eq!(location, CursorLocation{line: 0, path: vec![(4, CursorFollow::Bottom)], index: 1});
+
+        let (x, y) = find("11");
+        let location = state.locate(&Fixed, 800, 600, x + 1.0, y + 10.0).unwrap();
+        assert_eq!(location, CursorLocation{line: 0, path: Vec::new(), index: 0});
+
+        // far away from everything it still lands in the line
+        let location = state.locate(&Fixed, 800, 600, 790.0, 590.0).unwrap();
+        assert!(location.path.is_empty());
+    }
+
+    #[test]
+    fn click_between_values()
+    {
+        let mut state = ProgramState::new();
+        state.paste("abc");
+        state.paste_fraction("12\n3");
+
+        let (_, x, y) = text_positions(&state, 400, 200).into_iter().find(|(text, _, _)| text == "a").unwrap();
+
+        assert!(state.cursor_at(&Fixed, 400, 200, x + 13.0, y + 10.0));
+        assert!(state.cursor.value.follow.is_none());
+        assert_eq!(state.cursor.value.index, 1);
+
+        assert!(state.cursor_at(&Fixed, 400, 200, x + 18.0, y + 10.0));
+        assert!(state.cursor.value.follow.is_none());
+        assert_eq!(state.cursor.value.index, 2);
+    }
+
+    pub fn fraction(top: &str, bottom: &str) -> InputValue
+    {
+        InputValue::Fraction{top: line(top), bottom: line(bottom)}
+    }
+
+    #[test]
+    fn remove_range_into_a_structure()
+    {
+        // from after a into the numerator after b only takes the b
+        let mut values = line("a");
+        values.0.push(fraction("bc", "d"));
+        values.0.extend(line("e").0);
+
+        let anchor = ValueCursor{index: 1, follow: None};
+        let cursor = ValueCursor::from_path(&[(2, CursorFollow::Top)], 1);
+        let start = values.remove_range(&anchor, &cursor);
+
+        assert_eq!(values.to_plain(), "a(c/d)e");
+        assert_eq!(start.index, 1);
+        assert!(start.follow.is_none());
+    }
+
+    #[test]
+    fn remove_range_over_a_structure()
+    {
+        let mut values = line("a");
+        values.0.push(fraction("bc", "d"));
+        values.0.extend(line("e").0);
+
+        let start = values.remove_range(&ValueCursor{index: 3, follow: None}, &ValueCursor{index: 0, follow: None});
+
+        assert!(values.0.is_empty());
+        assert_eq!(start.index, 0);
+    }
+
+    #[test]
+    fn remove_range_between_parts()
+    {
+        // from the numerator into the denominator keeps the fraction with whats left of both
+        let mut values = line("a");
+        values.0.push(fraction("bc", "de"));
+
+        let top = ValueCursor::from_path(&[(2, CursorFollow::Top)], 1);
+        let bottom = ValueCursor::from_path(&[(2, CursorFollow::Bottom)], 1);
+        values.remove_range(&bottom, &top);
+
+        assert_eq!(values.to_plain(), "a(b/e)");
+    }
+
+    #[test]
+    fn type_over_selection()
+    {
+        let no = Modifiers::default();
+
+        let mut state = ProgramState::new();
+        state.paste("ab");
+        state.paste_fraction("1\n2");
+        state.paste("cd");
+        state.key_pressed(Key::Left, no);
+
+        state.selection = Some(ValueCursor{index: 1, follow: None});
+        state.text_input("x".to_owned());
+
+        assert_eq!(state.lines[0].values.to_plain(), "axd");
+
+        state.key_pressed(Key::Left, Modifiers{ctrl: false, shift: true});
+        state.key_pressed(Key::Left, Modifiers{ctrl: false, shift: true});
+        assert!(state.selection.is_some());
+
+        state.key_pressed(Key::Backspace, no);
+
+        assert_eq!(state.lines[0].values.to_plain(), "d");
+        assert_eq!(state.cursor.value.index, 0);
+    }
+
+    #[test]
+    fn delete_into_a_structure()
+    {
+        let mut values = line("1");
+        values.0.push(fraction("ab", "c"));
+
+        // steps into the numerator and takes its first value
+        let mut cursor = ValueCursor{index: 1, follow: None};
+        values.remove_next(&mut cursor, DeleteStructure::StepInto);
+
+        assert_eq!(values.to_plain(), "1(b/c)");
+        assert_eq!(cursor.index, 2);
+        assert_eq!(cursor.path().len(), 1);
+
+        values.remove_next(&mut cursor, DeleteStructure::StepInto);
+        assert_eq!(values.to_plain(), "1(()/c)");
+
+        // doesnt go past the end of the numerator
+        values.remove_next(&mut cursor, DeleteStructure::StepInto);
+        assert_eq!(values.to_plain(), "1(()/c)");
+
+        // an empty entry part has nothing to step into so the whole thing goes
+        let mut cursor = ValueCursor{index: 1, follow: None};
+        values.remove_next(&mut cursor, DeleteStructure::StepInto);
+        assert_eq!(values.to_plain(), "1");
+    }
+
+    #[test]
+    fn delete_whole_structure()
+    {
+        let mut values = line("1");
+        values.0.push(fraction("ab", "c"));
+
+        let mut cursor = ValueCursor{index: 1, follow: None};
+        values.remove_next(&mut cursor, DeleteStructure::Whole);
+
+        assert_eq!(values.to_plain(), "1");
+        assert_eq!(cursor.index, 1);
+
+        // nothing after the cursor
+        values.remove_next(&mut cursor, DeleteStructure::Whole);
+        assert_eq!(values.to_plain(), "1");
+    }
+
+    #[test]
+    fn delete_key()
+    {
+        let no = Modifiers::default();
+
+        let mut state = ProgramState::new();
+        state.paste("1");
+        state.paste_fraction("2\n3");
+        state.cursor.value = ValueCursor{index: 1, follow: None};
+
+        state.key_pressed(Key::Delete, no);
+        assert_eq!(state.lines[0].values.to_plain(), "1(()/3)");
+
+        state.set_delete_structure(DeleteStructure::Whole);
+        state.cursor.value = ValueCursor{index: 1, follow: None};
+
+        state.key_pressed(Key::Delete, no);
+        assert_eq!(state.lines[0].values.to_plain(), "1");
+    }
+
+    #[test]
+    fn split_line()
+    {
+        let ctrl = Modifiers{ctrl: true, shift: false};
+
+        let mut state = ProgramState::new();
+        type_text(&mut state, "1+2/3");
+
+        // refuses inside of the fraction
+        assert!(!state.key_pressed(Key::Return, ctrl));
+        assert_eq!(state.lines.len(), 1);
+
+        state.key_pressed(Key::Right, Modifiers::default());
+        type_text(&mut state, "4");
+
+        // right before the fraction
+        state.cursor.value = ValueCursor{index: 2, follow: None};
+        assert!(state.key_pressed(Key::Return, ctrl));
+
+        assert_eq!(state.lines[0].values.to_plain(), "1+");
+        assert_eq!(state.lines[1].values.to_latex(), "\\frac{2}{3}4");
+
+        assert_eq!(state.cursor.line, 1);
+        assert_eq!(state.cursor.value.index, 0);
+        assert!(state.cursor.value.follow.is_none());
+
+        assert!(state.undo());
+        assert_eq!(state.lines.len(), 1);
+    }
+
+    #[test]
+    fn swap_fraction()
+    {
+        let ctrl_shift = Modifiers{ctrl: true, shift: true};
+
+        let mut state = ProgramState::new();
+        type_text(&mut state, "7/34");
+
+        // the cursor stays at the end of the part it was in which is now the top
+        state.key_pressed(Key::Character('u'), ctrl_shift);
+        assert_eq!(state.lines[0].values.to_latex(), "\\frac{34}{7}");
+
+        state.text_input("5".to_owned());
+        assert_eq!(state.lines[0].values.to_latex(), "\\frac{345}{7}");
+
+        // right after the fraction works too
+        state.key_pressed(Key::Right, Modifiers::default());
+        state.key_pressed(Key::Character('u'), ctrl_shift);
+        assert_eq!(state.lines[0].values.to_latex(), "\\frac{7}{345}");
+
+        state.text_input("+".to_owned());
+        assert_eq!(state.lines[0].values.to_latex(), "\\frac{7}{345}+");
+
+        // typing the plus and swapping are separate steps
+        assert!(state.undo());
+        assert!(state.undo());
+        assert_eq!(state.lines[0].values.to_latex(), "\\frac{345}{7}");
+    }
+
+    #[test]
+    fn cursor_at_the_start_of_a_line()
+    {
+        let mut state = ProgramState::new();
+        type_text(&mut state, "1/2");
+        state.key_pressed(Key::Right, Modifiers::default());
+        type_text(&mut state, "3");
+
+        let cursor_y = |state: &mut ProgramState, index|
+        {
+            cursor_position(state, ValueCursor{index, follow: None}).1
+        };
+
+        let start = cursor_y(&mut state, 0);
+        let between = cursor_y(&mut state, 1);
+        let end = cursor_y(&mut state, 2);
+
+        assert_eq!(start, between);
+        assert_eq!(start, end);
+
+        // level with the 3 after the fraction, not pushed down by how tall the fraction is
+        let (_, _, three) = text_positions(&state, 400, 200).into_iter().find(|(text, _, _)| text == "3").unwrap();
+        assert_eq!(start, three + 10.0);
+    }
+
+    #[test]
+    fn undo_fraction_collapse()
+    {
+        let no = Modifiers::default();
+
+        let mut state = ProgramState::new();
+        type_text(&mut state, "1+a/b");
+
+        let before = state.lines[0].values.to_latex();
+        assert_eq!(before, "1+\\frac{a}{b}");
+
+        // backspacing the b and then the empty denominator collapses the fraction
+        state.key_pressed(Key::Backspace, no);
+        state.key_pressed(Key::Backspace, no);
+        assert_eq!(state.lines[0].values.to_plain(), "1+a");
+
+        assert!(state.undo());
+        assert_eq!(state.lines[0].values.to_latex(), "1+\\frac{a}{}");
+        assert_eq!(state.cursor.value.path().len(), 1);
+        assert_eq!(state.cursor.value.index, 3);
+
+        assert!(state.undo());
+        assert_eq!(state.lines[0].values.to_latex(), before);
+    }
+
+    fn matrix_size(state: &ProgramState) -> (usize, usize)
+    {
+        let Some(InputValue::Matrix{rows, ..}) = state.lines[0].values.0.first() else { panic!() };
+
+        (rows.len(), rows[0].len())
+    }
+
+    #[test]
+    fn tab_through_matrix()
+    {
+        let no = Modifiers::default();
+
+        let mut state = ProgramState::new();
+        state.key_pressed(Key::Character('m'), Modifiers{ctrl: true, shift: false});
+
+        // the first row grows to the right
+        type_text(&mut state, "1");
+        state.key_pressed(Key::Tab, no);
+        type_text(&mut state, "2");
+        assert_eq!(matrix_size(&state), (1, 2));
+
+        state.key_pressed(Key::Return, no);
+        type_text(&mut state, "3");
+        assert_eq!(matrix_size(&state), (2, 2));
+
+        // after that the last cell of a row goes on to the next one
+        state.key_pressed(Key::Tab, no);
+        type_text(&mut state, "4");
+        state.key_pressed(Key::Tab, no);
+        assert_eq!(matrix_size(&state), (3, 2));
+
+        type_text(&mut state, "5");
+        assert_eq!(state.lines[0].values.to_latex(), "\\begin{pmatrix} 1 & 2 \\\\ 3 & 4 \\\\ 5 &  \\end{pmatrix}");
+
+        state.key_pressed(Key::Tab, Modifiers{ctrl: false, shift: true});
+        type_text(&mut state, "6");
+        assert_eq!(state.lines[0].values.to_latex(), "\\begin{pmatrix} 1 & 2 \\\\ 3 & 46 \\\\ 5 &  \\end{pmatrix}");
+    }
+
+    #[test]
+    fn cramped_denominator()
+    {
+        let power = |exponent: &str| InputValue::Superscript{base: line("x"), exponent: line(exponent)};
+        let nested = |base: &str, exponent: InputValue| InputValue::Superscript{base: line(base), exponent: InputValues(vec![exponent])};
+
+        let mut state = ProgramState::new();
+        state.lines[0].values = InputValues(vec![
+            power("2"),
+            InputValue::Fraction{top: InputValues(vec![power("3")]), bottom: InputValues(vec![power("4")])},
+            nested("x", nested("5", power("6"))),
+            InputValue::Fraction{
+                top: line("1"),
+                bottom: InputValues(vec![InputValue::Fraction{top: line("1"), bottom: InputValues(vec![power("7")])}])
+            }
+        ]);
+
+        let mut scales = Vec::new();
+        state.render(&Fixed, 400, 200, |_| (), |value|
+        {
+            if let RenderValue::Scaled{scale, value} = value
+            {
+                if let RenderValue::Text{text, ..} = &**value
+                {
+                    scales.push((text.to_string(), *scale));
+                }
+            }
+        });
+
+        let scale = |text: &str| scales.iter().find(|(x, _)| x == text).unwrap().1;
+
+        let layout = LayoutConfig::default();
+        assert_eq!(scale("2"), layout.exponent_scale);
+        assert_eq!(scale("3"), layout.exponent_scale);
+        assert_eq!(scale("4"), layout.exponent_scale * layout.cramped_scale);
+
+        let close = |a: f32, b: f32| (a - b).abs() < 0.0001;
+
+        // scripts of scripts are cramped and dont get any smaller than scriptscript
+        let scriptscript = layout.exponent_scale * layout.exponent_scale * layout.cramped_scale;
+        assert_eq!(scale("5"), layout.exponent_scale);
+        assert!(close(scale("x"), scriptscript));
+        assert!(close(scale("6"), scriptscript));
+
+        // a denominator inside a denominator isnt cramped twice
+        assert!(close(scale("7"), layout.exponent_scale * layout.cramped_scale));
+    }
+
+    #[test]
+    fn middle_bar()
+    {
+        let no = Modifiers::default();
+
+        let mut state = ProgramState::new();
+        type_text(&mut state, "{x\\middle x>0}+1");
+        assert_eq!(state.lines[0].values.to_latex(), "\\left\\{x\\middle|x>0\\right\\}+1");
+        assert_eq!(state.lines[0].values.to_plain(), "{x|x>0}+1");
+
+        let copy = ProgramState::from_compact(&state.to_compact()).unwrap();
+        assert_eq!(copy.lines[0].values.to_latex(), state.lines[0].values.to_latex());
+
+        // coming in from the right goes into the part after the bar
+        (0..6).for_each(|_| { state.key_pressed(Key::Left, no); });
+        state.key_pressed(Key::Backspace, no);
+        type_text(&mut state, ",");
+        assert_eq!(state.lines[0].values.to_latex(), "\\left\\{x,x>0\\right\\}+1");
+
+        // theres nothing to split outside of brackets
+        let mut state = ProgramState::new();
+        type_text(&mut state, "a\\middle b");
+        assert!(!state.lines[0].values.0.iter().any(|value| matches!(value, InputValue::Divided{..})));
+    }
+
+    #[test]
+    fn big_operators_grow()
+    {
+        let sign_scale = |body: fn() -> InputValues, sign: &str|
+        {
+            let mut state = ProgramState::new();
+            state.lines[0].values = InputValues(vec![
+                InputValue::Sum{lower: line("i"), upper: line("n"), body: body()},
+                InputValue::Integral{lower: line("0"), upper: line("1"), body: body()}
+            ]);
+
+            let mut scale = None;
+            state.render(&Fixed, 400, 400, |_| (), |value|
+            {
+                if let RenderValue::Scaled{scale: x, value} = value
+                {
+                    if matches!(&**value, RenderValue::Text{text, ..} if text == sign)
+                    {
+                        scale = Some(*x);
+                    }
+                }
+            });
+
+            scale.unwrap()
+        };
+
+        let layout = LayoutConfig::default();
+        assert_eq!(sign_scale(|| line("x"), "Σ"), layout.sum_scale);
+        assert_eq!(sign_scale(|| line("x"), "∫"), layout.integral_scale);
+
+        let tall = || InputValues(vec![InputValue::Fraction{
+            top: InputValues(vec![fraction("1", "2")]),
+            bottom: InputValues(vec![fraction("3", "4")])
+        }]);
+
+        assert!(sign_scale(tall, "Σ") > layout.sum_scale);
+        assert!(sign_scale(tall, "∫") > layout.integral_scale);
+    }
+
+    #[test]
+    fn table()
+    {
+        let no = Modifiers::default();
+
+        let mut state = ProgramState::new();
+        type_text(&mut state, "\\table 1");
+        state.key_pressed(Key::Tab, no);
+        type_text(&mut state, "2");
+        state.key_pressed(Key::Return, no);
+        type_text(&mut state, "3");
+        state.key_pressed(Key::Tab, no);
+        type_text(&mut state, "44");
+
+        // only the column the cursor is in changes
+        state.key_pressed(Key::Character('\\'), Modifiers{ctrl: true, shift: false});
+        state.key_pressed(Key::Character('\\'), Modifiers{ctrl: true, shift: true});
+
+        let latex = "\\begin{array}{c|r} 1 & 2 \\\\ \\hline 3 & 44 \\end{array}";
+        assert_eq!(state.lines[0].values.to_latex(), latex);
+
+        let copy = ProgramState::from_compact(&state.to_compact()).unwrap();
+        assert_eq!(copy.lines[0].values.to_latex(), latex);
+
+        let positions = text_positions(&state, 400, 200);
+        let find = |text: &str| positions.iter().find(|(x, _, _)| x == text).map(|(_, x, _)| *x).unwrap();
+        assert_eq!(find("2") + 10.0, find("44") + 20.0);
+
+        let mut lines = 0;
+        state.render(&Fixed, 400, 200, |_| (), |value|
+        {
+            if let RenderValue::Line{..} = value
+            {
+                lines += 1;
+            }
+        });
+
+        assert_eq!(lines, 2);
+
+        // an empty column taken away takes its alignment with it
+        let mut state = ProgramState::new();
+        type_text(&mut state, "\\table 1");
+        state.key_pressed(Key::Tab, no);
+        state.key_pressed(Key::Backspace, no);
+        assert_eq!(state.lines[0].values.to_latex(), "\\begin{array}{c} 1 \\end{array}");
+    }
+
+    #[test]
+    fn script_shifts()
+    {
+        let mut state = ProgramState::new();
+        state.lines[0].values = InputValues(vec![
+            InputValue::Superscript{base: line("x"), exponent: line("2")},
+            InputValue::Subscript{base: line("y"), subscript: line("1")}
+        ]);
+
+        // scripts are drawn scaled, the line moves around as it gets taller so its from the base
+        let script_y = |state: &ProgramState, script: &str, base: &str|
+        {
+            let base_y = text_positions(state, 400, 200).into_iter().find(|(x, _, _)| x == base).unwrap().2;
+
+            let mut position = None;
+            state.render(&Fixed, 400, 200, |_| (), |value|
+            {
+                if let RenderValue::Scaled{value, ..} = value
+                {
+                    if let RenderValue::Text{y, text, ..} = &**value
+                    {
+                        if text == script
+                        {
+                            position = Some(*y);
+                        }
+                    }
+                }
+            });
+
+            position.unwrap() - base_y
+        };
+
+        let (superscript, subscript) = (script_y(&state, "2", "x"), script_y(&state, "1", "y"));
+
+        let layout = LayoutConfig::default();
+        state.set_scripts(layout.exponent_scale, layout.superscript_shift + 0.5, layout.subscript_shift + 0.25);
+
+        let font_size = layout.font_size as f32;
+        assert_eq!(script_y(&state, "2", "x"), superscript - font_size * 0.5);
+        assert_eq!(script_y(&state, "1", "y"), subscript + font_size * 0.25);
+    }
+
+    #[test]
+    fn export_results_around_invalid_lines()
+    {
+        let mut state = ProgramState::new();
+        state.lines = [
+            line("1+2"),
+            InputValues(vec![InputValue::Fraction{top: line("1"), bottom: InputValues::default()}]),
+            line("3*3")
+        ].into_iter().map(|values| Line{values, ..Default::default()}).collect();
+
+        let (text, errors) = state.export_results(ExportFormat::Plain);
+
+        assert_eq!(text, "1+2 = 3\n3*3 = 9");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 1);
+    }
+
+    #[test]
+    fn result_precision()
+    {
+        let mut state = ProgramState::new();
+        state.lines[0].values = line("2/3");
+
+        let hash = state.frame_hash(400, 200);
+        assert_eq!(state.export_results(ExportFormat::Plain).0, "2/3 = 0.666667");
+
+        state.set_precision(2);
+        assert_eq!(state.export_results(ExportFormat::Plain).0, "2/3 = 0.67");
+
+        // the shown result changes so the frame has to be drawn again
+        assert_ne!(state.frame_hash(400, 200), hash);
+    }
+
+    #[test]
+    fn replay_is_one_step()
+    {
+        let no = Modifiers::default();
+        let ctrl = Modifiers{ctrl: true, shift: false};
+        let ctrl_shift = Modifiers{ctrl: true, shift: true};
+
+        let mut state = ProgramState::new();
+        type_text(&mut state, "x");
+
+        state.key_pressed(Key::Character('q'), ctrl_shift);
+        type_text(&mut state, "+1");
+        state.key_pressed(Key::Left, no);
+
+        // undoing would change what gets replayed
+        assert!(!state.key_pressed(Key::Character('z'), ctrl));
+        state.key_pressed(Key::Character('q'), ctrl_shift);
+
+        assert_eq!(state.lines[0].values.to_plain(), "x+1");
+
+        state.key_pressed(Key::Character('y'), ctrl_shift);
+        assert_eq!(state.lines[0].values.to_plain(), "x++11");
+
+        state.key_pressed(Key::Character('z'), ctrl);
+        assert_eq!(state.lines[0].values.to_plain(), "x+1");
+
+        state.key_pressed(Key::Character('y'), ctrl);
+        assert_eq!(state.lines[0].values.to_plain(), "x++11");
+    }
+}
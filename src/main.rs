@@ -1,1042 +1,1402 @@
-use std::{cell::RefCell, mem};
+use std::{
+    env,
+    fs,
+    process,
+    cell::RefCell,
+    hash::{Hash, Hasher, DefaultHasher},
+    time::{Duration, Instant}
+};
 
 use sdl2::{
-    rect::Rect,
-    ttf::Font,
-    pixels::Color,
-    render::{WindowCanvas, Texture, TextureCreator},
+    rect::{Point, Rect},
+    ttf::{Font, Hinting},
+    pixels::{Color, PixelFormatEnum},
+    surface::Surface,
+    render::{Canvas, BlendMode, RenderTarget, WindowCanvas, Texture, TextureCreator},
     video::WindowContext,
     event::{WindowEvent, Event},
-    keyboard::Keycode
+    keyboard::{Mod, Keycode},
+    mouse::MouseButton
+};
+
+use matheditor::{
+    FONT_SIZE,
+    ProgramState,
+    RenderValue,
+    RenderRect,
+    LayoutConfig,
+    DotsKind,
+    UnitKind,
+    Key,
+    Modifiers,
+    FractionEntryMode,
+    DeleteStructure,
+    VerticalCentering,
+    TextMeasure,
+    png,
+    export::{ExportFormat, ImageBorder}
 };
 
 
-const FONT_SIZE: u32 = 20;
+const WINDOW_STATE_PATH: &str = ".matheditor_window";
+
+const IMAGE_EXPORT_PATH: &str = "matheditor.png";
+
+// sdls clipboard only holds text so regions go to a file too
+const REGION_EXPORT_PATH: &str = "matheditor_region.png";
+
+const DEFAULT_FONT: &str = "font/LiberationMono-Regular.ttf";
+
+// side length of the magnifier panel in pixels and how much its contents get scaled up
+const LOUPE_SIZE: u32 = 180;
+const LOUPE_ZOOM: u32 = 3;
+
+const ANIMATION_FRAME_MS: u32 = 16;
+
+// drawn over the document in the window only
+#[derive(Debug, Clone, Copy, Default, Hash)]
+struct Overlays
+{
+    // magnified view around the cursor
+    loupe: bool,
+    // where an alt drag started and where its at now, whats inside gets saved as an image
+    region: Option<((i32, i32), (i32, i32))>
+}
+
+struct Fonts<'a>
+{
+    text: Font<'a, 'static>,
+    math: Font<'a, 'static>,
+    // what was last applied to both fonts
+    settings: FontSettings
+}
+
+// how glyphs get rasterized, different fonts and displays look best with different ones
+#[derive(Debug, Clone, PartialEq)]
+struct FontSettings
+{
+    hinting: Hinting,
+    kerning: bool
+}
 
-#[derive(Debug)]
-enum RenderValue<'a>
+// hinting doesnt implement hash itself but its a plain enum
+impl Hash for FontSettings
 {
-    Text{x: i32, y: i32, text: &'a str},
-    Line{x: i32, y: i32, width: u32},
-    Cursor{x: i32, y: i32}
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        (self.hinting.clone() as i32, self.kerning).hash(state);
+    }
 }
 
-impl RenderValue<'_>
+impl Default for FontSettings
 {
-    pub fn new_cursor(x: i32, y: i32) -> Self
+    fn default() -> Self
     {
-        Self::Cursor{x, y: y - FONT_SIZE as i32 / 2}
+        Self{hinting: Hinting::Normal, kerning: true}
     }
+}
 
-    pub fn new_cursor_rect(rect: RenderRect) -> Self
+impl FontSettings
+{
+    fn next_hinting(&mut self)
     {
-        Self::new_cursor(rect.x + rect.width as i32, rect.y + rect.height as i32 / 2)
+        self.hinting = match self.hinting
+        {
+            Hinting::Normal => Hinting::Light,
+            Hinting::Light => Hinting::Mono,
+            Hinting::Mono => Hinting::None,
+            Hinting::None => Hinting::Normal
+        };
     }
+}
 
-    pub fn shift(&mut self, shift_x: i32, shift_y: i32)
+impl Fonts<'_>
+{
+    pub fn apply_settings(&mut self, settings: &FontSettings)
     {
-        match self
+        [&mut self.text, &mut self.math].into_iter().for_each(|font|
         {
-            Self::Text{x, y, ..} =>
-            {
-                *x += shift_x;
-                *y += shift_y;
-            },
-            Self::Line{x, y, ..} =>
-            {
-                *x += shift_x;
-                *y += shift_y;
-            },
-            Self::Cursor{x, y} =>
-            {
-                *x += shift_x;
-                *y += shift_y;
-            }
+            font.set_hinting(settings.hinting.clone());
+            font.set_kerning(settings.kerning);
+        });
+
+        self.settings = settings.clone();
+    }
+
+    // letters and numbers use the text font, operators and other symbols the math one
+    pub fn for_text(&self, text: &str) -> &Font<'_, 'static>
+    {
+        if text.chars().all(|c| c.is_alphanumeric() || c == '.' || c.is_whitespace())
+        {
+            &self.text
+        } else
+        {
+            &self.math
         }
     }
 }
 
-#[derive(Debug)]
-struct InputValues(Vec<InputValue>);
+impl TextMeasure for Fonts<'_>
+{
+    fn text_size(&self, text: &str) -> (u32, u32)
+    {
+        self.for_text(text).size_of(text).unwrap()
+    }
+
+    // only asked about symbols so these go to the math font
+    fn has_glyph(&self, c: char) -> bool
+    {
+        self.math.find_glyph(c).is_some()
+    }
+
+    fn height(&self) -> u32
+    {
+        self.math.height() as u32
+    }
+}
 
-#[derive(Debug)]
-enum InputValue
+#[derive(Debug, Clone, Copy)]
+struct WindowGeometry
 {
-    Value(String),
-    Fraction{top: InputValues, bottom: InputValues}
+    position: Option<(i32, i32)>,
+    width: u32,
+    height: u32
 }
 
-impl Default for InputValue
+impl Default for WindowGeometry
 {
     fn default() -> Self
     {
-        Self::Value(String::new())
+        Self{position: None, width: 640, height: 480}
     }
 }
 
-impl InputValue
+impl WindowGeometry
 {
-    #[allow(dead_code)]
-    pub fn is_value(&self) -> bool
+    pub fn load() -> Option<Self>
     {
-        if let Self::Value(_) = self
-        {
-            true
-        } else
+        let text = fs::read_to_string(WINDOW_STATE_PATH).ok()?;
+
+        let mut values = text.split_whitespace();
+        let mut next = || values.next()?.parse::<i64>().ok();
+
+        let (x, y, width, height) = (next()?, next()?, next()?, next()?);
+
+        Some(Self{
+            position: Some((x.try_into().ok()?, y.try_into().ok()?)),
+            width: width.try_into().ok().filter(|width| *width > 0)?,
+            height: height.try_into().ok().filter(|height| *height > 0)?
+        })
+    }
+
+    pub fn save(&self)
+    {
+        let (x, y) = self.position.unwrap_or_default();
+        let text = format!("{x} {y} {} {}\n", self.width, self.height);
+
+        if let Err(err) = fs::write(WINDOW_STATE_PATH, text)
         {
-            false
+            eprintln!("couldnt save window state: {err}");
         }
     }
+}
+
+struct Args
+{
+    width: Option<u32>,
+    height: Option<u32>,
+    fraction_entry: FractionEntryMode,
+    delete_structure: DeleteStructure,
+    text_font: String,
+    math_font: String,
+    decimal_separator: char,
+    // digits after the decimal point in results
+    precision: Option<usize>,
+    vertical_centering: VerticalCentering,
+    font_settings: FontSettings,
+    // in font sizes
+    wrap_width: Option<u32>,
+    // size of scripts and how far theyre moved from their base, the shifts are in font sizes
+    script_scale: f32,
+    superscript_shift: f32,
+    subscript_shift: f32,
+    // number of edits to time instead of opening a window
+    bench: Option<u32>,
+    image_border: ImageBorder
+}
 
-    pub fn render(
-        &self,
-        cursor: Option<&(CursorFollow, Box<ValueCursor>)>,
-        x: i32,
-        y: i32,
-        f: &impl Fn(RenderValue) -> RenderResult
-    ) -> RenderResult
+impl Args
+{
+    pub fn parse() -> Self
     {
-        match self
+        let layout = LayoutConfig::default();
+
+        let mut this = Self{
+            width: None,
+            height: None,
+            fraction_entry: FractionEntryMode::default(),
+            delete_structure: DeleteStructure::default(),
+            text_font: DEFAULT_FONT.to_owned(),
+            math_font: DEFAULT_FONT.to_owned(),
+            decimal_separator: '.',
+            precision: None,
+            vertical_centering: VerticalCentering::default(),
+            font_settings: FontSettings::default(),
+            wrap_width: None,
+            script_scale: layout.exponent_scale,
+            superscript_shift: layout.superscript_shift,
+            subscript_shift: layout.subscript_shift,
+            bench: None,
+            image_border: ImageBorder::default()
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next()
         {
-            Self::Value(text) => f(RenderValue::Text{x, y, text}),
-            Self::Fraction{top, bottom} =>
+            let mut value = |name: &str| -> u32
             {
-                let top_cursor = cursor.and_then(|x@(follow, _)|
+                let value = args.next().unwrap_or_else(||
                 {
-                    (*follow == CursorFollow::Top).then_some(&*x.1)
+                    eprintln!("{name} expects a value");
+                    process::exit(1)
                 });
 
-                let mut top = top.render(top_cursor, x, y, f);
-
-                let bottom_cursor = cursor.and_then(|x@(follow, _)|
+                value.parse().unwrap_or_else(|_|
                 {
-                    (*follow == CursorFollow::Bottom).then_some(&*x.1)
-                });
+                    eprintln!("{name} expects a positive number, got {value}");
+                    process::exit(1)
+                })
+            };
 
-                let mut bottom = bottom.render(bottom_cursor, x, y, f);
+            match arg.as_ref()
+            {
+                "--width" | "--height" =>
+                {
+                    // a window with no size cant be made
+                    let size = value(&arg);
+                    if size == 0
+                    {
+                        eprintln!("{arg} expects a positive number, got 0");
+                        process::exit(1)
+                    }
 
-                let (top_shift_x, bottom_shift_x) = if top.rect.width < bottom.rect.width
+                    if arg == "--width" { this.width = Some(size) } else { this.height = Some(size) }
+                },
+                "--bench" => this.bench = Some(value("--bench")),
+                "--wrap-width" => this.wrap_width = Some(value("--wrap-width")),
+                "--export-padding" => this.image_border.padding = value("--export-padding") as f32,
+                "--export-shadow" => this.image_border.shadow = true,
+                "--delete-structure" =>
                 {
-                    ((bottom.rect.width as i32 - top.rect.width as i32) / 2, 0)
-                } else
+                    this.delete_structure = match args.next().as_deref()
+                    {
+                        Some("step") => DeleteStructure::StepInto,
+                        Some("whole") => DeleteStructure::Whole,
+                        _ =>
+                        {
+                            eprintln!("--delete-structure expects step or whole");
+                            process::exit(1)
+                        }
+                    };
+                },
+                "--decimal-separator" =>
                 {
-                    (0, (top.rect.width as i32 - bottom.rect.width as i32) / 2)
-                };
-
-                let offset_y = top.rect.height.max(bottom.rect.height) as i32 / 2;
-                top.shift(top_shift_x, -offset_y);
-                bottom.shift(bottom_shift_x, offset_y);
-
-                let width = top.rect.width.max(bottom.rect.width);
-
-                let line = {
-                    let y = (top.rect.y + top.rect.height as i32 + bottom.rect.y) / 2;
-                    f(RenderValue::Line{x, y, width})
-                };
+                    this.decimal_separator = match args.next().as_deref()
+                    {
+                        Some(".") => '.',
+                        Some(",") => ',',
+                        _ =>
+                        {
+                            eprintln!("--decimal-separator expects . or ,");
+                            process::exit(1)
+                        }
+                    };
+                },
+                "--precision" => this.precision = Some(value("--precision") as usize),
+                "--center" =>
+                {
+                    this.vertical_centering = match args.next().as_deref()
+                    {
+                        Some("bounds") => VerticalCentering::Bounds,
+                        Some("axis") => VerticalCentering::Axis,
+                        _ =>
+                        {
+                            eprintln!("--center expects bounds or axis");
+                            process::exit(1)
+                        }
+                    };
+                },
+                "--hinting" =>
+                {
+                    this.font_settings.hinting = match args.next().as_deref()
+                    {
+                        Some("normal") => Hinting::Normal,
+                        Some("light") => Hinting::Light,
+                        Some("mono") => Hinting::Mono,
+                        Some("none") => Hinting::None,
+                        _ =>
+                        {
+                            eprintln!("--hinting expects normal, light, mono or none");
+                            process::exit(1)
+                        }
+                    };
+                },
+                "--kerning" =>
+                {
+                    this.font_settings.kerning = match args.next().as_deref()
+                    {
+                        Some("on") => true,
+                        Some("off") => false,
+                        _ =>
+                        {
+                            eprintln!("--kerning expects on or off");
+                            process::exit(1)
+                        }
+                    };
+                },
+                "--script-scale" | "--superscript-shift" | "--subscript-shift" =>
+                {
+                    let number = args.next().and_then(|value| value.parse::<f32>().ok()).filter(|x| x.is_finite());
 
-                let rect = bottom.rect.combine(top.rect);
+                    // shifts can go either way but scripts need some size
+                    let number = match number
+                    {
+                        Some(x) if arg != "--script-scale" || x > 0.0 => x,
+                        _ =>
+                        {
+                            eprintln!("{arg} expects a number of font sizes");
+                            process::exit(1)
+                        }
+                    };
 
-                let mut render = top.render;
-                render.extend(bottom.render);
-                render.extend(line.render);
+                    match arg.as_ref()
+                    {
+                        "--script-scale" => this.script_scale = number,
+                        "--superscript-shift" => this.superscript_shift = number,
+                        _ => this.subscript_shift = number
+                    }
+                },
+                "--text-font" | "--math-font" =>
+                {
+                    let Some(path) = args.next() else
+                    {
+                        eprintln!("{arg} expects a path");
+                        process::exit(1)
+                    };
 
-                RenderResult{rect, render}
+                    if arg == "--text-font" { this.text_font = path } else { this.math_font = path }
+                },
+                "--fraction-entry" =>
+                {
+                    this.fraction_entry = match args.next().as_deref()
+                    {
+                        Some("wrap") => FractionEntryMode::WrapPrevious,
+                        Some("top-first") => FractionEntryMode::EmptyTopFirst,
+                        _ =>
+                        {
+                            eprintln!("--fraction-entry expects wrap or top-first");
+                            process::exit(1)
+                        }
+                    };
+                },
+                x =>
+                {
+                    eprintln!("unknown argument: {x}");
+                    process::exit(1)
+                }
             }
         }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct RenderRect
-{
-    x: i32,
-    y: i32,
-    width: u32,
-    height: u32
-}
 
-impl From<RenderRect> for Rect
-{
-    fn from(v: RenderRect) -> Self
-    {
-        Self::new(v.x, v.y, v.width, v.height)
+        this
     }
 }
 
-impl From<Rect> for RenderRect
-{
-    fn from(v: Rect) -> Self
-    {
-        Self{x: v.x, y: v.y, width: v.width(), height: v.height()}
-    }
-}
+// every glyph is the same size so timings dont depend on fonts
+struct HeadlessMeasure;
 
-impl RenderRect
+impl TextMeasure for HeadlessMeasure
 {
-    pub fn empty() -> Self
+    fn text_size(&self, text: &str) -> (u32, u32)
     {
-        Self{x: 0, y: 0, width: 0, height: 0}
+        (text.chars().count() as u32 * FONT_SIZE / 2, FONT_SIZE)
     }
 
-    fn end(self) -> (i32, i32)
+    fn has_glyph(&self, _c: char) -> bool
     {
-        (self.x + self.width as i32, self.y + self.height as i32)
+        true
     }
 
-    pub fn combine(self, other: Self) -> Self
+    fn height(&self) -> u32
     {
-        let x = self.x.min(other.x);
-        let y = self.y.min(other.y);
-
-        let this_end = self.end();
-        let other_end = other.end();
-        let end_x = this_end.0.max(other_end.0);
-        let end_y = this_end.1.max(other_end.1);
-
-        Self{x, y, width: (end_x - x) as u32, height: (end_y - y) as u32}
+        FONT_SIZE
     }
 }
 
-struct RenderResult<'a>
+// types into the middle of a big document and times the render after every edit
+fn run_bench(edits: u32)
 {
-    rect: RenderRect,
-    render: Vec<RenderValue<'a>>
-}
+    const LINES: usize = 200;
+    const SIZE: (u32, u32) = (1280, 720);
 
-impl<'a> RenderResult<'a>
-{
-    pub fn new(rect: RenderRect, render: RenderValue<'a>) -> Self
-    {
-        Self{rect, render: vec![render]}
-    }
+    let mut state = ProgramState::new();
 
-    pub fn empty(rect: RenderRect) -> Self
+    let type_text = |state: &mut ProgramState, text: &str|
     {
-        Self{rect, render: Vec::new()}
-    }
+        text.chars().for_each(|c| { state.text_input(c.to_string()); });
+    };
 
-    fn is_cursor(&self) -> bool
+    (0..LINES).for_each(|index|
     {
-        if self.render.len() == 1
+        if index > 0
         {
-            match &self.render[0]
-            {
-                RenderValue::Cursor{..} => return true,
-                _ => ()
-            }
+            state.key_pressed(Key::Return, Modifiers::default());
         }
 
-        false
-    }
+        type_text(&mut state, &format!("x{index}=1/2+3/(4+5)*{index}/7-2^3"));
+    });
+
+    // back up to the middle so edits have lines on both sides
+    (0..LINES / 2).for_each(|_| { state.key_pressed(Key::Up, Modifiers::default()); });
 
-    pub fn combine(mut self, other: RenderResult<'a>) -> Self
+    let mut times: Vec<Duration> = (0..edits).map(|index|
     {
-        if !other.is_cursor()
+        // alternate typing and deleting so the line stays the same length
+        if index % 2 == 0
         {
-            self.rect = self.rect.combine(other.rect);
+            type_text(&mut state, "1");
+        } else
+        {
+            state.key_pressed(Key::Backspace, Modifiers::default());
         }
 
-        self.render.extend(other.render);
+        let start = Instant::now();
+        state.render(&HeadlessMeasure, SIZE.0, SIZE.1, |_| (), |_| ());
 
-        self
-    }
+        start.elapsed()
+    }).collect();
 
-    pub fn shift(&mut self, x: i32, y: i32)
+    if times.is_empty()
     {
-        self.rect.x += x;
-        self.rect.y += y;
-
-        self.render.iter_mut().for_each(|r| r.shift(x, y));
+        return;
     }
 
-    pub fn render(&self, renderer: impl FnMut(&RenderValue))
-    {
-        self.render.iter().for_each(renderer);
-    }
-}
+    times.sort();
 
-trait CursorTrait
-{
-    fn next(self) -> Self;
-    fn follow(&self) -> Option<CursorFollow>;
-    fn index(&self) -> usize;
+    let total: Duration = times.iter().sum();
+
+    println!("{edits} edits on {LINES} lines");
+    println!("mean: {:?}", total / edits);
+    println!("median: {:?}", times[times.len() / 2]);
+    println!("max: {:?}", times[times.len() - 1]);
 }
 
-impl CursorTrait for &ValueCursor
+fn main()
 {
-    fn next(self) -> Self
-    {
-        &*self.follow.as_ref().unwrap().1
-    }
+    let args = Args::parse();
 
-    fn follow(&self) -> Option<CursorFollow>
+    if let Some(edits) = args.bench
     {
-        self.follow.as_ref().map(|x| x.0)
+        run_bench(edits);
+
+        return;
     }
 
-    fn index(&self) -> usize
+    let ctx = sdl2::init().unwrap();
+
+    let video = ctx.video().unwrap();
+
+    let mut geometry = WindowGeometry::load().unwrap_or_default();
+    geometry.width = args.width.unwrap_or(geometry.width);
+    geometry.height = args.height.unwrap_or(geometry.height);
+
+    let mut window = video.window("lil fun algebra thing", geometry.width, geometry.height);
+    window.resizable();
+
+    if let Some((x, y)) = geometry.position
     {
-        self.index
+        window.position(x, y);
     }
-}
 
-impl CursorTrait for &mut ValueCursor
-{
-    fn next(self) -> Self
+    let window = window.build().unwrap_or_else(|err|
     {
-        &mut *self.follow.as_mut().unwrap().1
-    }
+        eprintln!("couldnt create the window: {err}");
+        process::exit(1)
+    });
 
-    fn follow(&self) -> Option<CursorFollow>
+    let mut canvas = window.into_canvas().build().unwrap_or_else(|err|
     {
-        self.follow.as_ref().map(|x| x.0)
-    }
+        eprintln!("couldnt create the renderer: {err}");
+        process::exit(1)
+    });
+    let clipboard = video.clipboard();
+    let creator = canvas.texture_creator();
 
-    fn index(&self) -> usize
+    let mut events = ctx.event_pump().unwrap();
+
+    fn key_from_keycode(key: Keycode) -> Option<Key>
     {
-        self.index
+        let key = match key
+        {
+            Keycode::BACKSPACE => Key::Backspace,
+            Keycode::DELETE => Key::Delete,
+            Keycode::RETURN => Key::Return,
+            Keycode::TAB => Key::Tab,
+            Keycode::LEFT => Key::Left,
+            Keycode::RIGHT => Key::Right,
+            Keycode::UP => Key::Up,
+            Keycode::DOWN => Key::Down,
+            Keycode::F5 => Key::F5,
+            Keycode::ESCAPE => Key::Escape,
+            x => Key::Character(char::from_u32(x.into_i32() as u32)?)
+        };
+
+        Some(key)
     }
-}
 
-macro_rules! define_traverse
-{
-    ($name:ident, $($ref_t:tt)*) =>
+    // how many times a held key gets applied per repeat, grows the longer its held
+    fn repeat_count(held: Duration) -> usize
     {
-        fn $name<'a, T, C: CursorTrait>(
-            &'a $($ref_t)* self,
-            cursor: C,
-            finish: impl FnOnce(&'a $($ref_t)* Self, C) -> T
-        ) -> T
+        match held.as_millis()
         {
-            if let Some(direction) = cursor.follow()
-            {
-                match (& $($ref_t)* self.0[cursor.index() - 1], direction)
-                {
-                    (InputValue::Fraction{top, ..}, CursorFollow::Top) =>
-                    {
-                        top.$name(cursor.next(), finish)
-                    },
-                    (InputValue::Fraction{bottom, ..}, CursorFollow::Bottom) =>
-                    {
-                        bottom.$name(cursor.next(), finish)
-                    },
-                    (InputValue::Value(_), _) => unreachable!()
-                }
-            } else
-            {
-                finish(self, cursor)
-            }
+            0..=800 => 1,
+            801..=2000 => 2,
+            _ => 4
         }
     }
-}
 
-impl Default for InputValues
-{
-    fn default() -> Self
+    fn draw_value<T: RenderTarget, C>(
+        canvas: &mut Canvas<T>,
+        fonts: &Fonts,
+        creator: &TextureCreator<C>,
+        layout: &LayoutConfig,
+        render: &RenderValue,
+        color: Color
+    )
     {
-        Self(Vec::new())
-    }
-}
-
-impl InputValues
-{
-    define_traverse!{traverse, }
-    define_traverse!{traverse_mut, mut}
+        canvas.set_draw_color(color);
 
-    pub fn add_text(&mut self, cursor: &ValueCursor, text: String)
-    {
-        self.traverse_mut(cursor, |this, cursor| this.0.insert(cursor.index, InputValue::Value(text)));
-    }
+        let round = |value: f32| value.round() as i32;
 
-    pub fn add_fraction(&mut self, cursor: &ValueCursor)
-    {
-        self.traverse_mut(cursor, |this, cursor|
+        match render
         {
-            if let Some(index) = cursor.index.checked_sub(1)
+            RenderValue::Text{x, y, text: value} =>
             {
-                let value = mem::take(&mut this.0[index]);
+                let text = fonts.for_text(value).render(value).blended(color).unwrap();
+                let texture = Texture::from_surface(&text, creator).unwrap();
 
-                this.0[index] = InputValue::Fraction{top: Self(vec![value]), bottom: Self(Vec::new())};
-            }
-        });
-    }
+                let rect = Rect::new(round(*x), round(*y), text.width(), text.height());
+                canvas.copy(&texture, None, rect).unwrap();
+            },
+            // horizontal lines like fraction bars stay a crisp rect
+            RenderValue::Line{x1, y1, x2, y2, thickness} if y1 == y2 =>
+            {
+                let x = x1.min(*x2);
+                let rect = RenderRect{x, y: y1 - thickness / 2.0, width: (x2 - x1).abs(), height: *thickness};
 
-    fn replace(&mut self, index: usize, values: InputValues)
-    {
-        self.0.remove(index);
+                canvas.fill_rect(Rect::from(rect)).unwrap();
+            },
+            RenderValue::Line{x1, y1, x2, y2, thickness} =>
+            {
+                let (dx, dy) = (x2 - x1, y2 - y1);
+                let length = dx.hypot(dy);
 
-        values.0.into_iter().rev().for_each(|value|
-        {
-            self.0.insert(index, value);
-        });
-    }
+                // unit normal, thin lines get stacked along it every half pixel to fill the thickness
+                let (normal_x, normal_y) = (-dy / length, dx / length);
 
-    pub fn remove_single(&mut self, cursor: &mut ValueCursor) -> bool
-    {
-        if let Some((direction, follow)) = cursor.follow.as_mut()
-        {
-            let index = cursor.index - 1;
-            let remove_this = match (&mut self.0[index], direction)
+                let steps = (thickness * 2.0).ceil().max(1.0) as i32;
+                (0..steps).for_each(|step|
+                {
+                    let offset = step as f32 / 2.0 - thickness / 2.0;
+                    let (offset_x, offset_y) = (normal_x * offset, normal_y * offset);
+
+                    let start = Point::new(round(x1 + offset_x), round(y1 + offset_y));
+                    let end = Point::new(round(x2 + offset_x), round(y2 + offset_y));
+
+                    canvas.draw_line(start, end).unwrap();
+                });
+            },
+            RenderValue::Dots{x, y, kind} =>
             {
-                (InputValue::Fraction{top, bottom}, CursorFollow::Top) =>
+                if fonts.math.find_glyph(kind.as_char()).is_some()
+                {
+                    let text = fonts.math.render_char(kind.as_char())
+                        .blended(color)
+                        .unwrap();
+
+                    let texture = Texture::from_surface(&text, creator).unwrap();
+
+                    let rect = Rect::new(round(*x), round(*y), text.width(), text.height());
+                    canvas.copy(&texture, None, rect).unwrap();
+                } else
                 {
-                    let remove_this = top.remove_single(follow);
+                    let size = DotsKind::dot_size(layout.font_size);
+                    let height = fonts.math.height() as f32;
 
-                    if remove_this
+                    kind.dots(layout.font_size as f32, height).into_iter().for_each(|(dot_x, dot_y)|
                     {
-                        let value = mem::take(bottom);
-                        self.replace(index, value);
-                    }
+                        let half = size as f32 / 2.0;
+                        let rect = Rect::new(
+                            round(x + dot_x - half),
+                            round(y + dot_y - half),
+                            size,
+                            size
+                        );
+
+                        canvas.fill_rect(rect).unwrap();
+                    });
+                }
+            },
+            RenderValue::Unit{x, y, kind} =>
+            {
+                let text = if fonts.math.find_glyph(kind.as_char()).is_some()
+                {
+                    Some(fonts.math.render_char(kind.as_char()))
+                } else
+                {
+                    kind.fallback().map(|fallback| fonts.math.render(fallback))
+                };
 
-                    remove_this
-                },
-                (InputValue::Fraction{top, bottom}, CursorFollow::Bottom) =>
+                if let Some(text) = text
+                {
+                    let text = text.blended(color).unwrap();
+                    let texture = Texture::from_surface(&text, creator).unwrap();
+
+                    let rect = Rect::new(round(*x), round(*y), text.width(), text.height());
+                    canvas.copy(&texture, None, rect).unwrap();
+                } else
                 {
-                    let remove_this = bottom.remove_single(follow);
+                    let radius = UnitKind::circle_radius(layout.font_size) as f32;
+                    let center_x = x + radius * 2.0;
+                    let center_y = y + radius * 2.0;
 
-                    if remove_this
+                    let points: Vec<Point> = (0..32).map(|i|
                     {
-                        let value = mem::take(top);
-                        self.replace(index, value);
-                    }
+                        let angle = i as f32 / 32.0 * std::f32::consts::TAU;
 
-                    remove_this
-                },
-                (InputValue::Value(_), _) => unreachable!()
-            };
+                        Point::new(
+                            round(center_x + angle.cos() * radius),
+                            round(center_y + angle.sin() * radius)
+                        )
+                    }).collect();
 
-            if remove_this
+                    canvas.draw_points(points.as_slice()).unwrap();
+                }
+            },
+            RenderValue::Placeholder{x, y, width, height} =>
             {
-                cursor.follow = None;
-            }
+                canvas.set_draw_color(Color::RGB(170, 170, 170));
 
-            false
-        } else
-        {
-            if let Some(index) = cursor.index.checked_sub(1)
-            {
-                self.0.remove(index);
-                cursor.index = index;
+                let (left, top) = (round(*x), round(*y));
+                let (right, bottom) = (round(x + width), round(y + height));
 
-                false
-            } else
-            {
-                true
-            }
-        }
-    }
+                // every other few pixels along each edge
+                let dash = 3;
+                let horizontal = (left..=right).filter(|x| (x - left) / dash % 2 == 0)
+                    .flat_map(|x| [Point::new(x, top), Point::new(x, bottom)]);
 
-    fn move_right_inner(&self, cursor: &mut ValueCursor) -> bool
-    {
-        if let Some((direction, follow)) = cursor.follow.as_mut()
-        {
-            let move_this = match (&self.0[cursor.index - 1], direction)
-            {
-                (InputValue::Fraction{top, ..}, CursorFollow::Top) =>
-                {
-                    top.move_right_inner(follow)
-                },
-                (InputValue::Fraction{bottom, ..}, CursorFollow::Bottom) =>
-                {
-                    bottom.move_right_inner(follow)
-                },
-                (InputValue::Value(_), _) => unreachable!()
-            };
+                let vertical = (top..=bottom).filter(|y| (y - top) / dash % 2 == 0)
+                    .flat_map(|y| [Point::new(left, y), Point::new(right, y)]);
 
-            if move_this
-            {
-                cursor.follow = None;
-            }
+                let points: Vec<Point> = horizontal.chain(vertical).collect();
 
-            move_this
-        } else
-        {
-            if cursor.index < self.0.len()
+                canvas.draw_points(points.as_slice()).unwrap();
+            },
+            RenderValue::Panel{x, y, width, height} =>
             {
-                cursor.index += 1;
+                let rect = Rect::from(RenderRect{x: *x, y: *y, width: *width, height: *height});
+
+                canvas.set_draw_color(Color::RGB(255, 255, 255));
+                canvas.fill_rect(rect).unwrap();
 
-                false
-            } else
+                canvas.set_draw_color(Color::RGB(0, 0, 0));
+                canvas.draw_rect(rect).unwrap();
+            },
+            RenderValue::Cursor{x, y, color: [r, g, b]} =>
             {
-                true
-            }
-        }
-    }
+                canvas.set_draw_color(Color::RGB(*r, *g, *b));
+
+                let cursor_height = layout.font_size;
+                canvas.fill_rect(Rect::new(
+                    round(*x),
+                    round(*y),
+                    layout.cursor_width,
+                    cursor_height
+                )).unwrap();
+            },
+            RenderValue::Radical{x, y, width, height, thickness} =>
+            {
+                let points = RenderValue::radical_points(*x, *y, *width, *height);
 
-    fn move_left_inner(&self, cursor: &mut ValueCursor) -> bool
-    {
-        if let Some((_direction, follow)) = cursor.follow.as_mut()
-        {
-            if self.move_left_inner(follow)
+                points.windows(2).for_each(|segment|
+                {
+                    let ((x1, y1), (x2, y2)) = (segment[0], segment[1]);
+                    let line = RenderValue::Line{x1, y1, x2, y2, thickness: *thickness};
+
+                    draw_value(canvas, fonts, creator, layout, &line, color);
+                });
+            },
+            RenderValue::Bracket{x, y, height, side, kind} =>
             {
-                cursor.follow = None;
-                self.move_left_inner(cursor);
-            }
-        } else
-        {
-            if let Some(index) = cursor.index.checked_sub(1)
+                let points = kind.points(*side, layout.bracket_width(), *height);
+
+                points.windows(2).for_each(|segment|
+                {
+                    let ((x1, y1), (x2, y2)) = (segment[0], segment[1]);
+                    let line = RenderValue::Line{
+                        x1: x + x1,
+                        y1: y + y1,
+                        x2: x + x2,
+                        y2: y + y2,
+                        thickness: layout.line_thickness as f32
+                    };
+
+                    draw_value(canvas, fonts, creator, layout, &line, color);
+                });
+            },
+            RenderValue::Ghost(value) =>
             {
-                cursor.index = index;
-            } else
+                draw_value(canvas, fonts, creator, layout, value, Color::RGB(170, 170, 170));
+            },
+            RenderValue::Colored{color: [r, g, b], value} =>
             {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn step_in(&self, cursor: &mut ValueCursor, right: bool) -> bool
-    {
-        self.traverse(cursor, |this, cursor|
-        {
-            if let Some(index) = cursor.index.checked_sub(1)
+                draw_value(canvas, fonts, creator, layout, value, Color::RGB(*r, *g, *b));
+            },
+            RenderValue::Scaled{scale, value} =>
             {
-                match &this.0[index]
+                if let RenderValue::Text{x, y, text: value} = &**value
                 {
-                    InputValue::Fraction{top, ..} =>
-                    {
-                        let index = if right { top.0.len() } else { 0 };
-                        let new_cursor = ValueCursor{index, ..Default::default()};
+                    let text = fonts.for_text(value).render(value).blended(color).unwrap();
+                    let texture = Texture::from_surface(&text, creator).unwrap();
 
-                        cursor.follow = Some((CursorFollow::Top, Box::new(new_cursor)));
+                    // the texture gets squeezed into a smaller rect instead of rendering a smaller font
+                    let size = |value: u32| (value as f32 * scale).round() as u32;
 
-                        return true;
-                    },
-                    InputValue::Value(_) => ()
+                    let rect = Rect::new(round(*x), round(*y), size(text.width()), size(text.height()));
+                    canvas.copy(&texture, None, rect).unwrap();
+                } else
+                {
+                    draw_value(canvas, fonts, creator, layout, value, color);
                 }
             }
-
-            false
-        })
-    }
-
-    pub fn move_left(&self, cursor: &mut ValueCursor)
-    {
-        if !self.step_in(cursor, true)
-        {
-            self.move_left_inner(cursor);
         }
     }
 
-    pub fn move_right(&self, cursor: &mut ValueCursor)
+    // copies the area around the cursor out of the finished frame and draws it scaled up next to it
+    fn draw_loupe(
+        canvas: &mut WindowCanvas,
+        creator: &TextureCreator<WindowContext>,
+        font_size: u32,
+        (cursor_x, cursor_y): (i32, i32)
+    )
     {
-        if !self.move_right_inner(cursor)
-        {
-            self.step_in(cursor, false);
-        }
-    }
+        let (width, height) = canvas.window().size();
 
-    fn move_vertical(
-        &self,
-        cursor: &mut ValueCursor,
-        which: CursorFollow
-    ) -> bool
-    {
-        if let Some((direction, follow)) = cursor.follow.as_mut()
-        {
-            let this = &self.0[cursor.index - 1];
+        let source_size = LOUPE_SIZE / LOUPE_ZOOM;
+        let center_y = cursor_y + font_size as i32 / 2;
 
-            if follow.follow.is_none()
-            {
-                if *direction == which
-                {
-                    *direction = which.opposite();
+        let clamp = |value: i32, size: u32, limit: u32| value.min(limit as i32 - size as i32).max(0);
 
-                    if let InputValue::Fraction{top, bottom} = this
-                    {
-                        let (a, b) = if which == CursorFollow::Top
-                        {
-                            (top.0.len(), bottom.0.len())
-                        } else
-                        {
-                            (bottom.0.len(), top.0.len())
-                        };
+        let source = Rect::new(
+            clamp(cursor_x - source_size as i32 / 2, source_size, width),
+            clamp(center_y - source_size as i32 / 2, source_size, height),
+            source_size.min(width),
+            source_size.min(height)
+        );
 
-                        let diff = a as i32 - b as i32;
-                        let half_diff = diff / 2;
+        let Ok(mut pixels) = canvas.read_pixels(source, PixelFormatEnum::RGB24) else { return };
 
-                        let limit = b as i32;
-                        follow.index = (follow.index as i32 - half_diff).clamp(0, limit) as usize;
-                    } else
-                    {
-                        unreachable!()
-                    }
+        let pitch = source.width() * 3;
+        let surface = Surface::from_data(&mut pixels, source.width(), source.height(), pitch, PixelFormatEnum::RGB24)
+            .unwrap();
 
-                    return true;
-                }
+        let texture = Texture::from_surface(&surface, creator).unwrap();
 
-                false
-            } else
-            {
-                match (this, direction)
-                {
-                    (InputValue::Fraction{top, ..}, CursorFollow::Top) =>
-                    {
-                        top.move_down(&mut **follow)
-                    },
-                    (InputValue::Fraction{bottom, ..}, CursorFollow::Bottom) =>
-                    {
-                        bottom.move_down(&mut **follow)
-                    },
-                    (InputValue::Value(_), _) => unreachable!()
-                }
-            }
+        // goes under the cursor unless that would put it off the window
+        let below = cursor_y + font_size as i32 * 2;
+        let y = if below + LOUPE_SIZE as i32 <= height as i32
+        {
+            below
         } else
         {
-            false
-        }
-    }
+            cursor_y - font_size as i32 - LOUPE_SIZE as i32
+        };
 
-    pub fn move_up(&self, cursor: &mut ValueCursor) -> bool
-    {
-        self.move_vertical(cursor, CursorFollow::Bottom)
-    }
+        let panel = Rect::new(
+            clamp(cursor_x - LOUPE_SIZE as i32 / 2, LOUPE_SIZE, width),
+            clamp(y, LOUPE_SIZE, height),
+            source.width() * LOUPE_ZOOM,
+            source.height() * LOUPE_ZOOM
+        );
 
-    pub fn move_down(&self, cursor: &mut ValueCursor) -> bool
-    {
-        self.move_vertical(cursor, CursorFollow::Top)
+        canvas.copy(&texture, None, panel).unwrap();
+
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.draw_rect(panel).unwrap();
     }
 
-    pub fn render(
-        &self,
-        cursor: Option<&ValueCursor>,
-        x: i32,
-        y: i32,
-        f: &impl Fn(RenderValue) -> RenderResult
-    ) -> RenderResult
+    // the whole document without the cursor, not just what fits in the window
+    fn export_png(
+        state: &ProgramState,
+        fonts: &Fonts,
+        path: &str,
+        transparent: bool,
+        border: ImageBorder
+    ) -> Result<(), String>
     {
-        let mut start = RenderResult::empty(RenderRect{x, y, width: 0, height: 0});
+        let (content_width, content_height) = state.fit_size(fonts);
 
-        if let Some(ValueCursor{index: 0, follow: None}) = cursor
-        {
-            start = start.combine(f(RenderValue::new_cursor(x, y + FONT_SIZE as i32 / 2)));
-        }
+        let image = border.image_size(content_width as f32, content_height as f32);
+        let (width, height) = (image.0.ceil() as u32, image.1.ceil() as u32);
 
-        self.0.iter().enumerate().fold(start, |acc, (index, value)|
-        {
-            let this_index = Some(index + 1) == cursor.map(|x| x.index);
-            let cursor = cursor.and_then(|cursor|
-            {
-                this_index.then(|| { cursor.follow.as_ref() }).flatten()
-            });
+        let surface = Surface::new(width, height, PixelFormatEnum::RGBA32)?;
+        let mut canvas = surface.into_canvas()?;
+        let creator = canvas.texture_creator();
 
-            let render = value.render(cursor, x + acc.rect.width as i32, y, f);
-            let rect = render.rect;
+        // text gets blended onto the background so a transparent one has to be black to keep the edges dark
+        let background = if transparent { Color::RGBA(0, 0, 0, 0) } else { Color::RGB(255, 255, 255) };
 
-            let mut combined = acc.combine(render);
-            if this_index && cursor.is_none()
-            {
-                combined = combined.combine(f(RenderValue::new_cursor_rect(rect)));
-            }
+        canvas.set_draw_color(background);
+        canvas.clear();
 
-            combined
-        })
-    }
-}
+        // the text goes on an opaque card so the shadow only shows around it
+        if border.shadow
+        {
+            let (card_width, card_height) = border.card_size(content_width as f32, content_height as f32);
+            let (card_width, card_height) = (card_width.ceil() as u32, card_height.ceil() as u32);
+            let offset = border.shadow_offset().round() as i32;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum CursorFollow
-{
-    Top,
-    Bottom
-}
+            let [r, g, b, a] = ImageBorder::SHADOW_COLOR;
 
-impl CursorFollow
-{
-    pub fn opposite(self) -> Self
-    {
-        match self
-        {
-            Self::Top => Self::Bottom,
-            Self::Bottom => Self::Top
+            canvas.set_blend_mode(BlendMode::Blend);
+            canvas.set_draw_color(Color::RGBA(r, g, b, a));
+            canvas.fill_rect(Rect::new(offset, offset, card_width, card_height))?;
+
+            canvas.set_draw_color(Color::RGB(255, 255, 255));
+            canvas.fill_rect(Rect::new(0, 0, card_width, card_height))?;
         }
-    }
-}
 
-#[derive(Debug)]
-struct ValueCursor
-{
-    index: usize,
-    follow: Option<(CursorFollow, Box<ValueCursor>)>
-}
+        // everything after this is drawn relative to the inside of the padding
+        let padding = border.padding.round() as i32;
+        canvas.set_viewport(Rect::new(padding, padding, content_width, content_height));
 
-impl Default for ValueCursor
-{
-    fn default() -> Self
-    {
-        Self{index: 0, follow: None}
+        state.render(fonts, content_width, content_height, |_| {}, |render|
+        {
+            if !matches!(render, RenderValue::Cursor{..})
+            {
+                draw_value(&mut canvas, fonts, &creator, state.layout(), render, Color::RGB(0, 0, 0));
+            }
+        });
+
+        write_png(&canvas.into_surface(), path)
     }
-}
 
-impl ValueCursor
-{
-    pub fn add_fraction(&mut self)
+    // the part of the window inside of the region the way its drawn apart from the cursor
+    fn export_region(
+        state: &ProgramState,
+        fonts: &Fonts,
+        (width, height): (u32, u32),
+        region: Rect,
+        path: &str
+    ) -> Result<(), String>
     {
-        if let Some((_, follow)) = self.follow.as_mut()
-        {
-            follow.add_fraction();
-        } else
+        let surface = Surface::new(width, height, PixelFormatEnum::RGBA32)?;
+        let mut canvas = surface.into_canvas()?;
+        let creator = canvas.texture_creator();
+
+        canvas.set_draw_color(Color::RGB(255, 255, 255));
+        canvas.clear();
+
+        state.render(fonts, width, height, |_| {}, |render|
         {
-            if self.index != 0
+            if !matches!(render, RenderValue::Cursor{..})
             {
-                self.follow = Some((CursorFollow::Bottom, Box::new(Self::default())));
+                draw_value(&mut canvas, fonts, &creator, state.layout(), render, Color::RGB(0, 0, 0));
             }
-        }
+        });
+
+        let whole = canvas.into_surface();
+
+        let mut cropped = Surface::new(region.width(), region.height(), PixelFormatEnum::RGBA32)?;
+        whole.blit(region, &mut cropped, None)?;
+
+        write_png(&cropped, path)
     }
 
-    pub fn added(&mut self)
+    fn write_png(surface: &Surface, path: &str) -> Result<(), String>
     {
-        if let Some((_direction, follow)) = self.follow.as_mut()
-        {
-            follow.added();
-        } else
-        {
-            self.index += 1;
-        }
-    }
-}
+        let (width, height) = surface.size();
 
-struct Cursor
-{
-    line: usize,
-    value: ValueCursor
-}
+        // rows in the surface can be padded past the pixels
+        let row = width as usize * 4;
+        let pitch = surface.pitch() as usize;
+        let rgba: Vec<u8> = surface.with_lock(|pixels|
+        {
+            pixels.chunks(pitch).flat_map(|pixels| &pixels[..row]).copied().collect()
+        });
 
-struct ProgramState<'a>
-{
-    font: Font<'a, 'static>,
-    cursor: Cursor,
-    lines: Vec<InputValues>
-}
+        fs::write(path, png::encode(width, height, &rgba)).map_err(|err| err.to_string())
+    }
 
-impl<'a> ProgramState<'a>
-{
-    pub fn new(font: Font<'a, 'static>) -> Self
+    // the rectangle between where a region drag started and where it is now
+    fn region_rect(((start_x, start_y), (end_x, end_y)): ((i32, i32), (i32, i32))) -> Rect
     {
-        Self{
-            font,
-            cursor: Cursor{line: 0, value: ValueCursor::default()},
-            lines: vec![InputValues::default()]
-        }
+        let width = start_x.abs_diff(end_x);
+        let height = start_y.abs_diff(end_y);
+
+        Rect::new(start_x.min(end_x), start_y.min(end_y), width.max(1), height.max(1))
     }
 
-    pub fn add_text(&mut self, text: String)
+    fn redraw_window(
+        state: &ProgramState,
+        fonts: &Fonts,
+        creator: &TextureCreator<WindowContext>,
+        canvas: &mut WindowCanvas,
+        last_frame: &mut Option<u64>,
+        snap: bool,
+        overlays: Overlays
+    )
     {
-        match text.as_ref()
+        if snap
         {
-            "/" => self.add_fraction(),
-            _ => self.add_normal(text)
+            let (width, height) = state.fit_size(fonts);
+
+            // never bigger than the display its on
+            let window = canvas.window();
+            let bounds = window.display_index().and_then(|index|
+            {
+                window.subsystem().display_usable_bounds(index)
+            });
+
+            let (width, height) = match bounds
+            {
+                Ok(bounds) => (width.min(bounds.width()), height.min(bounds.height())),
+                Err(_) => (width, height)
+            };
+
+            if canvas.window().size() != (width, height)
+            {
+                canvas.window_mut().set_size(width, height).unwrap();
+            }
         }
-    }
 
-    pub fn new_line(&mut self)
-    {
-        if self.cursor.value.follow.is_some()
+        let (width, height) = canvas.window().size();
+
+        // focus changes and repeated inputs redraw a lot without anything changing,
+        // so anything else the frame depends on goes in the hash too
+        let mut hasher = DefaultHasher::new();
+        (state.frame_hash(width, height), &fonts.settings, overlays).hash(&mut hasher);
+
+        let hash = hasher.finish();
+        if *last_frame == Some(hash)
         {
             return;
         }
 
-        let rest = self.lines[self.cursor.line].0.split_off(self.cursor.value.index);
+        *last_frame = Some(hash);
 
-        self.cursor.line += 1;
-        self.cursor.value = ValueCursor::default();
+        canvas.set_draw_color(Color::RGB(255, 255, 255));
+        canvas.clear();
 
-        self.lines.insert(self.cursor.line, InputValues(rest));
-    }
+        let canvas = RefCell::new(canvas);
 
-    fn add_normal(&mut self, text: String)
-    {
-        self.lines[self.cursor.line].add_text(&self.cursor.value, text);
-        self.cursor.value.added();
-    }
+        let mut cursor = None;
 
-    fn add_fraction(&mut self)
-    {
-        self.lines[self.cursor.line].add_fraction(&self.cursor.value);
-        self.cursor.value.add_fraction();
-    }
+        state.render(fonts, width, height, |rect|
+        {
+            canvas.borrow_mut().set_draw_color(Color::RGB(200, 200, 200));
 
-    pub fn remove_single(&mut self)
-    {
-        if self.cursor.value.follow.is_none() && self.cursor.value.index == 0
+            canvas.borrow_mut().fill_rect(Rect::from(rect)).unwrap();
+        }, |render|
         {
-            if self.lines.len() == 1
+            if let RenderValue::Cursor{x, y, ..} = render
             {
-                return;
+                cursor = Some((x.round() as i32, y.round() as i32));
             }
 
-            let previous = self.lines.remove(self.cursor.line);
-
-            self.cursor.line -= 1;
+            draw_value(&mut canvas.borrow_mut(), fonts, creator, state.layout(), render, Color::RGB(0, 0, 0));
+        });
 
-            self.cursor.value.follow = None;
-            self.cursor.value.index = self.lines[self.cursor.line].0.len();
+        let canvas = canvas.into_inner();
 
-            self.lines[self.cursor.line].0.extend(previous.0);
-        } else
+        if let Some(region) = overlays.region
         {
-            self.lines[self.cursor.line].remove_single(&mut self.cursor.value);
+            canvas.set_draw_color(Color::RGB(30, 90, 220));
+            canvas.draw_rect(region_rect(region)).unwrap();
         }
-    }
-
-    pub fn remove_next_single(&mut self)
-    {
-        let line_length = self.lines[self.cursor.line].0.len();
-        if self.cursor.value.follow.is_none() && self.cursor.value.index == line_length
-        {
-            if self.lines.len() - 1 > self.cursor.line
-            {
-                let line = self.lines.remove(self.cursor.line + 1);
 
-                self.lines[self.cursor.line].0.extend(line.0);
-            }
-        } else
+        if let Some(cursor) = cursor.filter(|_| overlays.loupe)
         {
-            self.move_right();
-            self.remove_single();
+            draw_loupe(canvas, creator, state.font_size(), cursor);
         }
-    }
 
-    pub fn move_left(&mut self)
-    {
-        self.lines[self.cursor.line].move_left(&mut self.cursor.value);
+        canvas.present();
     }
 
-    pub fn move_right(&mut self)
+    let ttf_ctx = sdl2::ttf::init().unwrap();
+    // fonts only come in the size they were loaded at so zooming loads them again
+    let load_fonts = |size: u32|
     {
-        self.lines[self.cursor.line].move_right(&mut self.cursor.value);
-    }
+        let load_font = |path: &str| ttf_ctx.load_font(path, size as u16).unwrap();
 
-    fn truncate_index(&mut self)
-    {
-        self.cursor.value.index = self.cursor.value.index.min(self.lines[self.cursor.line].0.len());
-    }
+        Fonts{text: load_font(&args.text_font), math: load_font(&args.math_font), settings: FontSettings::default()}
+    };
 
-    pub fn move_up(&mut self)
-    {
-        if !self.lines[self.cursor.line].move_up(&mut self.cursor.value)
-        {
-            if self.cursor.value.follow.is_none() && self.cursor.line > 0
-            {
-                self.cursor.line -= 1;
-                self.truncate_index();
-            }
-        }
-    }
+    let mut fonts = load_fonts(FONT_SIZE);
 
-    pub fn move_down(&mut self)
+    let mut font_settings = args.font_settings.clone();
+    fonts.apply_settings(&font_settings);
+
+    let mut state = ProgramState::new();
+    state.set_fraction_entry(args.fraction_entry);
+    state.set_delete_structure(args.delete_structure);
+    state.set_decimal_separator(args.decimal_separator);
+    state.set_vertical_centering(args.vertical_centering);
+    state.set_wrap_width(args.wrap_width.map(|width| width as f32));
+    state.set_scripts(args.script_scale, args.superscript_shift, args.subscript_shift);
+
+    if let Some(precision) = args.precision
     {
-        if !self.lines[self.cursor.line].move_down(&mut self.cursor.value)
-        {
-            if self.cursor.value.follow.is_none() && self.cursor.line < self.lines.len() - 1
-            {
-                self.cursor.line += 1;
-                self.truncate_index();
-            }
-        }
+        state.set_precision(precision);
     }
 
-    pub fn render(
-        &self,
-        width: u32,
-        height: u32,
-        _highlight: impl FnMut(Rect),
-        f: impl Fn(RenderValue) -> RenderResult,
-        renderer: impl FnMut(&RenderValue)
-    )
-    {
-        let start = RenderRect::empty();
-        let mut render = self.lines.iter().enumerate()
-            .fold(RenderResult::empty(start), |acc, (index, line)|
-            {
-                let cursor = (self.cursor.line == index).then_some(&self.cursor.value);
+    let mut last_frame = None;
 
-                let y = acc.rect.y + acc.rect.height as i32;
-                let mut rendered = line.render(cursor, 0, y, &f);
+    // keeps the window sized to fit the content
+    let mut snap = false;
 
-                let diff = y - rendered.rect.y;
+    // the loupe and region selection
+    let mut overlays = Overlays::default();
 
-                rendered.shift(0, diff);
+    // key being held down and when it was first pressed
+    let mut held: Option<(Keycode, Instant)> = None;
 
-                acc.combine(rendered)
-            });
+    // set for one more frame after an animation ends so its final state gets drawn
+    let mut animated = false;
+
+    // when animations were last moved forward
+    let mut last_tick = Instant::now();
+
+    loop
+    {
+        let animating = state.animating();
 
-        let center = |size, start, other_size|
+        // animations need frames even when nothing is happening
+        let event = if animating || animated
+        {
+            events.wait_event_timeout(ANIMATION_FRAME_MS)
+        } else
         {
-            start + (size as i32 - other_size as i32) / 2
+            Some(events.wait_event())
         };
 
-        let x = center(width, render.rect.x, render.rect.width);
-        let y = center(height, render.rect.y, render.rect.height);
+        animated = animating;
 
-        render.shift(x, y);
+        let now = Instant::now();
+        state.tick((now - last_tick).as_secs_f32());
+        last_tick = now;
 
-        if render.rect.y < 0
+        let Some(event) = event
+        else
         {
-            render.shift(0, render.rect.y);
-        }
+            redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+            continue;
+        };
 
-        if render.rect.x < 0
+        match event
         {
-            render.shift(render.rect.x, 0);
-        }
-
-        render.render(renderer);
-    }
-}
+            Event::Quit{..} =>
+            {
+                let window = canvas.window();
+                let (width, height) = window.size();
 
-fn main()
-{
-    let ctx = sdl2::init().unwrap();
+                WindowGeometry{position: Some(window.position()), width, height}.save();
 
-    let video = ctx.video().unwrap();
+                return;
+            },
+            Event::TextInput{text, ..} =>
+            {
+                if !state.text_input(text)
+                {
+                    continue;
+                }
 
-    let window = video.window("lil fun algebra thing", 640, 480)
-        .resizable()
-        .build()
-        .unwrap();
+                redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+            },
+            Event::MouseButtonDown{mouse_btn: MouseButton::Left, x, y, ..}
+                if ctx.keyboard().mod_state().intersects(Mod::LALTMOD | Mod::RALTMOD) =>
+            {
+                overlays.region = Some(((x, y), (x, y)));
+            },
+            Event::MouseMotion{x, y, ..} if overlays.region.is_some() =>
+            {
+                overlays.region = overlays.region.map(|(start, _)| (start, (x, y)));
 
-    let mut canvas = window.into_canvas().build().unwrap();
-    let creator = canvas.texture_creator();
+                redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+            },
+            Event::MouseButtonUp{mouse_btn: MouseButton::Left, x, y, ..} if overlays.region.is_some() =>
+            {
+                let (start, _) = overlays.region.take().unwrap();
 
-    let mut events = ctx.event_pump().unwrap();
+                let size = canvas.window().size();
+                let window = Rect::new(0, 0, size.0, size.1);
 
-    fn redraw_window(
-        state: &ProgramState,
-        creator: &TextureCreator<WindowContext>,
-        canvas: &mut WindowCanvas
-    )
-    {
-        canvas.set_draw_color(Color::RGB(255, 255, 255));
-        canvas.clear();
+                if let Some(rect) = region_rect((start, (x, y))).intersection(window)
+                {
+                    match export_region(&state, &fonts, size, rect, REGION_EXPORT_PATH)
+                    {
+                        Ok(()) => eprintln!("saved {REGION_EXPORT_PATH}"),
+                        Err(err) => eprintln!("cant export region: {err}")
+                    }
+                }
 
-        let (width, height) = canvas.window().size();
+                redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+            },
+            Event::MouseButtonDown{mouse_btn: MouseButton::Left, x, y, ..} =>
+            {
+                let (width, height) = canvas.window().size();
 
-        let canvas = RefCell::new(canvas);
+                // a click moves the cursor and holding it down afterwards can still drag a structure
+                let moved = state.cursor_at(&fonts, width, height, x as f32, y as f32);
+                state.drag_start(&fonts, width, height, x as f32, y as f32);
 
-        state.render(width, height, |rect|
-        {
-            canvas.borrow_mut().set_draw_color(Color::RGB(200, 200, 200));
+                if !moved
+                {
+                    continue;
+                }
 
-            canvas.borrow_mut().fill_rect(rect).unwrap();
-        }, |render|
-        {
-            let rect = match render
+                redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+            },
+            Event::MouseButtonUp{mouse_btn: MouseButton::Left, x, y, ..} =>
             {
-                RenderValue::Text{x, y, text: value} =>
-                {
-                    let text = state.font.render(value).blended(Color::RGB(0, 0, 0)).unwrap();
+                let (width, height) = canvas.window().size();
 
-                    Rect::new(x, y, text.width(), text.height())
-                },
-                RenderValue::Line{x, y, width} =>
+                if !state.drag_end(&fonts, width, height, x as f32, y as f32)
                 {
-                    let height = 2;
-                    Rect::new(x, y - height as i32 / 2, width, height)
-                },
-                RenderValue::Cursor{x, y} =>
-                {
-                    Rect::new(x, y, 0, 0)
+                    continue;
                 }
-            };
-
-            RenderResult::new(rect.into(), render)
-        }, |render|
-        {
-            canvas.borrow_mut().set_draw_color(Color::RGB(0, 0, 0));
 
-            match render
+                redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+            },
+            Event::KeyUp{keycode: Some(key), ..} if held.is_some_and(|(held_key, _)| held_key == key) =>
+            {
+                held = None;
+            },
+            Event::KeyDown{keycode: Some(key), keymod, repeat, ..} =>
             {
-                RenderValue::Text{x, y, text: value} =>
+                let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+                let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+                let alt = keymod.intersects(Mod::LALTMOD | Mod::RALTMOD);
+
+                // exporting needs the clipboard so it stays in the frontend, ctrl+alt+l is the typst one
+                let typst = key == Keycode::L && ctrl && alt;
+                if typst || ((key == Keycode::L || key == Keycode::P) && ctrl && shift)
                 {
-                    let text = state.font.render(value).blended(Color::RGB(0, 0, 0)).unwrap();
-                    let texture = Texture::from_surface(&text, creator).unwrap();
+                    let format = match key
+                    {
+                        _ if typst => ExportFormat::Typst,
+                        Keycode::L => ExportFormat::Latex,
+                        _ => ExportFormat::Plain
+                    };
 
-                    let rect = Rect::new(*x, *y, text.width(), text.height());
-                    canvas.borrow_mut().copy(&texture, None, rect).unwrap();
-                },
-                RenderValue::Line{x, y, width} =>
+                    let (text, errors) = state.export_results(format);
+
+                    errors.into_iter().for_each(|(line, error)|
+                    {
+                        eprintln!("left out line {}: {error}", line + 1);
+                    });
+
+                    clipboard.set_clipboard_text(&text).unwrap();
+
+                    continue;
+                }
+
+                let count = match held
                 {
-                    let height = 2;
-                    let rect = Rect::new(*x, y - height as i32 / 2, *width, height);
-                    canvas.borrow_mut().fill_rect(rect).unwrap();
-                },
-                RenderValue::Cursor{x, y} =>
+                    Some((held_key, start)) if repeat && held_key == key => repeat_count(start.elapsed()),
+                    _ =>
+                    {
+                        held = Some((key, Instant::now()));
+
+                        1
+                    }
+                };
+
+                // ctrl+alt+e leaves out the white background
+                if key == Keycode::E && ctrl && !shift
                 {
-                    let cursor_height = FONT_SIZE;
-                    canvas.borrow_mut().fill_rect(Rect::new(
-                        *x,
-                        *y,
-                        4,
-                        cursor_height
-                    )).unwrap();
+                    match export_png(&state, &fonts, IMAGE_EXPORT_PATH, alt, args.image_border)
+                    {
+                        Ok(()) => eprintln!("saved {IMAGE_EXPORT_PATH}"),
+                        Err(err) => eprintln!("cant export image: {err}")
+                    }
+
+                    continue;
                 }
-            }
-        });
 
-        canvas.borrow_mut().present();
-    }
+                if key == Keycode::S && ctrl && alt
+                {
+                    let family = fonts.text.face_family_name().unwrap_or_else(|| "monospace".to_owned());
+                    clipboard.set_clipboard_text(&state.to_svg(&fonts, &family, args.image_border)).unwrap();
 
-    let ttf_ctx = sdl2::ttf::init().unwrap();
-    let font = ttf_ctx.load_font("font/LiberationMono-Regular.ttf", FONT_SIZE as u16).unwrap();
+                    continue;
+                }
 
-    let mut state = ProgramState::new(font);
+                // share codes go through the clipboard too
+                if key == Keycode::S && ctrl && shift
+                {
+                    clipboard.set_clipboard_text(&state.to_compact()).unwrap();
 
-    for event in events.wait_iter()
-    {
-        match event
-        {
-            Event::Quit{..} => return,
-            Event::TextInput{text, ..} =>
-            {
-                state.add_text(text);
-                redraw_window(&state, &creator, &mut canvas);
-            },
-            Event::KeyDown{keycode: Some(key), ..} =>
-            {
-                match key
+                    continue;
+                }
+
+                if key == Keycode::O && ctrl && shift
                 {
-                    Keycode::BACKSPACE =>
+                    let text = clipboard.clipboard_text().unwrap_or_default();
+
+                    if let Err(err) = state.load_compact(&text)
                     {
-                        state.remove_single();
-                    },
-                    Keycode::DELETE =>
+                        eprintln!("cant load shared document: {err}");
+                        continue;
+                    }
+
+                    redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+
+                    continue;
+                }
+
+                if (key == Keycode::H || key == Keycode::K) && ctrl && shift
+                {
+                    if key == Keycode::H
                     {
-                        state.remove_next_single();
-                    },
-                    Keycode::RETURN =>
+                        font_settings.next_hinting();
+                    } else
                     {
-                        state.new_line();
-                    },
-                    Keycode::LEFT =>
+                        font_settings.kerning = !font_settings.kerning;
+                    }
+
+                    fonts.apply_settings(&font_settings);
+
+                    redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+
+                    continue;
+                }
+
+                if key == Keycode::C && ctrl && !shift
+                {
+                    if let Some(text) = state.copy_selection()
                     {
-                        state.move_left();
-                    },
-                    Keycode::RIGHT =>
+                        clipboard.set_clipboard_text(&text).unwrap();
+                    }
+
+                    continue;
+                }
+
+                // with shift two lines make a fraction
+                if key == Keycode::V && ctrl
+                {
+                    let text = clipboard.clipboard_text().unwrap_or_default();
+
+                    if shift
                     {
-                        state.move_right();
-                    },
-                    Keycode::UP =>
+                        state.paste_fraction(&text);
+                    } else
                     {
-                        state.move_up();
-                    },
-                    Keycode::DOWN =>
+                        state.paste(&text);
+                    }
+
+                    redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+
+                    continue;
+                }
+
+                if key == Keycode::W && ctrl && shift
+                {
+                    snap = !snap;
+
+                    redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+
+                    continue;
+                }
+
+                // ctrl+= already adds a sum so zooming in needs alt too, ctrl with + or - on the keypad works too
+                let zoom = match key
+                {
+                    Keycode::EQUALS if ctrl && alt => Some(1),
+                    Keycode::MINUS if ctrl => Some(-1),
+                    Keycode::KP_PLUS if ctrl => Some(1),
+                    Keycode::KP_MINUS if ctrl => Some(-1),
+                    _ => None
+                };
+
+                if let Some(direction) = zoom
+                {
+                    let size = state.font_size();
+                    state.set_font_size(if direction > 0 { size + 2 } else { size.saturating_sub(2) });
+
+                    if state.font_size() != size
                     {
-                        state.move_down();
-                    },
-                    _ => continue
+                        fonts = load_fonts(state.font_size());
+                        fonts.apply_settings(&font_settings);
+
+                        redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+                    }
+
+                    continue;
+                }
+
+                if key == Keycode::Z && ctrl && shift
+                {
+                    overlays.loupe = !overlays.loupe;
+
+                    redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
+
+                    continue;
+                }
+
+                // read out for screen readers
+                if key == Keycode::G && ctrl && shift
+                {
+                    let speech = state.to_speech();
+
+                    println!("{speech}");
+                    clipboard.set_clipboard_text(&speech).unwrap();
+
+                    continue;
+                }
+
+                if key == Keycode::D && ctrl && shift
+                {
+                    print!("{}", state.dump());
+
+                    continue;
+                }
+
+                let Some(key) = key_from_keycode(key) else { continue };
+
+                // only movement and deleting speed up, repeating anything else would be surprising
+                let count = match key
+                {
+                    Key::Left | Key::Right | Key::Up | Key::Down | Key::Backspace | Key::Delete => count,
+                    _ => 1
+                };
+
+                let changed = (0..count).filter(|_| state.key_pressed(key, Modifiers{ctrl, shift})).count();
+                if changed == 0
+                {
+                    continue;
                 }
 
-                redraw_window(&state, &creator, &mut canvas);
+                redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
             },
             Event::Window{win_event, ..} =>
             {
                 match win_event
                 {
+                    // the windows contents got damaged so it has to be drawn again even if nothing changed
                     WindowEvent::Exposed =>
                     {
-                        redraw_window(&state, &creator, &mut canvas);
+                        last_frame = None;
+                        redraw_window(&state, &fonts, &creator, &mut canvas, &mut last_frame, snap, overlays);
                     },
                     _ => ()
                 }
@@ -0,0 +1,96 @@
+// just enough png to save renders without an image library, the pixels get stored uncompressed
+
+
+// deflate stored blocks cant be longer than this
+const MAX_BLOCK: usize = 0xffff;
+
+fn crc32(bytes: &[u8]) -> u32
+{
+    let crc = bytes.iter().fold(0xffffffff_u32, |crc, byte|
+    {
+        (0..8).fold(crc ^ *byte as u32, |crc, _|
+        {
+            if crc & 1 == 1 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 }
+        })
+    });
+
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32
+{
+    let (a, b) = bytes.iter().fold((1_u32, 0_u32), |(a, b), byte|
+    {
+        let a = (a + *byte as u32) % 65521;
+
+        (a, (b + a) % 65521)
+    });
+
+    (b << 16) | a
+}
+
+fn push_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8])
+{
+    output.extend((data.len() as u32).to_be_bytes());
+
+    let start = output.len();
+    output.extend(kind);
+    output.extend(data);
+
+    let crc = crc32(&output[start..]);
+    output.extend(crc.to_be_bytes());
+}
+
+// a zlib stream of stored blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8>
+{
+    let mut output = vec![0x78, 0x01];
+
+    let blocks = data.len().div_ceil(MAX_BLOCK).max(1);
+    (0..blocks).for_each(|index|
+    {
+        let block = &data[index * MAX_BLOCK..((index + 1) * MAX_BLOCK).min(data.len())];
+        let last = index + 1 == blocks;
+
+        let length = block.len() as u16;
+
+        output.push(last as u8);
+        output.extend(length.to_le_bytes());
+        output.extend((!length).to_le_bytes());
+        output.extend(block);
+    });
+
+    output.extend(adler32(data).to_be_bytes());
+
+    output
+}
+
+// rgba is 4 bytes per pixel going row by row without any padding
+pub fn encode(width: u32, height: u32, rgba: &[u8]) -> Vec<u8>
+{
+    let row = width as usize * 4;
+    assert_eq!(rgba.len(), row * height as usize);
+
+    let mut output = b"\x89PNG\r\n\x1a\n".to_vec();
+
+    let mut header = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits per channel, rgba, default compression, filtering and no interlacing
+    header.extend([8, 6, 0, 0, 0]);
+
+    push_chunk(&mut output, b"IHDR", &header);
+
+    // every row starts with its filter type, 0 leaves it as is
+    let mut raw = Vec::with_capacity((row + 1) * height as usize);
+    rgba.chunks(row.max(1)).take(height as usize).for_each(|pixels|
+    {
+        raw.push(0);
+        raw.extend(pixels);
+    });
+
+    push_chunk(&mut output, b"IDAT", &zlib_stored(&raw));
+    push_chunk(&mut output, b"IEND", &[]);
+
+    output
+}
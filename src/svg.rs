@@ -0,0 +1,241 @@
+// vector version of what gets drawn, for pasting into documents
+use std::fmt::Write;
+
+use crate::{ProgramState, RenderValue, RenderRect, LayoutConfig, TextMeasure, export::{format_number, ImageBorder}};
+
+
+// distance from the top of a line of text to its baseline as a fraction of the font size
+const BASELINE: f32 = 0.8;
+
+fn number(value: f32) -> String
+{
+    format_number(value as f64, 2, '.')
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String
+{
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn escape(text: &str) -> String
+{
+    text.chars().map(|c|
+    {
+        match c
+        {
+            '&' => "&amp;".to_owned(),
+            '<' => "&lt;".to_owned(),
+            '>' => "&gt;".to_owned(),
+            c => c.to_string()
+        }
+    }).collect()
+}
+
+struct Svg<'a, M>
+{
+    layout: &'a LayoutConfig,
+    measurer: &'a M,
+    body: String,
+    // everything drawn so far, the view box ends up being this
+    bounds: Option<RenderRect>
+}
+
+impl<M: TextMeasure> Svg<'_, M>
+{
+    fn include(&mut self, rect: RenderRect)
+    {
+        self.bounds = Some(self.bounds.map_or(rect, |bounds| bounds.combine(rect)));
+    }
+
+    fn include_points(&mut self, points: &[(f32, f32)], thickness: f32)
+    {
+        let half = thickness / 2.0;
+
+        points.iter().for_each(|(x, y)|
+        {
+            self.include(RenderRect{x: x - half, y: y - half, width: thickness, height: thickness});
+        });
+    }
+
+    fn text(&mut self, x: f32, y: f32, text: &str, scale: f32, color: [u8; 3])
+    {
+        let size = self.layout.font_size as f32 * scale;
+
+        let (width, height) = self.measurer.text_size(text);
+        self.include(RenderRect{x, y, width: width as f32 * scale, height: height as f32 * scale});
+
+        writeln!(
+            self.body,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>",
+            number(x),
+            number(y + size * BASELINE),
+            number(size),
+            hex(color),
+            escape(text)
+        ).unwrap();
+    }
+
+    fn polyline(&mut self, points: &[(f32, f32)], thickness: f32, color: [u8; 3])
+    {
+        self.include_points(points, thickness);
+
+        let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", number(*x), number(*y))).collect();
+
+        writeln!(
+            self.body,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+            points.join(" "),
+            hex(color),
+            number(thickness)
+        ).unwrap();
+    }
+
+    // the cursor and anything only there while editing gets left out
+    fn value(&mut self, value: &RenderValue, scale: f32, color: [u8; 3])
+    {
+        match value
+        {
+            RenderValue::Text{x, y, text} => self.text(*x, *y, text, scale, color),
+            RenderValue::Dots{x, y, kind} => self.text(*x, *y, &kind.as_char().to_string(), scale, color),
+            RenderValue::Unit{x, y, kind} => self.text(*x, *y, &kind.as_char().to_string(), scale, color),
+            RenderValue::Line{x1, y1, x2, y2, thickness} =>
+            {
+                self.include_points(&[(*x1, *y1), (*x2, *y2)], *thickness);
+
+                writeln!(
+                    self.body,
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                    number(*x1),
+                    number(*y1),
+                    number(*x2),
+                    number(*y2),
+                    hex(color),
+                    number(*thickness)
+                ).unwrap();
+            },
+            RenderValue::Radical{x, y, width, height, thickness} =>
+            {
+                let points = RenderValue::radical_points(*x, *y, *width, *height);
+
+                self.polyline(&points, *thickness, color);
+            },
+            RenderValue::Bracket{x, y, height, side, kind} =>
+            {
+                let points: Vec<_> = kind.points(*side, self.layout.bracket_width(), *height).into_iter()
+                    .map(|(point_x, point_y)| (x + point_x, y + point_y))
+                    .collect();
+
+                self.polyline(&points, self.layout.line_thickness as f32, color);
+            },
+            RenderValue::Colored{color, value} => self.value(value, scale, *color),
+            RenderValue::Scaled{scale, value} => self.value(value, *scale, color),
+            RenderValue::Ghost(_)
+                | RenderValue::Placeholder{..}
+                | RenderValue::Panel{..}
+                | RenderValue::Cursor{..} => ()
+        }
+    }
+}
+
+impl ProgramState
+{
+    // the whole document with a view box that fits tightly around what gets drawn and the border,
+    // text uses the font family by name
+    pub fn to_svg(&self, measurer: &impl TextMeasure, font_family: &str, border: ImageBorder) -> String
+    {
+        let (width, height) = self.fit_size(measurer);
+
+        let mut svg = Svg{layout: &self.layout, measurer, body: String::new(), bounds: None};
+        let rect = self.render(measurer, width, height, |_| (), |value|
+        {
+            svg.value(value, 1.0, [0, 0, 0]);
+        });
+
+        // results are drawn outside of the documents own rect and empty space around it shouldnt count
+        let rect = svg.bounds.unwrap_or(rect);
+
+        let (x, y) = (rect.x - border.padding, rect.y - border.padding);
+        let (card_width, card_height) = border.card_size(rect.width, rect.height);
+        let (width, height) = border.image_size(rect.width, rect.height);
+
+        // the card has to be filled for the shadow to only show around it
+        let mut body = String::new();
+        if border.shadow
+        {
+            let offset = border.shadow_offset();
+            let [r, g, b, a] = ImageBorder::SHADOW_COLOR;
+
+            let card = |x: f32, y: f32, fill: [u8; 3], opacity: f32|
+            {
+                format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" fill-opacity=\"{}\"/>\n",
+                    number(x),
+                    number(y),
+                    number(card_width),
+                    number(card_height),
+                    hex(fill),
+                    number(opacity)
+                )
+            };
+
+            body += &card(x + offset, y + offset, [r, g, b], a as f32 / 255.0);
+            body += &card(x, y, [255, 255, 255], 1.0);
+        }
+
+        body += &svg.body;
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\" font-family=\"{}\" xml:space=\"preserve\">\n{body}</svg>\n",
+            number(x),
+            number(y),
+            number(width),
+            number(height),
+            number(width),
+            number(height),
+            escape(font_family).replace('"', "&quot;")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::tests::Fixed;
+
+    fn document() -> ProgramState
+    {
+        let mut state = ProgramState::new();
+        state.paste("1+\\frac{a}{b}");
+
+        state
+    }
+
+    #[test]
+    fn tight_view_box()
+    {
+        let svg = document().to_svg(&Fixed, "Mono", ImageBorder::default());
+
+        assert!(svg.contains("viewBox=\"10 10 73 48\""));
+        assert!(svg.contains("<line"));
+        assert!(!svg.contains("<rect"));
+        assert_eq!(svg.matches("<text").count(), 5);
+    }
+
+    #[test]
+    fn border()
+    {
+        let svg = document().to_svg(&Fixed, "Mono", ImageBorder{padding: 5.0, shadow: false});
+        assert!(svg.contains("viewBox=\"5 5 83 58\""));
+        assert!(!svg.contains("<rect"));
+
+        // the shadow is drawn first so the card covers it
+        let svg = document().to_svg(&Fixed, "Mono", ImageBorder{padding: 5.0, shadow: true});
+        assert!(svg.contains("viewBox=\"5 5 89 64\""));
+
+        let shadow = svg.find("<rect x=\"11\" y=\"11\" width=\"83\" height=\"58\"").unwrap();
+        let card = svg.find("<rect x=\"5\" y=\"5\" width=\"83\" height=\"58\" fill=\"#ffffff\"").unwrap();
+        assert!(shadow < card);
+        assert!(card < svg.find("<text").unwrap());
+    }
+}